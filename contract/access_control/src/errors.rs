@@ -9,4 +9,6 @@ pub enum AccessError {
     AlreadyHasRole = 4,
     MissingPermission = 5,
     MultisigNotApproved = 6,
+    MissingPrerequisite = 7,
+    InvalidThreshold = 8,
 }
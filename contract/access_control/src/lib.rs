@@ -29,6 +29,16 @@ impl AccessControl {
         events::role_assigned(&env, user, role);
     }
 
+    pub fn set_role_prerequisite(env: Env, admin: Address, role: Symbol, prerequisite: Option<Symbol>) {
+        admin.require_auth();
+
+        if !has_role(&env, admin.clone(), ROLE_ADMIN) {
+            panic_with_error!(&env, errors::AccessError::Unauthorized);
+        }
+
+        set_prerequisite(&env, role, prerequisite);
+    }
+
     pub fn revoke_role(env: Env, admin: Address, user: Address, role: Symbol) {
         assert_not_paused(&env);
         admin.require_auth();
@@ -37,9 +47,47 @@ impl AccessControl {
         events::role_revoked(&env, user, role);
     }
 
+    /// Approvals every proposal needs; can be set once and never to 0
+    pub fn set_multisig_threshold(env: Env, admin: Address, threshold: u32) {
+        admin.require_auth();
+
+        if !has_role(&env, admin.clone(), ROLE_ADMIN) {
+            panic_with_error!(&env, errors::AccessError::Unauthorized);
+        }
+
+        multisig::set_threshold(&env, threshold);
+    }
+
+    /// Propose `action` against `target` (e.g. `ACTION_TRANSFER_ADMIN` to a new admin)
+    pub fn create_proposal(env: Env, admin: Address, action: Symbol, target: Option<Address>) -> u64 {
+        admin.require_auth();
+
+        if !has_role(&env, admin.clone(), ROLE_ADMIN) {
+            panic_with_error!(&env, errors::AccessError::Unauthorized);
+        }
+
+        multisig::create_proposal(&env, action, target)
+    }
+
+    pub fn approve_proposal(env: Env, signer: Address, proposal_id: u64) {
+        signer.require_auth();
+
+        if !has_role(&env, signer.clone(), ROLE_ADMIN) {
+            panic_with_error!(&env, errors::AccessError::Unauthorized);
+        }
+
+        multisig::approve(&env, proposal_id, signer);
+    }
+
+    /// Queried by other contracts (e.g. the marketplace admin transfer); only
+    /// true for a proposal made for this exact action and target
+    pub fn is_proposal_approved(env: Env, proposal_id: u64, action: Symbol, target: Address) -> bool {
+        multisig::is_approved(&env, proposal_id, action, Some(target))
+    }
+
     pub fn pause(env: Env, admin: Address, proposal_id: u64) {
         admin.require_auth();
-        multisig::assert_approved(&env, proposal_id);
+        multisig::assert_approved(&env, proposal_id, multisig::ACTION_PAUSE, None);
         set_pause(&env, true);
         events::paused(&env, true);
     }
@@ -1,15 +1,36 @@
-use soroban_sdk::{Env, Address, Vec};
+use soroban_sdk::{Env, Address, Symbol, Vec};
 use crate::{storage::DataKey, errors::AccessError};
 
+/// Actions a proposal can authorize; checked together with the proposal's target
+pub const ACTION_PAUSE: Symbol = symbol_short!("pause");
+pub const ACTION_TRANSFER_ADMIN: Symbol = symbol_short!("xfer_adm");
+
 #[derive(Clone)]
 pub struct Proposal {
+    pub action: Symbol,
+    pub target: Option<Address>,
     pub approvals: Vec<Address>,
     pub threshold: u32,
 }
 
-pub fn create_proposal(env: &Env, threshold: u32) -> u64 {
+/// The threshold is fixed once set, so a single admin cannot lower it later
+pub fn set_threshold(env: &Env, threshold: u32) {
+    if threshold == 0 {
+        panic_with_error!(env, AccessError::InvalidThreshold);
+    }
+    if env.storage().instance().has(&DataKey::MultisigThreshold) {
+        panic_with_error!(env, AccessError::Unauthorized);
+    }
+    env.storage().instance().set(&DataKey::MultisigThreshold, &threshold);
+}
+
+pub fn create_proposal(env: &Env, action: Symbol, target: Option<Address>) -> u64 {
+    let threshold: u32 = env.storage().instance().get(&DataKey::MultisigThreshold)
+        .unwrap_or_else(|| panic_with_error!(env, AccessError::InvalidThreshold));
     let nonce: u64 = env.storage().instance().get(&DataKey::ProposalNonce).unwrap_or(0);
     let proposal = Proposal {
+        action,
+        target,
         approvals: Vec::new(env),
         threshold,
     };
@@ -30,11 +51,24 @@ pub fn approve(env: &Env, id: u64, signer: Address) {
     env.storage().instance().set(&DataKey::MultisigProposal(id), &proposal);
 }
 
-pub fn assert_approved(env: &Env, id: u64) {
-    let proposal: Proposal =
-        env.storage().instance().get(&DataKey::MultisigProposal(id)).unwrap();
+/// Approved for exactly this action and target
+pub fn is_approved(env: &Env, id: u64, action: Symbol, target: Option<Address>) -> bool {
+    let proposal: Option<Proposal> =
+        env.storage().instance().get(&DataKey::MultisigProposal(id));
+
+    match proposal {
+        Some(p) => {
+            p.action == action
+                && p.target == target
+                && p.threshold > 0
+                && p.approvals.len() >= p.threshold
+        }
+        None => false,
+    }
+}
 
-    if proposal.approvals.len() < proposal.threshold {
+pub fn assert_approved(env: &Env, id: u64, action: Symbol, target: Option<Address>) {
+    if !is_approved(env, id, action, target) {
         panic_with_error!(env, AccessError::MultisigNotApproved);
     }
 }
@@ -14,6 +14,12 @@ pub fn assign_role(env: &Env, user: Address, role: Symbol) {
         panic_with_error!(env, AccessError::AlreadyHasRole);
     }
 
+    if let Some(prerequisite) = get_prerequisite(env, role.clone()) {
+        if !roles.contains(&prerequisite) {
+            panic_with_error!(env, AccessError::MissingPrerequisite);
+        }
+    }
+
     roles.push_back(role);
     env.storage().instance().set(&DataKey::Roles(user), &roles);
 }
@@ -33,3 +39,14 @@ pub fn has_role(env: &Env, user: Address, role: Symbol) -> bool {
         .map(|r| r.contains(&role))
         .unwrap_or(false)
 }
+
+pub fn set_prerequisite(env: &Env, role: Symbol, prerequisite: Option<Symbol>) {
+    match prerequisite {
+        Some(p) => env.storage().instance().set(&DataKey::Prerequisite(role), &p),
+        None => env.storage().instance().remove(&DataKey::Prerequisite(role)),
+    }
+}
+
+pub fn get_prerequisite(env: &Env, role: Symbol) -> Option<Symbol> {
+    env.storage().instance().get(&DataKey::Prerequisite(role))
+}
@@ -7,4 +7,6 @@ pub enum DataKey {
     Paused,
     MultisigProposal(u64),
     ProposalNonce,
+    MultisigThreshold,
+    Prerequisite(Symbol),
 }
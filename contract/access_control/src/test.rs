@@ -15,3 +15,74 @@ fn admin_can_assign_role() {
 
     assert!(roles::has_role(&env, user, roles::ROLE_BUYER));
 }
+
+#[test]
+#[should_panic]
+fn role_grant_requires_prerequisite() {
+    let env = Env::default();
+    let admin = Address::random(&env);
+    let user = Address::random(&env);
+
+    env.mock_all_auths();
+
+    roles::assign_role(&env, admin.clone(), roles::ROLE_ADMIN);
+    AccessControl::set_role_prerequisite(env.clone(), admin.clone(), roles::ROLE_SELLER, Some(roles::ROLE_BUYER));
+    AccessControl::assign_role(env.clone(), admin, user, roles::ROLE_SELLER);
+}
+
+#[test]
+fn role_grant_after_prerequisite() {
+    let env = Env::default();
+    let admin = Address::random(&env);
+    let user = Address::random(&env);
+
+    env.mock_all_auths();
+
+    roles::assign_role(&env, admin.clone(), roles::ROLE_ADMIN);
+    AccessControl::set_role_prerequisite(env.clone(), admin.clone(), roles::ROLE_SELLER, Some(roles::ROLE_BUYER));
+    AccessControl::assign_role(env.clone(), admin.clone(), user.clone(), roles::ROLE_BUYER);
+    AccessControl::assign_role(env.clone(), admin, user.clone(), roles::ROLE_SELLER);
+
+    assert!(roles::has_role(&env, user, roles::ROLE_SELLER));
+}
+
+#[test]
+fn proposal_only_approves_its_own_action_and_target() {
+    let env = Env::default();
+    let admin = Address::random(&env);
+    let cosigner = Address::random(&env);
+    let new_admin = Address::random(&env);
+
+    env.mock_all_auths();
+
+    roles::assign_role(&env, admin.clone(), roles::ROLE_ADMIN);
+    roles::assign_role(&env, cosigner.clone(), roles::ROLE_ADMIN);
+    AccessControl::set_multisig_threshold(env.clone(), admin.clone(), 2);
+
+    let id = AccessControl::create_proposal(
+        env.clone(), admin.clone(), multisig::ACTION_TRANSFER_ADMIN, Some(new_admin.clone()));
+    AccessControl::approve_proposal(env.clone(), admin.clone(), id);
+    assert!(!AccessControl::is_proposal_approved(
+        env.clone(), id, multisig::ACTION_TRANSFER_ADMIN, new_admin.clone()));
+
+    AccessControl::approve_proposal(env.clone(), cosigner, id);
+    assert!(AccessControl::is_proposal_approved(
+        env.clone(), id, multisig::ACTION_TRANSFER_ADMIN, new_admin.clone()));
+    assert!(!AccessControl::is_proposal_approved(
+        env.clone(), id, multisig::ACTION_TRANSFER_ADMIN, admin.clone()));
+    assert!(!AccessControl::is_proposal_approved(
+        env.clone(), id, multisig::ACTION_PAUSE, new_admin));
+}
+
+#[test]
+#[should_panic]
+fn multisig_threshold_cannot_be_lowered() {
+    let env = Env::default();
+    let admin = Address::random(&env);
+
+    env.mock_all_auths();
+
+    roles::assign_role(&env, admin.clone(), roles::ROLE_ADMIN);
+    AccessControl::set_multisig_threshold(env.clone(), admin.clone(), 2);
+    AccessControl::set_multisig_threshold(env.clone(), admin, 1);
+}
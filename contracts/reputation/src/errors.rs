@@ -0,0 +1,21 @@
+use soroban_sdk::contracterror;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    InvalidInput = 1,
+    AlreadyInitialized = 2,
+    NotInitialized = 3,
+    Unauthorized = 4,
+    RateLimited = 5,
+    DuplicateComment = 6,
+    HasNativeReviews = 7,
+    ReviewNotFound = 8,
+    AlreadyVoted = 9,
+    AlreadyResponded = 10,
+    EditWindowClosed = 11,
+    TransactionNotVerified = 12,
+    AlreadyFlagged = 13,
+    AlreadyReviewed = 14,
+}
@@ -0,0 +1,9 @@
+use soroban_sdk::{contractclient, Address, Env, Vec};
+
+/// The slice of the escrow contract's interface used to verify reviews.
+#[allow(dead_code)]
+#[contractclient(name = "EscrowClient")]
+pub trait EscrowParties {
+    /// Whether the escrow is closed, with its payers and payees
+    fn get_escrow_parties(env: Env, id: u128) -> (bool, Vec<Address>, Vec<Address>);
+}
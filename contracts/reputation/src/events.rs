@@ -0,0 +1,17 @@
+use soroban_sdk::{contractevent, Address, Symbol};
+
+/// Reasons carried by `ReputationChangedEventData`
+pub const REASON_REVIEW: &str = "REVIEW";
+pub const REASON_ADMIN_ADJUST: &str = "ADMIN_ADJUST";
+pub const REASON_DECAY: &str = "DECAY";
+pub const REASON_REMOVAL: &str = "REMOVAL";
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReputationChangedEventData {
+    #[topic]
+    pub user: Address,
+    pub old_score: u32,
+    pub new_score: u32,
+    pub reason: Symbol,
+}
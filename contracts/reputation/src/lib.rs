@@ -1,5 +1,8 @@
 #![no_std]
 
+mod errors;
+mod escrow;
+mod events;
 mod reputation;
 mod storage;
 mod types;
@@ -7,14 +10,59 @@ mod types;
 #[cfg(test)]
 mod tests;
 
-use soroban_sdk::{contract, contractimpl, Address, Env, String, Vec};
-use types::{Reputation, Review};
+use errors::Error;
+use soroban_sdk::{contract, contractimpl, Address, BytesN, Env, String, Symbol, Vec};
+use types::{
+    Attestation, BayesianPrior, DecayConfig, ReportCard, Reputation, ReputationConfig, Review,
+    ReviewResponse, ReviewWindow, ReviewerStats, UserExport,
+};
 
 #[contract]
 pub struct ReputationContract;
 
 #[contractimpl]
 impl ReputationContract {
+    pub fn initialize(env: Env, admin: Address) -> Result<(), Error> {
+        reputation::initialize(&env, admin)
+    }
+
+    /// Only publish reputation change events when the average moves by at least this much.
+    pub fn set_min_event_delta(
+        env: Env,
+        admin: Address,
+        min_event_delta: u32,
+    ) -> Result<(), Error> {
+        reputation::set_min_event_delta(&env, admin, min_event_delta)
+    }
+
+    /// Users whose score drops below `threshold` are added to the moderation watchlist.
+    pub fn set_watch_threshold(env: Env, admin: Address, threshold: u32) -> Result<(), Error> {
+        reputation::set_watch_threshold(&env, admin, threshold)
+    }
+
+    /// Returns watched users with their current score.
+    pub fn get_watchlist(env: Env, offset: u32, limit: u32) -> Vec<(Address, u32)> {
+        reputation::get_watchlist(&env, offset, limit)
+    }
+
+    /// Caps how many reviews one reviewer may submit per ledger; 0 disables the limit.
+    pub fn set_review_rate_limit(
+        env: Env,
+        admin: Address,
+        max_per_ledger: u32,
+    ) -> Result<(), Error> {
+        reputation::set_review_rate_limit(&env, admin, max_per_ledger)
+    }
+
+    /// Limits each reviewer to `max_reviews` (at most 20) within any `window` seconds.
+    pub fn set_review_window(env: Env, admin: Address, window: ReviewWindow) -> Result<(), Error> {
+        reputation::set_review_window(&env, admin, window)
+    }
+
+    pub fn get_review_window(env: Env) -> ReviewWindow {
+        reputation::get_review_window(&env)
+    }
+
     pub fn submit_review(
         env: Env,
         reviewer: Address,
@@ -25,11 +73,223 @@ impl ReputationContract {
         reputation::submit_review(&env, reviewer, subject, rating, comment)
     }
 
+    /// Submits a review backed by a closed escrow in which both parties took part.
+    /// Required instead of `submit_review` once an escrow contract is configured.
+    pub fn submit_verified_review(
+        env: Env,
+        reviewer: Address,
+        subject: Address,
+        transaction_id: u128,
+        rating: u32,
+        comment: String,
+    ) -> Result<Reputation, Error> {
+        reputation::submit_verified_review(&env, reviewer, subject, transaction_id, rating, comment)
+    }
+
+    /// Sets (or clears with None) the escrow contract reviews are verified against (admin only).
+    pub fn set_escrow_contract(
+        env: Env,
+        admin: Address,
+        escrow: Option<Address>,
+    ) -> Result<(), Error> {
+        reputation::set_escrow_contract(&env, admin, escrow)
+    }
+
+    /// Rejects reviewers reusing one of their recent comments verbatim.
+    pub fn set_duplicate_comment_check(
+        env: Env,
+        admin: Address,
+        enabled: bool,
+    ) -> Result<(), Error> {
+        reputation::set_duplicate_comment_check(&env, admin, enabled)
+    }
+
+    /// Sets how fast inactive users' scores decay.
+    pub fn set_decay_config(env: Env, admin: Address, config: DecayConfig) -> Result<(), Error> {
+        reputation::set_decay_config(&env, admin, config)
+    }
+
+    pub fn get_decay_config(env: Env) -> DecayConfig {
+        reputation::get_decay_config(&env)
+    }
+
+    /// Sets the prior that `get_bayesian_score` blends averages toward.
+    pub fn set_bayesian_prior(env: Env, admin: Address, prior: BayesianPrior) -> Result<(), Error> {
+        reputation::set_bayesian_prior(&env, admin, prior)
+    }
+
+    pub fn get_bayesian_prior(env: Env) -> BayesianPrior {
+        reputation::get_bayesian_prior(&env)
+    }
+
+    /// Returns the 0-500 score blended with the configured prior, for ranking users
+    /// with few reviews against established ones. `get_reputation` stays raw.
+    pub fn get_bayesian_score(env: Env, user: Address) -> u32 {
+        reputation::get_bayesian_score(&env, user)
+    }
+
+    /// Returns (current score, score after pending decay, full inactive periods) without
+    /// changing anything, so front ends can warn before a score drops.
+    pub fn preview_decay(env: Env, user: Address) -> (u32, u32, u64) {
+        reputation::preview_decay(&env, user)
+    }
+
+    /// Writes back the decay pending on an inactive user. Permissionless; a new
+    /// review recomputes the average from all ratings and restores the score.
+    pub fn apply_decay(env: Env, user: Address) -> Reputation {
+        reputation::apply_decay(&env, user)
+    }
+
+    pub fn get_config(env: Env) -> ReputationConfig {
+        reputation::get_config(&env)
+    }
+
+    /// Replaces every admin-set setting at once.
+    pub fn set_config(env: Env, admin: Address, config: ReputationConfig) -> Result<(), Error> {
+        reputation::set_config(&env, admin, config)
+    }
+
+    /// Seeds a user without native reviews with a discounted score from a partner platform.
+    pub fn admin_attest_reputation(
+        env: Env,
+        admin: Address,
+        user: Address,
+        external_score: u32,
+        external_count: u32,
+        source: Symbol,
+    ) -> Result<Reputation, Error> {
+        reputation::admin_attest_reputation(
+            &env,
+            admin,
+            user,
+            external_score,
+            external_count,
+            source,
+        )
+    }
+
+    pub fn get_attestation(env: Env, user: Address) -> Option<Attestation> {
+        reputation::get_attestation(&env, user)
+    }
+
     pub fn get_reputation(env: Env, subject: Address) -> Reputation {
         reputation::get_reputation(&env, subject)
     }
 
+    /// Returns the average rating converted to a 0..=`scale` range (5, 10, 100 or 500).
+    pub fn get_score_scaled(env: Env, subject: Address, scale: u32) -> Result<u32, Error> {
+        reputation::get_score_scaled(&env, subject, scale)
+    }
+
+    /// Returns the 0-500 score of each user in order, 0 for unknown users (max 100 users).
+    pub fn get_scores_batch(env: Env, users: Vec<Address>) -> Result<Vec<(Address, u32)>, Error> {
+        reputation::get_scores_batch(&env, users)
+    }
+
+    /// Returns the 0-5 star tier of each user in order, 0 for unknown users (max 100 users).
+    pub fn get_tiers_batch(env: Env, users: Vec<Address>) -> Result<Vec<(Address, u32)>, Error> {
+        reputation::get_tiers_batch(&env, users)
+    }
+
+    /// Returns a display summary of a user: score, tier, review mix and whether the
+    /// last five reviews run above or below the overall average.
+    pub fn get_report_card(env: Env, user: Address) -> ReportCard {
+        reputation::get_report_card(&env, user)
+    }
+
+    /// Estimates the percentage of ranked users scoring at or below `user`, from
+    /// 0.5-star buckets. Users need 3 reviews to be ranked; unranked users get 0.
+    pub fn get_percentile(env: Env, user: Address) -> u32 {
+        reputation::get_percentile(&env, user)
+    }
+
     pub fn get_reviews(env: Env, subject: Address) -> Vec<Review> {
         reputation::get_reviews(&env, subject)
     }
+
+    pub fn get_review(env: Env, review_id: u64) -> Option<Review> {
+        reputation::get_review(&env, review_id)
+    }
+
+    /// Marks a review as helpful; each voter counts once and reviewers cannot vote on their own.
+    pub fn vote_review_helpful(env: Env, voter: Address, review_id: u64) -> Result<u32, Error> {
+        reputation::vote_review_helpful(&env, voter, review_id)
+    }
+
+    /// Flags a review for moderation; `reason_hash` references the off-chain reason.
+    /// Returns the review's flag count. Each address may flag a review once.
+    pub fn flag_review(
+        env: Env,
+        flagger: Address,
+        review_id: u64,
+        reason_hash: BytesN<32>,
+    ) -> Result<u32, Error> {
+        reputation::flag_review(&env, flagger, review_id, reason_hash)
+    }
+
+    /// Sets how many flags put a review in the moderation queue (default 3).
+    pub fn set_flag_threshold(env: Env, admin: Address, threshold: u32) -> Result<(), Error> {
+        reputation::set_flag_threshold(&env, admin, threshold)
+    }
+
+    /// Returns a page of the moderation queue as (review id, flag count), oldest first.
+    pub fn get_flagged_reviews(env: Env, offset: u32, limit: u32) -> Vec<(u64, u32)> {
+        reputation::get_flagged_reviews(&env, offset, limit)
+    }
+
+    /// Deletes a fraudulent review, reverses its rating and counts it against the reviewer.
+    pub fn admin_remove_review(
+        env: Env,
+        admin: Address,
+        review_id: u64,
+    ) -> Result<Reputation, Error> {
+        reputation::admin_remove_review(&env, admin, review_id)
+    }
+
+    /// Reviews a user has written and how many of them were removed as fraudulent.
+    pub fn get_reviewer_stats(env: Env, reviewer: Address) -> ReviewerStats {
+        reputation::get_reviewer_stats(&env, reviewer)
+    }
+
+    /// Lets a reviewer change their rating and comment within the edit window.
+    pub fn edit_review(
+        env: Env,
+        reviewer: Address,
+        review_id: u64,
+        new_rating: u32,
+        new_comment: String,
+    ) -> Result<Reputation, Error> {
+        reputation::edit_review(&env, reviewer, review_id, new_rating, new_comment)
+    }
+
+    /// Sets how long after submission a review can be edited, in seconds (default 24 hours).
+    pub fn set_edit_window(env: Env, admin: Address, window: u64) -> Result<(), Error> {
+        reputation::set_edit_window(&env, admin, window)
+    }
+
+    /// Lets the reviewed user post one public reply to a review, referenced by content hash.
+    pub fn respond_to_review(
+        env: Env,
+        responder: Address,
+        review_id: u64,
+        response_hash: BytesN<32>,
+    ) -> Result<ReviewResponse, Error> {
+        reputation::respond_to_review(&env, responder, review_id, response_hash)
+    }
+
+    pub fn get_review_response(env: Env, review_id: u64) -> Option<ReviewResponse> {
+        reputation::get_review_response(&env, review_id)
+    }
+
+    /// Keeps the listed users' reputation and reviews alive; returns how many had entries.
+    /// Permissionless so an off-chain keeper can maintain active records.
+    pub fn bump_ttl(env: Env, users: Vec<Address>) -> Result<u32, Error> {
+        reputation::bump_ttl(&env, users)
+    }
+
+    /// Bundles a user's reputation and a page of received reviews, with their
+    /// responses, for off-chain export.
+    pub fn export_user_data(env: Env, user: Address, offset: u32, limit: u32) -> UserExport {
+        reputation::export_user_data(&env, user, offset, limit)
+    }
 }
@@ -1,6 +1,326 @@
+use crate::errors::Error;
+use crate::escrow::EscrowClient;
+use crate::events::{
+    ReputationChangedEventData, REASON_ADMIN_ADJUST, REASON_DECAY, REASON_REMOVAL, REASON_REVIEW,
+};
 use crate::storage::Storage;
-use crate::types::{Reputation, Review};
-use soroban_sdk::{Address, Env, String};
+use crate::types::{
+    Attestation, BayesianPrior, DecayConfig, ReportCard, Reputation, ReputationConfig, Review,
+    ReviewResponse, ReviewWindow, ReviewerStats, Trend, UserExport,
+};
+use soroban_sdk::{panic_with_error, Address, BytesN, Env, String, Symbol, Vec};
+
+/// Maximum number of reviews bundled into a single export page
+const MAX_EXPORT_REVIEWS: u32 = 100;
+
+/// External reviews count for half a native review each, up to this many
+const MAX_ATTESTED_WEIGHT: u32 = 10;
+
+/// Maximum number of users tracked on the moderation watchlist
+const MAX_WATCHLIST: u32 = 200;
+
+/// Maximum number of entries returned by a single page query
+const MAX_PAGE_SIZE: u32 = 50;
+
+/// Number of recent comment hashes remembered per reviewer for duplicate detection
+const MAX_RECENT_COMMENT_HASHES: u32 = 10;
+
+/// Largest `max_reviews` a review window may allow, bounding the per-reviewer buffer
+const MAX_WINDOW_REVIEWS: u32 = 20;
+
+/// Length of one inactivity period for score decay, 30 days
+const DECAY_PERIOD: u64 = 30 * 24 * 60 * 60;
+
+/// Maximum number of users whose entries can be extended in one call
+const MAX_BUMP_BATCH: u32 = 50;
+
+/// Number of latest reviews compared against the overall average for the trend
+const TREND_WINDOW: u32 = 5;
+
+/// Gap between recent and overall average, in rating * 100, that counts as a trend
+const TREND_MARGIN: u32 = 25;
+
+/// Reviews a user needs before they are counted in percentile ranks
+const MIN_RANKED_REVIEWS: u32 = 3;
+
+/// Width of one score-distribution bucket on the 0-500 scale; 500 gets its own bucket
+const SCORE_BUCKET_WIDTH: u32 = 50;
+
+/// Maximum number of users looked up by a single batch query
+const MAX_QUERY_BATCH: u32 = 100;
+
+/// Ledgers a bumped entry stays live for, roughly 30 days
+pub(crate) const BUMP_TTL: u32 = 518_400;
+
+pub fn initialize(env: &Env, admin: Address) -> Result<(), Error> {
+    let storage = Storage::new(env);
+    if storage.get_admin().is_some() {
+        return Err(Error::AlreadyInitialized);
+    }
+    admin.require_auth();
+    storage.set_admin(&admin);
+    Ok(())
+}
+
+pub fn require_admin(env: &Env, admin: &Address) -> Result<(), Error> {
+    let stored = Storage::new(env).get_admin().ok_or(Error::NotInitialized)?;
+    if stored != *admin {
+        return Err(Error::Unauthorized);
+    }
+    admin.require_auth();
+    Ok(())
+}
+
+pub fn set_duplicate_comment_check(env: &Env, admin: Address, enabled: bool) -> Result<(), Error> {
+    require_admin(env, &admin)?;
+    Storage::new(env).set_duplicate_check(enabled);
+    Ok(())
+}
+
+pub fn admin_attest_reputation(
+    env: &Env,
+    admin: Address,
+    user: Address,
+    external_score: u32,
+    external_count: u32,
+    source: Symbol,
+) -> Result<Reputation, Error> {
+    require_admin(env, &admin)?;
+
+    if external_score > 500 || external_count == 0 {
+        return Err(Error::InvalidInput);
+    }
+
+    let storage = Storage::new(env);
+    let mut reputation = storage.get_reputation(&user);
+    // Imported reputation only bootstraps new users
+    if reputation.total_reviews > 0 {
+        return Err(Error::HasNativeReviews);
+    }
+
+    let weight = (external_count / 2).clamp(1, MAX_ATTESTED_WEIGHT);
+    storage.set_attestation(
+        &user,
+        &Attestation {
+            source,
+            score: external_score,
+            weight,
+        },
+    );
+
+    let old_score = reputation.average_rating;
+    reputation.average_rating = external_score;
+    reputation.last_updated = env.ledger().timestamp();
+    update_reputation(env, &user, old_score, &reputation, REASON_ADMIN_ADJUST);
+    Ok(reputation)
+}
+
+pub fn get_attestation(env: &Env, user: Address) -> Option<Attestation> {
+    Storage::new(env).get_attestation(&user)
+}
+
+pub fn set_min_event_delta(env: &Env, admin: Address, min_event_delta: u32) -> Result<(), Error> {
+    require_admin(env, &admin)?;
+    Storage::new(env).set_min_event_delta(min_event_delta);
+    Ok(())
+}
+
+pub fn set_watch_threshold(env: &Env, admin: Address, threshold: u32) -> Result<(), Error> {
+    require_admin(env, &admin)?;
+    Storage::new(env).set_watch_threshold(threshold);
+    Ok(())
+}
+
+pub fn get_watchlist(env: &Env, offset: u32, limit: u32) -> Vec<(Address, u32)> {
+    let storage = Storage::new(env);
+    let mut page = Vec::new(env);
+    for user in storage
+        .get_watchlist()
+        .iter()
+        .skip(offset as usize)
+        .take(limit.min(MAX_PAGE_SIZE) as usize)
+    {
+        let score = storage.get_reputation(&user).average_rating;
+        page.push_back((user, score));
+    }
+    page
+}
+
+// Users enter the watchlist when they drop below the threshold and leave once they climb back
+fn update_watchlist(storage: &Storage, user: &Address, score: u32) {
+    let threshold = storage.get_watch_threshold();
+    let mut watchlist = storage.get_watchlist();
+    let index = watchlist.first_index_of(user);
+
+    if threshold > 0 && score < threshold {
+        if index.is_none() && watchlist.len() < MAX_WATCHLIST {
+            watchlist.push_back(user.clone());
+            storage.set_watchlist(&watchlist);
+        }
+    } else if let Some(i) = index {
+        watchlist.remove(i);
+        storage.set_watchlist(&watchlist);
+    }
+}
+
+pub fn set_decay_config(env: &Env, admin: Address, config: DecayConfig) -> Result<(), Error> {
+    require_admin(env, &admin)?;
+    if config.percent_per_period > 100 {
+        return Err(Error::InvalidInput);
+    }
+    Storage::new(env).set_decay_config(&config);
+    Ok(())
+}
+
+pub fn get_decay_config(env: &Env) -> DecayConfig {
+    Storage::new(env).get_decay_config()
+}
+
+pub fn set_bayesian_prior(env: &Env, admin: Address, prior: BayesianPrior) -> Result<(), Error> {
+    require_admin(env, &admin)?;
+    if prior.mean > 500 {
+        return Err(Error::InvalidInput);
+    }
+    Storage::new(env).set_bayesian_prior(&prior);
+    Ok(())
+}
+
+pub fn get_bayesian_prior(env: &Env) -> BayesianPrior {
+    Storage::new(env).get_bayesian_prior()
+}
+
+pub fn get_bayesian_score(env: &Env, user: Address) -> u32 {
+    let storage = Storage::new(env);
+    let prior = storage.get_bayesian_prior();
+    storage
+        .get_reputation(&user)
+        .bayesian_score(prior.mean, prior.weight)
+}
+
+/// Score after compounding decay over the full periods since `last_updated`,
+/// with the number of periods. Users with no recorded activity never decay.
+fn decayed_score(reputation: &Reputation, config: &DecayConfig, now: u64) -> (u32, u64) {
+    if reputation.last_updated == 0 {
+        return (reputation.average_rating, 0);
+    }
+    let periods = now.saturating_sub(reputation.last_updated) / DECAY_PERIOD;
+
+    let mut score = reputation.average_rating;
+    if config.percent_per_period > 0 {
+        // Each period shaves at least one point once the score is small, so this stops quickly
+        for _ in 0..periods {
+            if score == 0 {
+                break;
+            }
+            score = score * (100 - config.percent_per_period) / 100;
+        }
+    }
+    (score, periods)
+}
+
+pub fn preview_decay(env: &Env, user: Address) -> (u32, u32, u64) {
+    let storage = Storage::new(env);
+    let reputation = storage.get_reputation(&user);
+    let (decayed, periods) = decayed_score(
+        &reputation,
+        &storage.get_decay_config(),
+        env.ledger().timestamp(),
+    );
+    (reputation.average_rating, decayed, periods)
+}
+
+pub fn apply_decay(env: &Env, user: Address) -> Reputation {
+    let storage = Storage::new(env);
+    let mut reputation = storage.get_reputation(&user);
+    let (decayed, periods) = decayed_score(
+        &reputation,
+        &storage.get_decay_config(),
+        env.ledger().timestamp(),
+    );
+    if periods == 0 {
+        return reputation;
+    }
+
+    let old_score = reputation.average_rating;
+    reputation.average_rating = decayed;
+    // Only whole periods are consumed so a partial one keeps counting
+    reputation.last_updated += periods * DECAY_PERIOD;
+    update_reputation(env, &user, old_score, &reputation, REASON_DECAY);
+    reputation
+}
+
+pub fn get_config(env: &Env) -> ReputationConfig {
+    let storage = Storage::new(env);
+    ReputationConfig {
+        max_reviews_per_ledger: storage.get_rate_limit(),
+        duplicate_comment_check: storage.get_duplicate_check(),
+        min_event_delta: storage.get_min_event_delta(),
+        watch_threshold: storage.get_watch_threshold(),
+        decay: storage.get_decay_config(),
+        bayesian_prior: storage.get_bayesian_prior(),
+        review_window: storage.get_review_window(),
+        edit_window: storage.get_edit_window(),
+        flag_threshold: storage.get_flag_threshold(),
+        escrow_contract: storage.get_escrow_contract(),
+    }
+}
+
+pub fn set_config(env: &Env, admin: Address, config: ReputationConfig) -> Result<(), Error> {
+    require_admin(env, &admin)?;
+    // Same bounds as the granular setters; nothing is written unless all pass
+    if config.decay.percent_per_period > 100
+        || config.bayesian_prior.mean > 500
+        || !review_window_valid(&config.review_window)
+        || config.flag_threshold == 0
+    {
+        return Err(Error::InvalidInput);
+    }
+    let storage = Storage::new(env);
+    storage.set_rate_limit(config.max_reviews_per_ledger);
+    storage.set_duplicate_check(config.duplicate_comment_check);
+    storage.set_min_event_delta(config.min_event_delta);
+    storage.set_watch_threshold(config.watch_threshold);
+    storage.set_decay_config(&config.decay);
+    storage.set_bayesian_prior(&config.bayesian_prior);
+    storage.set_review_window(&config.review_window);
+    storage.set_edit_window(config.edit_window);
+    storage.set_flag_threshold(config.flag_threshold);
+    storage.set_escrow_contract(&config.escrow_contract);
+    Ok(())
+}
+
+pub fn set_review_window(env: &Env, admin: Address, window: ReviewWindow) -> Result<(), Error> {
+    require_admin(env, &admin)?;
+    if !review_window_valid(&window) {
+        return Err(Error::InvalidInput);
+    }
+    Storage::new(env).set_review_window(&window);
+    Ok(())
+}
+
+fn review_window_valid(window: &ReviewWindow) -> bool {
+    window.max_reviews <= MAX_WINDOW_REVIEWS && (window.max_reviews == 0 || window.window > 0)
+}
+
+pub fn get_review_window(env: &Env) -> ReviewWindow {
+    Storage::new(env).get_review_window()
+}
+
+pub fn set_review_rate_limit(env: &Env, admin: Address, max_per_ledger: u32) -> Result<(), Error> {
+    require_admin(env, &admin)?;
+    Storage::new(env).set_rate_limit(max_per_ledger);
+    Ok(())
+}
+
+pub fn set_escrow_contract(
+    env: &Env,
+    admin: Address,
+    escrow: Option<Address>,
+) -> Result<(), Error> {
+    require_admin(env, &admin)?;
+    Storage::new(env).set_escrow_contract(&escrow);
+    Ok(())
+}
 
 pub fn submit_review(
     env: &Env,
@@ -8,6 +328,58 @@ pub fn submit_review(
     subject: Address,
     rating: u32,
     comment: String,
+) -> Reputation {
+    // With an escrow configured every review has to name the transaction it is about
+    if Storage::new(env).get_escrow_contract().is_some() {
+        panic_with_error!(env, Error::TransactionNotVerified);
+    }
+    record_review(env, reviewer, subject, None, rating, comment)
+}
+
+pub fn submit_verified_review(
+    env: &Env,
+    reviewer: Address,
+    subject: Address,
+    transaction_id: u128,
+    rating: u32,
+    comment: String,
+) -> Result<Reputation, Error> {
+    let storage = Storage::new(env);
+    // One review per party per escrow
+    if storage.has_transaction_review(transaction_id, &reviewer) {
+        return Err(Error::AlreadyReviewed);
+    }
+    if let Some(escrow) = storage.get_escrow_contract() {
+        let (closed, payers, payees) = EscrowClient::new(env, &escrow)
+            .try_get_escrow_parties(&transaction_id)
+            .ok()
+            .and_then(|parties| parties.ok())
+            .ok_or(Error::TransactionNotVerified)?;
+
+        let is_party = |user: &Address| payers.contains(user) || payees.contains(user);
+        if !closed || !is_party(&reviewer) || !is_party(&subject) {
+            return Err(Error::TransactionNotVerified);
+        }
+    }
+    let reputation = record_review(
+        env,
+        reviewer.clone(),
+        subject,
+        Some(transaction_id),
+        rating,
+        comment,
+    );
+    storage.set_transaction_review(transaction_id, &reviewer);
+    Ok(reputation)
+}
+
+fn record_review(
+    env: &Env,
+    reviewer: Address,
+    subject: Address,
+    transaction_id: Option<u128>,
+    rating: u32,
+    comment: String,
 ) -> Reputation {
     reviewer.require_auth();
 
@@ -20,35 +392,499 @@ pub fn submit_review(
     }
 
     let storage = Storage::new(env);
+
+    // Bound burst activity from a single reviewer within one ledger
+    let limit = storage.get_rate_limit();
+    if limit > 0 {
+        let sequence = env.ledger().sequence();
+        let count = storage.get_ledger_review_count(&reviewer, sequence);
+        if count >= limit {
+            panic_with_error!(env, Error::RateLimited);
+        }
+        storage.set_ledger_review_count(&reviewer, sequence, count + 1);
+    }
+
+    // Bound sustained activity: at most max_reviews within any window of that many seconds
+    let window = storage.get_review_window();
+    if window.max_reviews > 0 {
+        let now = env.ledger().timestamp();
+        let mut times = storage.get_review_times(&reviewer);
+        let recent = times
+            .iter()
+            .filter(|t| now.saturating_sub(*t) < window.window)
+            .count() as u32;
+        if recent >= window.max_reviews {
+            panic_with_error!(env, Error::RateLimited);
+        }
+        times.push_back(now);
+        while times.len() > window.max_reviews {
+            times.pop_front();
+        }
+        storage.set_review_times(&reviewer, &times);
+    }
+
+    // Reject copy-paste spam: the same comment reused within the reviewer's recent window
+    if storage.get_duplicate_check() {
+        let hash: BytesN<32> = env.crypto().sha256(&comment.to_bytes()).into();
+        let mut recent = storage.get_recent_comment_hashes(&reviewer);
+        if recent.contains(&hash) {
+            panic_with_error!(env, Error::DuplicateComment);
+        }
+        if recent.len() >= MAX_RECENT_COMMENT_HASHES {
+            recent.pop_front();
+        }
+        recent.push_back(hash);
+        storage.set_recent_comment_hashes(&reviewer, &recent);
+    }
+
     let mut reputation = storage.get_reputation(&subject);
+    let old_score = reputation.average_rating;
 
     // Update score
     reputation.total_reviews += 1;
     reputation.total_score += rating;
-    // Calculate new average scaled by 100
-    // e.g. total_score = 9, reviews = 2 => 4.5 => 450
-    reputation.average_rating = (reputation.total_score * 100) / reputation.total_reviews;
+    reputation.average_rating = average_rating(&storage, &subject, &reputation);
+    reputation.last_updated = env.ledger().timestamp();
 
     // Store Review
     let review = Review {
+        id: storage.next_review_id(),
         reviewer,
         rating,
         comment,
         timestamp: env.ledger().timestamp(),
+        helpful_count: 0,
+        transaction_id,
     };
 
+    let mut stats = storage.get_reviewer_stats(&review.reviewer);
+    stats.reviews_submitted += 1;
+    storage.set_reviewer_stats(&review.reviewer, &stats);
+
     storage.add_review(&subject, review);
-    storage.set_reputation(&subject, &reputation);
+    update_reputation(env, &subject, old_score, &reputation, REASON_REVIEW);
 
     reputation
 }
 
+pub fn admin_remove_review(env: &Env, admin: Address, review_id: u64) -> Result<Reputation, Error> {
+    require_admin(env, &admin)?;
+
+    let storage = Storage::new(env);
+    let (subject, index) = storage
+        .get_review_ref(review_id)
+        .ok_or(Error::ReviewNotFound)?;
+    let review = storage
+        .remove_review(&subject, index)
+        .ok_or(Error::ReviewNotFound)?;
+
+    let mut reputation = storage.get_reputation(&subject);
+    let old_score = reputation.average_rating;
+    reputation.total_reviews -= 1;
+    reputation.total_score -= review.rating;
+    reputation.average_rating = average_rating(&storage, &subject, &reputation);
+    update_reputation(env, &subject, old_score, &reputation, REASON_REMOVAL);
+
+    let mut stats = storage.get_reviewer_stats(&review.reviewer);
+    stats.reviews_removed += 1;
+    storage.set_reviewer_stats(&review.reviewer, &stats);
+
+    let mut queue = storage.get_moderation_queue();
+    if let Some(position) = queue.first_index_of(review_id) {
+        queue.remove(position);
+        storage.set_moderation_queue(&queue);
+    }
+    storage.remove_flags(review_id);
+    storage.remove_review_response(review_id);
+
+    Ok(reputation)
+}
+
+pub fn get_reviewer_stats(env: &Env, reviewer: Address) -> ReviewerStats {
+    Storage::new(env).get_reviewer_stats(&reviewer)
+}
+
+/// Average of all native ratings scaled by 100,
+/// e.g. total_score = 9, reviews = 2 => 4.5 => 450.
+/// An attested external score counts as `weight` extra reviews, so native reviews dominate over time
+fn average_rating(storage: &Storage, subject: &Address, reputation: &Reputation) -> u32 {
+    match storage.get_attestation(subject) {
+        Some(a) => {
+            (reputation.total_score * 100 + a.score * a.weight)
+                / (reputation.total_reviews + a.weight)
+        }
+        None if reputation.total_reviews == 0 => 0,
+        None => (reputation.total_score * 100) / reputation.total_reviews,
+    }
+}
+
+pub fn set_edit_window(env: &Env, admin: Address, window: u64) -> Result<(), Error> {
+    require_admin(env, &admin)?;
+    Storage::new(env).set_edit_window(window);
+    Ok(())
+}
+
+pub fn edit_review(
+    env: &Env,
+    reviewer: Address,
+    review_id: u64,
+    new_rating: u32,
+    new_comment: String,
+) -> Result<Reputation, Error> {
+    reviewer.require_auth();
+
+    let storage = Storage::new(env);
+    let (subject, index) = storage
+        .get_review_ref(review_id)
+        .ok_or(Error::ReviewNotFound)?;
+    let mut reviews = storage.get_reviews(&subject);
+    let mut review = reviews.get(index).ok_or(Error::ReviewNotFound)?;
+
+    if review.reviewer != reviewer {
+        return Err(Error::Unauthorized);
+    }
+    if !(1..=5).contains(&new_rating) {
+        return Err(Error::InvalidInput);
+    }
+    let now = env.ledger().timestamp();
+    if now > review.timestamp.saturating_add(storage.get_edit_window()) {
+        return Err(Error::EditWindowClosed);
+    }
+
+    // Swap the old rating's contribution for the new one
+    let mut reputation = storage.get_reputation(&subject);
+    let old_score = reputation.average_rating;
+    reputation.total_score = reputation.total_score - review.rating + new_rating;
+    reputation.average_rating = average_rating(&storage, &subject, &reputation);
+    reputation.last_updated = now;
+
+    review.rating = new_rating;
+    review.comment = new_comment;
+    reviews.set(index, review);
+    storage.set_reviews(&subject, &reviews);
+    update_reputation(env, &subject, old_score, &reputation, REASON_REVIEW);
+
+    Ok(reputation)
+}
+
+/// Stores a changed reputation and publishes why it changed.
+fn update_reputation(
+    env: &Env,
+    user: &Address,
+    old_score: u32,
+    reputation: &Reputation,
+    reason: &str,
+) {
+    let storage = Storage::new(env);
+    storage.set_reputation(user, reputation);
+    update_watchlist(&storage, user, reputation.average_rating);
+    update_score_distribution(&storage, user, reputation);
+
+    // Skip small moves on established users to keep indexer noise down
+    let new_score = reputation.average_rating;
+    if new_score != old_score && new_score.abs_diff(old_score) >= storage.get_min_event_delta() {
+        ReputationChangedEventData {
+            user: user.clone(),
+            old_score,
+            new_score,
+            reason: Symbol::new(env, reason),
+        }
+        .publish(env);
+    }
+}
+
+// Moves the user between distribution buckets so percentiles need no sorting
+fn update_score_distribution(storage: &Storage, user: &Address, reputation: &Reputation) {
+    let old_bucket = storage.get_rank_bucket(user);
+    let new_bucket = (reputation.total_reviews >= MIN_RANKED_REVIEWS)
+        .then_some(reputation.average_rating / SCORE_BUCKET_WIDTH);
+    if old_bucket == new_bucket {
+        return;
+    }
+
+    let mut distribution = storage.get_score_distribution();
+    while distribution.len() <= 500 / SCORE_BUCKET_WIDTH {
+        distribution.push_back(0);
+    }
+    if let Some(bucket) = old_bucket {
+        distribution.set(bucket, distribution.get_unchecked(bucket) - 1);
+    }
+    if let Some(bucket) = new_bucket {
+        distribution.set(bucket, distribution.get_unchecked(bucket) + 1);
+    }
+    storage.set_score_distribution(&distribution);
+    storage.set_rank_bucket(user, new_bucket);
+}
+
+/// Share of ranked users, in percent, whose score falls in the user's bucket or
+/// below. 0 for users with fewer than `MIN_RANKED_REVIEWS` reviews.
+pub fn get_percentile(env: &Env, user: Address) -> u32 {
+    let storage = Storage::new(env);
+    let Some(bucket) = storage.get_rank_bucket(&user) else {
+        return 0;
+    };
+
+    let (mut at_or_below, mut total) = (0, 0);
+    for (i, count) in storage.get_score_distribution().iter().enumerate() {
+        total += count;
+        if i as u32 <= bucket {
+            at_or_below += count;
+        }
+    }
+    at_or_below * 100 / total
+}
+
 pub fn get_reputation(env: &Env, subject: Address) -> Reputation {
     let storage = Storage::new(env);
     storage.get_reputation(&subject)
 }
 
+pub fn get_score_scaled(env: &Env, subject: Address, scale: u32) -> Result<u32, Error> {
+    match scale {
+        5 | 10 | 100 | 500 => {}
+        _ => return Err(Error::InvalidInput),
+    }
+
+    let reputation = get_reputation(env, subject);
+    Ok(scale_score(reputation.average_rating, scale))
+}
+
+fn scale_score(average_rating: u32, scale: u32) -> u32 {
+    // average_rating is stored on a 0-500 scale (rating * 100)
+    const MAX_SCORE: u32 = 500;
+
+    // Round half up so every client sees the same converted value
+    (average_rating * scale + MAX_SCORE / 2) / MAX_SCORE
+}
+
+pub fn get_scores_batch(env: &Env, users: Vec<Address>) -> Result<Vec<(Address, u32)>, Error> {
+    scores_batch(env, users, |score| score)
+}
+
+// Tier is the score rounded to whole stars, as get_score_scaled with scale 5
+pub fn get_tiers_batch(env: &Env, users: Vec<Address>) -> Result<Vec<(Address, u32)>, Error> {
+    scores_batch(env, users, |score| scale_score(score, 5))
+}
+
+// Unknown users read as a default reputation, so they come back as 0
+fn scores_batch(
+    env: &Env,
+    users: Vec<Address>,
+    convert: impl Fn(u32) -> u32,
+) -> Result<Vec<(Address, u32)>, Error> {
+    if users.len() > MAX_QUERY_BATCH {
+        return Err(Error::InvalidInput);
+    }
+
+    let storage = Storage::new(env);
+    let mut scores = Vec::new(env);
+    for user in users.iter() {
+        let score = convert(storage.get_reputation(&user).average_rating);
+        scores.push_back((user, score));
+    }
+    Ok(scores)
+}
+
+pub fn get_report_card(env: &Env, user: Address) -> ReportCard {
+    let storage = Storage::new(env);
+    let reputation = storage.get_reputation(&user);
+    let reviews = storage.get_reviews(&user);
+
+    let count = reviews.len();
+    let (mut positive, mut negative, mut total, mut recent) = (0u32, 0u32, 0u32, 0u32);
+    for (i, review) in reviews.iter().enumerate() {
+        match review.rating {
+            4 | 5 => positive += 1,
+            1 | 2 => negative += 1,
+            _ => {}
+        }
+        total += review.rating;
+        if i as u32 + TREND_WINDOW >= count {
+            recent += review.rating;
+        }
+    }
+
+    let percent = |part: u32| (part * 100).checked_div(count).unwrap_or(0);
+    let recent_trend = match (total * 100).checked_div(count) {
+        None => Trend::Flat,
+        Some(overall) => {
+            let recent = recent * 100 / count.min(TREND_WINDOW);
+            if recent > overall + TREND_MARGIN {
+                Trend::Up
+            } else if recent + TREND_MARGIN < overall {
+                Trend::Down
+            } else {
+                Trend::Flat
+            }
+        }
+    };
+
+    ReportCard {
+        score: reputation.average_rating,
+        tier: scale_score(reputation.average_rating, 5),
+        review_count: reputation.total_reviews,
+        positive_pct: percent(positive),
+        negative_pct: percent(negative),
+        recent_trend,
+    }
+}
+
 pub fn get_reviews(env: &Env, subject: Address) -> soroban_sdk::Vec<Review> {
     let storage = Storage::new(env);
     storage.get_reviews(&subject)
 }
+
+pub fn get_review(env: &Env, review_id: u64) -> Option<Review> {
+    let storage = Storage::new(env);
+    let (subject, index) = storage.get_review_ref(review_id)?;
+    storage.get_reviews(&subject).get(index)
+}
+
+pub fn vote_review_helpful(env: &Env, voter: Address, review_id: u64) -> Result<u32, Error> {
+    voter.require_auth();
+
+    let storage = Storage::new(env);
+    let (subject, index) = storage
+        .get_review_ref(review_id)
+        .ok_or(Error::ReviewNotFound)?;
+    let mut reviews = storage.get_reviews(&subject);
+    let mut review = reviews.get(index).ok_or(Error::ReviewNotFound)?;
+
+    if review.reviewer == voter {
+        return Err(Error::InvalidInput);
+    }
+    if storage.has_helpful_vote(&voter, review_id) {
+        return Err(Error::AlreadyVoted);
+    }
+
+    storage.set_helpful_vote(&voter, review_id);
+    review.helpful_count += 1;
+    reviews.set(index, review.clone());
+    storage.set_reviews(&subject, &reviews);
+    Ok(review.helpful_count)
+}
+
+pub fn flag_review(
+    env: &Env,
+    flagger: Address,
+    review_id: u64,
+    reason_hash: BytesN<32>,
+) -> Result<u32, Error> {
+    flagger.require_auth();
+
+    let storage = Storage::new(env);
+    if storage.get_review_ref(review_id).is_none() {
+        return Err(Error::ReviewNotFound);
+    }
+    if storage.has_flag(&flagger, review_id) {
+        return Err(Error::AlreadyFlagged);
+    }
+
+    storage.set_flag(&flagger, review_id, &reason_hash);
+    let count = storage.get_flag_count(review_id) + 1;
+    storage.set_flag_count(review_id, count);
+
+    if count >= storage.get_flag_threshold() {
+        let mut queue = storage.get_moderation_queue();
+        if !queue.contains(review_id) {
+            queue.push_back(review_id);
+            storage.set_moderation_queue(&queue);
+        }
+    }
+    Ok(count)
+}
+
+pub fn set_flag_threshold(env: &Env, admin: Address, threshold: u32) -> Result<(), Error> {
+    require_admin(env, &admin)?;
+    if threshold == 0 {
+        return Err(Error::InvalidInput);
+    }
+    Storage::new(env).set_flag_threshold(threshold);
+    Ok(())
+}
+
+pub fn get_flagged_reviews(env: &Env, offset: u32, limit: u32) -> Vec<(u64, u32)> {
+    let storage = Storage::new(env);
+    let mut page = Vec::new(env);
+    for review_id in storage
+        .get_moderation_queue()
+        .iter()
+        .skip(offset as usize)
+        .take(limit.min(MAX_PAGE_SIZE) as usize)
+    {
+        page.push_back((review_id, storage.get_flag_count(review_id)));
+    }
+    page
+}
+
+pub fn bump_ttl(env: &Env, users: Vec<Address>) -> Result<u32, Error> {
+    if users.len() > MAX_BUMP_BATCH {
+        return Err(Error::InvalidInput);
+    }
+
+    let storage = Storage::new(env);
+    storage.extend_instance_ttl(BUMP_TTL);
+    let mut bumped = 0;
+    for user in users.iter() {
+        if storage.extend_user_ttl(&user, BUMP_TTL) {
+            bumped += 1;
+        }
+    }
+    Ok(bumped)
+}
+
+pub fn respond_to_review(
+    env: &Env,
+    responder: Address,
+    review_id: u64,
+    response_hash: BytesN<32>,
+) -> Result<ReviewResponse, Error> {
+    let storage = Storage::new(env);
+    let (subject, _) = storage
+        .get_review_ref(review_id)
+        .ok_or(Error::ReviewNotFound)?;
+
+    if responder != subject {
+        return Err(Error::Unauthorized);
+    }
+    responder.require_auth();
+
+    if storage.get_review_response(review_id).is_some() {
+        return Err(Error::AlreadyResponded);
+    }
+
+    let response = ReviewResponse {
+        responder,
+        response_hash,
+        timestamp: env.ledger().timestamp(),
+    };
+    storage.set_review_response(review_id, &response);
+    Ok(response)
+}
+
+pub fn get_review_response(env: &Env, review_id: u64) -> Option<ReviewResponse> {
+    Storage::new(env).get_review_response(review_id)
+}
+
+pub fn export_user_data(env: &Env, user: Address, offset: u32, limit: u32) -> UserExport {
+    let storage = Storage::new(env);
+    let all_reviews = storage.get_reviews(&user);
+
+    let mut reviews = Vec::new(env);
+    let mut responses = Vec::new(env);
+    for review in all_reviews
+        .iter()
+        .skip(offset as usize)
+        .take(limit.min(MAX_EXPORT_REVIEWS) as usize)
+    {
+        responses.push_back(storage.get_review_response(review.id));
+        reviews.push_back(review);
+    }
+
+    UserExport {
+        reputation: storage.get_reputation(&user),
+        reviews,
+        responses,
+        total_reviews: all_reviews.len(),
+    }
+}
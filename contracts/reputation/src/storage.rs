@@ -1,5 +1,40 @@
-use crate::types::{Reputation, Review};
-use soroban_sdk::{Address, Env, Vec};
+use crate::types::{
+    Attestation, BayesianPrior, DecayConfig, Reputation, Review, ReviewResponse, ReviewWindow,
+    ReviewerStats,
+};
+use soroban_sdk::{symbol_short, Address, BytesN, Env, Symbol, Vec};
+
+const ADMIN: Symbol = symbol_short!("admin");
+const RATE_LIMIT: Symbol = symbol_short!("rl_max");
+const RATE_COUNT: Symbol = symbol_short!("rl_cnt");
+const RATE_WINDOW: Symbol = symbol_short!("rl_win");
+const RATE_TIMES: Symbol = symbol_short!("rl_times");
+const DUP_CHECK: Symbol = symbol_short!("dup_chk");
+const RECENT_HASHES: Symbol = symbol_short!("c_hashes");
+const ATTESTATION: Symbol = symbol_short!("attest");
+const MIN_EVENT_DELTA: Symbol = symbol_short!("ev_delta");
+const WATCH_THRESHOLD: Symbol = symbol_short!("watch_thr");
+const WATCHLIST: Symbol = symbol_short!("watchlist");
+const REVIEW_COUNT: Symbol = symbol_short!("rev_cnt");
+const REVIEW_REF: Symbol = symbol_short!("rev_ref");
+const HELPFUL_VOTE: Symbol = symbol_short!("h_vote");
+const TX_REVIEW: Symbol = symbol_short!("tx_review");
+const FLAG: Symbol = symbol_short!("flag");
+const FLAG_COUNT: Symbol = symbol_short!("flag_cnt");
+const FLAGGERS: Symbol = symbol_short!("flaggers");
+const FLAG_THRESHOLD: Symbol = symbol_short!("flag_thr");
+const MOD_QUEUE: Symbol = symbol_short!("mod_queue");
+const SCORE_DIST: Symbol = symbol_short!("score_dst");
+const RANK_BUCKET: Symbol = symbol_short!("rank_bkt");
+const DECAY_CONFIG: Symbol = symbol_short!("decay");
+const BAYES_PRIOR: Symbol = symbol_short!("bayes");
+const REVIEW_RESPONSE: Symbol = symbol_short!("rev_resp");
+const EDIT_WINDOW: Symbol = symbol_short!("edit_win");
+const REVIEWER_STATS: Symbol = symbol_short!("rv_stats");
+const ESCROW_CONTRACT: Symbol = symbol_short!("escrow");
+
+/// Reviews can be edited for 24 hours unless the admin changes it
+const DEFAULT_EDIT_WINDOW: u64 = 24 * 60 * 60;
 
 pub struct Storage {
     env: Env,
@@ -38,9 +73,399 @@ impl Storage {
         self.env.storage().persistent().set(&key, reviews);
     }
 
+    /// Appends the review and indexes its id to the subject and position
     pub fn add_review(&self, user: &Address, review: Review) {
         let mut reviews = self.get_reviews(user);
+        let key = (review.id, REVIEW_REF);
+        self.env
+            .storage()
+            .persistent()
+            .set(&key, &(user.clone(), reviews.len()));
         reviews.push_back(review);
         self.set_reviews(user, &reviews);
     }
+
+    /// Removes the review at `index` and re-indexes the reviews after it
+    pub fn remove_review(&self, user: &Address, index: u32) -> Option<Review> {
+        let mut reviews = self.get_reviews(user);
+        let review = reviews.get(index)?;
+        reviews.remove(index);
+
+        let persistent = self.env.storage().persistent();
+        persistent.remove(&(review.id, REVIEW_REF));
+        for i in index..reviews.len() {
+            let moved = reviews.get_unchecked(i);
+            persistent.set(&(moved.id, REVIEW_REF), &(user.clone(), i));
+        }
+        self.set_reviews(user, &reviews);
+        Some(review)
+    }
+
+    pub fn next_review_id(&self) -> u64 {
+        let id = self
+            .env
+            .storage()
+            .instance()
+            .get::<_, u64>(&REVIEW_COUNT)
+            .unwrap_or(0)
+            + 1;
+        self.env.storage().instance().set(&REVIEW_COUNT, &id);
+        id
+    }
+
+    /// Subject and position in the subject's review list for a review id
+    pub fn get_review_ref(&self, review_id: u64) -> Option<(Address, u32)> {
+        let key = (review_id, REVIEW_REF);
+        self.env.storage().persistent().get(&key)
+    }
+
+    pub fn has_helpful_vote(&self, voter: &Address, review_id: u64) -> bool {
+        let key = (voter.clone(), review_id, HELPFUL_VOTE);
+        self.env.storage().persistent().has(&key)
+    }
+
+    pub fn set_helpful_vote(&self, voter: &Address, review_id: u64) {
+        let key = (voter.clone(), review_id, HELPFUL_VOTE);
+        self.env.storage().persistent().set(&key, &true);
+    }
+
+    /// Whether `reviewer` already reviewed the counterparty of this escrow
+    pub fn has_transaction_review(&self, transaction_id: u128, reviewer: &Address) -> bool {
+        let key = (transaction_id, reviewer.clone(), TX_REVIEW);
+        self.env.storage().persistent().has(&key)
+    }
+
+    pub fn set_transaction_review(&self, transaction_id: u128, reviewer: &Address) {
+        let key = (transaction_id, reviewer.clone(), TX_REVIEW);
+        self.env.storage().persistent().set(&key, &true);
+    }
+
+    pub fn has_flag(&self, flagger: &Address, review_id: u64) -> bool {
+        let key = (flagger.clone(), review_id, FLAG);
+        self.env.storage().persistent().has(&key)
+    }
+
+    /// Records a flag with the hash of the flagger's off-chain reason
+    pub fn set_flag(&self, flagger: &Address, review_id: u64, reason_hash: &BytesN<32>) {
+        let key = (flagger.clone(), review_id, FLAG);
+        self.env.storage().persistent().set(&key, reason_hash);
+
+        let flaggers_key = (review_id, FLAGGERS);
+        let mut flaggers: Vec<Address> = self
+            .env
+            .storage()
+            .persistent()
+            .get(&flaggers_key)
+            .unwrap_or(Vec::new(&self.env));
+        flaggers.push_back(flagger.clone());
+        self.env
+            .storage()
+            .persistent()
+            .set(&flaggers_key, &flaggers);
+    }
+
+    /// Drops every flag on a review along with its count
+    pub fn remove_flags(&self, review_id: u64) {
+        let persistent = self.env.storage().persistent();
+        let flaggers_key = (review_id, FLAGGERS);
+        let flaggers: Vec<Address> = persistent.get(&flaggers_key).unwrap_or(Vec::new(&self.env));
+        for flagger in flaggers.iter() {
+            persistent.remove(&(flagger, review_id, FLAG));
+        }
+        persistent.remove(&flaggers_key);
+        persistent.remove(&(review_id, FLAG_COUNT));
+    }
+
+    pub fn get_flag_count(&self, review_id: u64) -> u32 {
+        let key = (review_id, FLAG_COUNT);
+        self.env.storage().persistent().get(&key).unwrap_or(0)
+    }
+
+    pub fn set_flag_count(&self, review_id: u64, count: u32) {
+        let key = (review_id, FLAG_COUNT);
+        self.env.storage().persistent().set(&key, &count);
+    }
+
+    /// Extends a user's reputation and review list to `ttl` ledgers; false if the user has none
+    pub fn extend_user_ttl(&self, user: &Address, ttl: u32) -> bool {
+        let persistent = self.env.storage().persistent();
+        if !persistent.has(user) {
+            return false;
+        }
+        persistent.extend_ttl(user, ttl, ttl);
+        let reviews_key = (user.clone(), soroban_sdk::symbol_short!("reviews"));
+        if persistent.has(&reviews_key) {
+            persistent.extend_ttl(&reviews_key, ttl, ttl);
+        }
+        true
+    }
+
+    pub fn extend_instance_ttl(&self, ttl: u32) {
+        self.env.storage().instance().extend_ttl(ttl, ttl);
+    }
+
+    pub fn get_review_response(&self, review_id: u64) -> Option<ReviewResponse> {
+        let key = (review_id, REVIEW_RESPONSE);
+        self.env.storage().persistent().get(&key)
+    }
+
+    pub fn set_review_response(&self, review_id: u64, response: &ReviewResponse) {
+        let key = (review_id, REVIEW_RESPONSE);
+        self.env.storage().persistent().set(&key, response);
+    }
+
+    pub fn remove_review_response(&self, review_id: u64) {
+        let key = (review_id, REVIEW_RESPONSE);
+        self.env.storage().persistent().remove(&key);
+    }
+
+    /// Seconds after submission during which a reviewer may edit their review
+    pub fn get_edit_window(&self) -> u64 {
+        self.env
+            .storage()
+            .instance()
+            .get(&EDIT_WINDOW)
+            .unwrap_or(DEFAULT_EDIT_WINDOW)
+    }
+
+    pub fn set_edit_window(&self, window: u64) {
+        self.env.storage().instance().set(&EDIT_WINDOW, &window);
+    }
+
+    pub fn get_reviewer_stats(&self, reviewer: &Address) -> ReviewerStats {
+        let key = (reviewer.clone(), REVIEWER_STATS);
+        self.env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_default()
+    }
+
+    pub fn set_reviewer_stats(&self, reviewer: &Address, stats: &ReviewerStats) {
+        let key = (reviewer.clone(), REVIEWER_STATS);
+        self.env.storage().persistent().set(&key, stats);
+    }
+
+    /// Escrow contract that reviews must be backed by; None accepts unverified reviews
+    pub fn get_escrow_contract(&self) -> Option<Address> {
+        self.env.storage().instance().get(&ESCROW_CONTRACT)
+    }
+
+    pub fn set_escrow_contract(&self, escrow: &Option<Address>) {
+        match escrow {
+            Some(escrow) => self.env.storage().instance().set(&ESCROW_CONTRACT, escrow),
+            None => self.env.storage().instance().remove(&ESCROW_CONTRACT),
+        }
+    }
+
+    pub fn get_admin(&self) -> Option<Address> {
+        self.env.storage().instance().get(&ADMIN)
+    }
+
+    pub fn set_admin(&self, admin: &Address) {
+        self.env.storage().instance().set(&ADMIN, admin);
+    }
+
+    /// Maximum reviews per reviewer per ledger; 0 means unlimited
+    pub fn get_rate_limit(&self) -> u32 {
+        self.env.storage().instance().get(&RATE_LIMIT).unwrap_or(0)
+    }
+
+    pub fn set_rate_limit(&self, limit: u32) {
+        self.env.storage().instance().set(&RATE_LIMIT, &limit);
+    }
+
+    // Per-ledger counters only matter for the current sequence, so they live in temporary storage
+    pub fn get_ledger_review_count(&self, reviewer: &Address, sequence: u32) -> u32 {
+        let key = (reviewer.clone(), RATE_COUNT, sequence);
+        self.env.storage().temporary().get(&key).unwrap_or(0)
+    }
+
+    pub fn set_ledger_review_count(&self, reviewer: &Address, sequence: u32, count: u32) {
+        let key = (reviewer.clone(), RATE_COUNT, sequence);
+        self.env.storage().temporary().set(&key, &count);
+    }
+
+    pub fn get_review_window(&self) -> ReviewWindow {
+        self.env
+            .storage()
+            .instance()
+            .get(&RATE_WINDOW)
+            .unwrap_or_default()
+    }
+
+    pub fn set_review_window(&self, window: &ReviewWindow) {
+        self.env.storage().instance().set(&RATE_WINDOW, window);
+    }
+
+    /// Timestamps of the reviewer's latest reviews, oldest first, at most `max_reviews` of them
+    pub fn get_review_times(&self, reviewer: &Address) -> Vec<u64> {
+        let key = (reviewer.clone(), RATE_TIMES);
+        self.env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(Vec::new(&self.env))
+    }
+
+    pub fn set_review_times(&self, reviewer: &Address, times: &Vec<u64>) {
+        let key = (reviewer.clone(), RATE_TIMES);
+        self.env.storage().persistent().set(&key, times);
+    }
+
+    pub fn get_duplicate_check(&self) -> bool {
+        self.env
+            .storage()
+            .instance()
+            .get(&DUP_CHECK)
+            .unwrap_or(false)
+    }
+
+    pub fn set_duplicate_check(&self, enabled: bool) {
+        self.env.storage().instance().set(&DUP_CHECK, &enabled);
+    }
+
+    pub fn get_recent_comment_hashes(&self, reviewer: &Address) -> Vec<BytesN<32>> {
+        let key = (reviewer.clone(), RECENT_HASHES);
+        self.env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(Vec::new(&self.env))
+    }
+
+    pub fn set_recent_comment_hashes(&self, reviewer: &Address, hashes: &Vec<BytesN<32>>) {
+        let key = (reviewer.clone(), RECENT_HASHES);
+        self.env.storage().persistent().set(&key, hashes);
+    }
+
+    pub fn get_attestation(&self, user: &Address) -> Option<Attestation> {
+        let key = (user.clone(), ATTESTATION);
+        self.env.storage().persistent().get(&key)
+    }
+
+    pub fn set_attestation(&self, user: &Address, attestation: &Attestation) {
+        let key = (user.clone(), ATTESTATION);
+        self.env.storage().persistent().set(&key, attestation);
+    }
+
+    /// Smallest average_rating change that publishes an event; 0 emits on every change
+    pub fn get_min_event_delta(&self) -> u32 {
+        self.env
+            .storage()
+            .instance()
+            .get(&MIN_EVENT_DELTA)
+            .unwrap_or(0)
+    }
+
+    pub fn set_min_event_delta(&self, delta: u32) {
+        self.env.storage().instance().set(&MIN_EVENT_DELTA, &delta);
+    }
+
+    /// Users scoring below this are put on the moderation watchlist; 0 disables it
+    pub fn get_watch_threshold(&self) -> u32 {
+        self.env
+            .storage()
+            .instance()
+            .get(&WATCH_THRESHOLD)
+            .unwrap_or(0)
+    }
+
+    pub fn set_watch_threshold(&self, threshold: u32) {
+        self.env
+            .storage()
+            .instance()
+            .set(&WATCH_THRESHOLD, &threshold);
+    }
+
+    pub fn get_decay_config(&self) -> DecayConfig {
+        self.env
+            .storage()
+            .instance()
+            .get(&DECAY_CONFIG)
+            .unwrap_or_default()
+    }
+
+    pub fn set_decay_config(&self, config: &DecayConfig) {
+        self.env.storage().instance().set(&DECAY_CONFIG, config);
+    }
+
+    pub fn get_bayesian_prior(&self) -> BayesianPrior {
+        self.env
+            .storage()
+            .instance()
+            .get(&BAYES_PRIOR)
+            .unwrap_or_default()
+    }
+
+    pub fn set_bayesian_prior(&self, prior: &BayesianPrior) {
+        self.env.storage().instance().set(&BAYES_PRIOR, prior);
+    }
+
+    pub fn get_watchlist(&self) -> Vec<Address> {
+        self.env
+            .storage()
+            .persistent()
+            .get(&WATCHLIST)
+            .unwrap_or(Vec::new(&self.env))
+    }
+
+    pub fn set_watchlist(&self, watchlist: &Vec<Address>) {
+        self.env.storage().persistent().set(&WATCHLIST, watchlist);
+    }
+
+    /// Number of ranked users per score bucket, lowest bucket first
+    pub fn get_score_distribution(&self) -> Vec<u32> {
+        self.env
+            .storage()
+            .instance()
+            .get(&SCORE_DIST)
+            .unwrap_or(Vec::new(&self.env))
+    }
+
+    pub fn set_score_distribution(&self, distribution: &Vec<u32>) {
+        self.env.storage().instance().set(&SCORE_DIST, distribution);
+    }
+
+    /// Bucket the user is currently counted in, if they are ranked
+    pub fn get_rank_bucket(&self, user: &Address) -> Option<u32> {
+        let key = (user.clone(), RANK_BUCKET);
+        self.env.storage().persistent().get(&key)
+    }
+
+    pub fn set_rank_bucket(&self, user: &Address, bucket: Option<u32>) {
+        let key = (user.clone(), RANK_BUCKET);
+        match bucket {
+            Some(bucket) => self.env.storage().persistent().set(&key, &bucket),
+            None => self.env.storage().persistent().remove(&key),
+        }
+    }
+
+    /// Flags after which a review enters the moderation queue
+    pub fn get_flag_threshold(&self) -> u32 {
+        self.env
+            .storage()
+            .instance()
+            .get(&FLAG_THRESHOLD)
+            .unwrap_or(3)
+    }
+
+    pub fn set_flag_threshold(&self, threshold: u32) {
+        self.env
+            .storage()
+            .instance()
+            .set(&FLAG_THRESHOLD, &threshold);
+    }
+
+    pub fn get_moderation_queue(&self) -> Vec<u64> {
+        self.env
+            .storage()
+            .persistent()
+            .get(&MOD_QUEUE)
+            .unwrap_or(Vec::new(&self.env))
+    }
+
+    pub fn set_moderation_queue(&self, queue: &Vec<u64>) {
+        self.env.storage().persistent().set(&MOD_QUEUE, queue);
+    }
 }
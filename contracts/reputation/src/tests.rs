@@ -1,8 +1,13 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::testutils::Address as _;
-use soroban_sdk::{Env, String};
+use crate::events::{
+    ReputationChangedEventData, REASON_ADMIN_ADJUST, REASON_DECAY, REASON_REMOVAL, REASON_REVIEW,
+};
+use crate::storage::Storage;
+use crate::types::{BayesianPrior, DecayConfig, ReputationConfig, ReviewWindow, Trend};
+use soroban_sdk::testutils::{Address as _, Events, Ledger};
+use soroban_sdk::{contract, contractimpl, symbol_short, vec, BytesN, Env, Event, String, Symbol};
 
 #[test]
 fn test_reputation_flow() {
@@ -81,3 +86,1151 @@ fn test_invalid_rating_fails() {
 
     client.submit_review(&reviewer, &subject, &6, &String::from_str(&env, "Too good"));
 }
+
+#[test]
+fn test_get_score_scaled() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(ReputationContract, ());
+    let client = ReputationContractClient::new(&env, &contract_id);
+
+    let reviewer1 = Address::generate(&env);
+    let reviewer2 = Address::generate(&env);
+    let subject = Address::generate(&env);
+
+    client.submit_review(&reviewer1, &subject, &5, &String::from_str(&env, "Great"));
+    client.submit_review(&reviewer2, &subject, &4, &String::from_str(&env, "Good"));
+
+    // Internal score is 450
+    assert_eq!(client.get_score_scaled(&subject, &5), 5);
+    assert_eq!(client.get_score_scaled(&subject, &10), 9);
+    assert_eq!(client.get_score_scaled(&subject, &100), 90);
+    assert_eq!(client.get_score_scaled(&subject, &500), 450);
+
+    assert_eq!(
+        client.try_get_score_scaled(&subject, &7),
+        Err(Ok(Error::InvalidInput))
+    );
+}
+
+#[test]
+fn test_export_user_data() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(ReputationContract, ());
+    let client = ReputationContractClient::new(&env, &contract_id);
+
+    let reviewer1 = Address::generate(&env);
+    let reviewer2 = Address::generate(&env);
+    let subject = Address::generate(&env);
+
+    client.submit_review(&reviewer1, &subject, &5, &String::from_str(&env, "Great"));
+    client.submit_review(&reviewer2, &subject, &3, &String::from_str(&env, "Okay"));
+
+    let review_id = client.get_reviews(&subject).get(0).unwrap().id;
+    let response =
+        client.respond_to_review(&subject, &review_id, &BytesN::from_array(&env, &[1; 32]));
+
+    let export = client.export_user_data(&subject, &0, &10);
+    assert_eq!(export.reputation, client.get_reputation(&subject));
+    assert_eq!(export.total_reviews, 2);
+    assert_eq!(export.reviews.len(), 2);
+    assert_eq!(export.reviews.get(0).unwrap().reviewer, reviewer1);
+    assert_eq!(export.reviews.get(1).unwrap().rating, 3);
+    assert_eq!(export.responses, vec![&env, Some(response), None]);
+
+    // Later pages pick up where the previous one stopped
+    let page = client.export_user_data(&subject, &1, &1);
+    assert_eq!(page.total_reviews, 2);
+    assert_eq!(page.reviews.len(), 1);
+    assert_eq!(page.reviews.get(0).unwrap().reviewer, reviewer2);
+    assert_eq!(page.responses, vec![&env, None]);
+    assert_eq!(client.export_user_data(&subject, &2, &10).reviews.len(), 0);
+
+    let empty = client.export_user_data(&reviewer1, &0, &10);
+    assert_eq!(empty.reputation.total_reviews, 0);
+    assert_eq!(empty.total_reviews, 0);
+    assert_eq!(empty.reviews.len(), 0);
+}
+
+#[test]
+fn test_review_rate_limit_per_ledger() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(ReputationContract, ());
+    let client = ReputationContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let reviewer = Address::generate(&env);
+    client.initialize(&admin);
+    assert_eq!(
+        client.try_initialize(&admin),
+        Err(Ok(Error::AlreadyInitialized))
+    );
+    assert_eq!(
+        client.try_set_review_rate_limit(&reviewer, &2),
+        Err(Ok(Error::Unauthorized))
+    );
+    client.set_review_rate_limit(&admin, &2);
+
+    let comment = String::from_str(&env, "Fine");
+    client.submit_review(&reviewer, &Address::generate(&env), &4, &comment);
+    client.submit_review(&reviewer, &Address::generate(&env), &4, &comment);
+    assert_eq!(
+        client.try_submit_review(&reviewer, &Address::generate(&env), &4, &comment),
+        Err(Ok(Error::RateLimited.into()))
+    );
+
+    env.ledger().with_mut(|li| li.sequence_number += 1);
+    client.submit_review(&reviewer, &Address::generate(&env), &4, &comment);
+}
+
+#[test]
+fn test_review_rate_limit_sliding_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 10_000);
+
+    let client = ReputationContractClient::new(&env, &env.register(ReputationContract, ()));
+    let admin = Address::generate(&env);
+    let reviewer = Address::generate(&env);
+    let comment = String::from_str(&env, "Fine");
+    client.initialize(&admin);
+
+    let window = ReviewWindow {
+        max_reviews: 3,
+        window: 3_600,
+    };
+    assert_eq!(
+        client.try_set_review_window(
+            &admin,
+            &ReviewWindow {
+                max_reviews: 21,
+                window: 60
+            }
+        ),
+        Err(Ok(Error::InvalidInput))
+    );
+    assert_eq!(
+        client.try_set_review_window(&reviewer, &window),
+        Err(Ok(Error::Unauthorized))
+    );
+    client.set_review_window(&admin, &window);
+    assert_eq!(client.get_review_window(), window);
+
+    // A burst hits the limit even across ledgers
+    for _ in 0..3 {
+        client.submit_review(&reviewer, &Address::generate(&env), &4, &comment);
+        env.ledger().with_mut(|li| li.timestamp += 60);
+    }
+    assert_eq!(
+        client.try_submit_review(&reviewer, &Address::generate(&env), &4, &comment),
+        Err(Ok(Error::RateLimited.into()))
+    );
+
+    // Other reviewers are unaffected
+    client.submit_review(&Address::generate(&env), &reviewer, &4, &comment);
+
+    // Once the first review leaves the window one more slot opens
+    env.ledger().with_mut(|li| li.timestamp = 10_000 + 3_600);
+    client.submit_review(&reviewer, &Address::generate(&env), &4, &comment);
+    assert_eq!(
+        client.try_submit_review(&reviewer, &Address::generate(&env), &4, &comment),
+        Err(Ok(Error::RateLimited.into()))
+    );
+
+    // Spaced-out reviews are never limited
+    for _ in 0..5 {
+        env.ledger().with_mut(|li| li.timestamp += 1_800);
+        client.submit_review(&reviewer, &Address::generate(&env), &4, &comment);
+    }
+}
+
+#[test]
+fn test_duplicate_comment_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(ReputationContract, ());
+    let client = ReputationContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let reviewer = Address::generate(&env);
+    client.initialize(&admin);
+
+    let comment = String::from_str(&env, "Best seller ever!!!");
+    // Disabled by default
+    client.submit_review(&reviewer, &Address::generate(&env), &5, &comment);
+    client.submit_review(&reviewer, &Address::generate(&env), &5, &comment);
+
+    client.set_duplicate_comment_check(&admin, &true);
+    client.submit_review(&reviewer, &Address::generate(&env), &5, &comment);
+    assert_eq!(
+        client.try_submit_review(&reviewer, &Address::generate(&env), &5, &comment),
+        Err(Ok(Error::DuplicateComment.into()))
+    );
+
+    // Other reviewers and other comments are unaffected
+    client.submit_review(
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &5,
+        &comment,
+    );
+    client.submit_review(
+        &reviewer,
+        &Address::generate(&env),
+        &4,
+        &String::from_str(&env, "Solid"),
+    );
+}
+
+#[test]
+fn test_attested_reputation_converges() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(ReputationContract, ());
+    let client = ReputationContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    client.initialize(&admin);
+
+    // 40 external reviews averaging 5.00 count for the capped weight of 10
+    let rep = client.admin_attest_reputation(&admin, &user, &500, &40, &symbol_short!("partner"));
+    assert_eq!(rep.average_rating, 500);
+    assert_eq!(rep.total_reviews, 0);
+    let attestation = client.get_attestation(&user).unwrap();
+    assert_eq!(attestation.source, symbol_short!("partner"));
+    assert_eq!(attestation.weight, 10);
+
+    // Native 1-star reviews pull the score down towards 100
+    let comment = String::from_str(&env, "Bad");
+    let mut last = 500;
+    for _ in 0..10 {
+        let rep = client.submit_review(&Address::generate(&env), &user, &1, &comment);
+        assert!(rep.average_rating < last);
+        last = rep.average_rating;
+    }
+    assert_eq!(last, 300); // (10 * 100 + 500 * 10) / 20
+    for _ in 0..30 {
+        last = client
+            .submit_review(&Address::generate(&env), &user, &1, &comment)
+            .average_rating;
+    }
+    assert_eq!(last, 180); // (40 * 100 + 500 * 10) / 50
+
+    // Only users without native reviews can be attested
+    assert_eq!(
+        client.try_admin_attest_reputation(&admin, &user, &500, &40, &symbol_short!("partner")),
+        Err(Ok(Error::HasNativeReviews))
+    );
+}
+
+#[test]
+fn test_min_event_delta_suppresses_small_changes() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(ReputationContract, ());
+    let client = ReputationContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_min_event_delta(&admin, &10);
+
+    let changed = |old_score: u32, new_score: u32| {
+        ReputationChangedEventData {
+            user: user.clone(),
+            old_score,
+            new_score,
+            reason: Symbol::new(&env, REASON_REVIEW),
+        }
+        .to_xdr(&env, &client.address)
+    };
+
+    let comment = String::from_str(&env, "Review");
+    client.submit_review(&Address::generate(&env), &user, &5, &comment);
+    assert!(env.events().all().events().contains(&changed(0, 500)));
+    for _ in 0..19 {
+        client.submit_review(&Address::generate(&env), &user, &5, &comment);
+    }
+
+    // 20 x 5 stars then a 4: 500 -> 495 stays below the delta
+    client.submit_review(&Address::generate(&env), &user, &4, &comment);
+    assert!(!env.events().all().events().contains(&changed(500, 495)));
+
+    // A 1-star review moves it 495 -> 477
+    client.submit_review(&Address::generate(&env), &user, &1, &comment);
+    assert!(env.events().all().events().contains(&changed(495, 477)));
+}
+
+#[test]
+fn test_reputation_event_reason() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(ReputationContract, ());
+    let client = ReputationContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    client.initialize(&admin);
+
+    let changed = |old_score: u32, new_score: u32, reason: &str| {
+        ReputationChangedEventData {
+            user: user.clone(),
+            old_score,
+            new_score,
+            reason: Symbol::new(&env, reason),
+        }
+        .to_xdr(&env, &client.address)
+    };
+
+    client.admin_attest_reputation(&admin, &user, &400, &10, &symbol_short!("partner"));
+    assert!(env
+        .events()
+        .all()
+        .events()
+        .contains(&changed(0, 400, REASON_ADMIN_ADJUST)));
+
+    // weight 5: (100 + 400 * 5) / 6
+    client.submit_review(
+        &Address::generate(&env),
+        &user,
+        &1,
+        &String::from_str(&env, "Meh"),
+    );
+    assert!(env
+        .events()
+        .all()
+        .events()
+        .contains(&changed(400, 350, REASON_REVIEW)));
+
+    let review_id = client.get_reviews(&user).get(0).unwrap().id;
+    client.admin_remove_review(&admin, &review_id);
+    assert!(env
+        .events()
+        .all()
+        .events()
+        .contains(&changed(350, 400, REASON_REMOVAL)));
+}
+
+#[test]
+fn test_watchlist_tracks_low_scores() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(ReputationContract, ());
+    let client = ReputationContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let good_seller = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_watch_threshold(&admin, &300);
+
+    let comment = String::from_str(&env, "Review");
+    client.submit_review(&Address::generate(&env), &good_seller, &5, &comment);
+    client.submit_review(&Address::generate(&env), &seller, &1, &comment);
+
+    let watchlist = client.get_watchlist(&0, &10);
+    assert_eq!(watchlist.len(), 1);
+    assert_eq!(watchlist.get(0).unwrap(), (seller.clone(), 100));
+
+    // 1 + 5 + 5 => 366, back above the threshold
+    client.submit_review(&Address::generate(&env), &seller, &5, &comment);
+    client.submit_review(&Address::generate(&env), &seller, &5, &comment);
+    assert_eq!(client.get_watchlist(&0, &10).len(), 0);
+}
+
+#[test]
+fn test_config_round_trip() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(ReputationContract, ());
+    let client = ReputationContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let defaults = client.get_config();
+    assert_eq!(defaults.max_reviews_per_ledger, 0);
+    assert!(!defaults.duplicate_comment_check);
+
+    assert_eq!(defaults.flag_threshold, 3);
+    assert_eq!(defaults.escrow_contract, None);
+
+    let config = ReputationConfig {
+        max_reviews_per_ledger: 3,
+        duplicate_comment_check: true,
+        min_event_delta: 25,
+        watch_threshold: 200,
+        decay: DecayConfig {
+            percent_per_period: 10,
+        },
+        bayesian_prior: BayesianPrior {
+            mean: 350,
+            weight: 5,
+        },
+        review_window: ReviewWindow {
+            max_reviews: 4,
+            window: 3600,
+        },
+        edit_window: 7200,
+        flag_threshold: 2,
+        escrow_contract: Some(Address::generate(&env)),
+    };
+    client.set_config(&admin, &config);
+    assert_eq!(client.get_config(), config);
+    assert_eq!(client.get_decay_config(), config.decay);
+    assert_eq!(client.get_bayesian_prior(), config.bayesian_prior);
+    assert_eq!(client.get_review_window(), config.review_window);
+
+    // Granular setters are reflected too
+    client.set_min_event_delta(&admin, &5);
+    assert_eq!(client.get_config().min_event_delta, 5);
+    client.set_edit_window(&admin, &60);
+    assert_eq!(client.get_config().edit_window, 60);
+
+    // Out-of-range values are rejected as a whole
+    let mut invalid = config.clone();
+    invalid.flag_threshold = 0;
+    assert_eq!(
+        client.try_set_config(&admin, &invalid),
+        Err(Ok(Error::InvalidInput))
+    );
+    assert_eq!(client.get_config().flag_threshold, 2);
+
+    assert_eq!(
+        client.try_set_config(&Address::generate(&env), &config),
+        Err(Ok(Error::Unauthorized))
+    );
+}
+
+#[test]
+fn test_vote_review_helpful() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(ReputationContract, ());
+    let client = ReputationContractClient::new(&env, &contract_id);
+
+    let reviewer = Address::generate(&env);
+    let subject = Address::generate(&env);
+    let voter = Address::generate(&env);
+
+    client.submit_review(&reviewer, &subject, &4, &String::from_str(&env, "Solid"));
+    let review_id = client.get_reviews(&subject).get(0).unwrap().id;
+
+    assert_eq!(client.vote_review_helpful(&voter, &review_id), 1);
+    assert_eq!(
+        client.try_vote_review_helpful(&voter, &review_id),
+        Err(Ok(Error::AlreadyVoted))
+    );
+    assert_eq!(
+        client.try_vote_review_helpful(&reviewer, &review_id),
+        Err(Ok(Error::InvalidInput))
+    );
+    assert_eq!(
+        client.try_vote_review_helpful(&voter, &(review_id + 1)),
+        Err(Ok(Error::ReviewNotFound))
+    );
+
+    let review = client.get_review(&review_id).unwrap();
+    assert_eq!(review.helpful_count, 1);
+    assert_eq!(review.reviewer, reviewer);
+    assert_eq!(
+        client.get_reviews(&subject).get(0).unwrap().helpful_count,
+        1
+    );
+}
+
+#[test]
+fn test_bump_ttl_keeps_reputations_alive() {
+    use soroban_sdk::testutils::storage::Persistent as _;
+
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(ReputationContract, ());
+    let client = ReputationContractClient::new(&env, &contract_id);
+
+    let reviewer = Address::generate(&env);
+    let active = Address::generate(&env);
+    let idle = Address::generate(&env);
+    client.submit_review(&reviewer, &active, &5, &String::from_str(&env, "Fast"));
+    client.submit_review(&reviewer, &idle, &3, &String::from_str(&env, "Slow"));
+
+    let mut users = Vec::new(&env);
+    users.push_back(active.clone());
+    users.push_back(Address::generate(&env));
+    assert_eq!(client.bump_ttl(&users), 1);
+
+    let ttl =
+        |user: &Address| env.as_contract(&contract_id, || env.storage().persistent().get_ttl(user));
+    assert_eq!(ttl(&active), reputation::BUMP_TTL);
+    assert!(ttl(&idle) < 100_000);
+
+    // Past the default entry lifetime only the bumped user is still live
+    env.ledger().with_mut(|li| li.sequence_number += 100_000);
+    assert_eq!(ttl(&active), reputation::BUMP_TTL - 100_000);
+    assert_eq!(client.get_reputation(&active).average_rating, 500);
+    assert_eq!(client.get_reviews(&active).len(), 1);
+
+    let mut too_many = Vec::new(&env);
+    for _ in 0..51 {
+        too_many.push_back(Address::generate(&env));
+    }
+    assert_eq!(client.try_bump_ttl(&too_many), Err(Ok(Error::InvalidInput)));
+}
+
+#[test]
+fn test_preview_decay() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let contract_id = env.register(ReputationContract, ());
+    let client = ReputationContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let subject = Address::generate(&env);
+    client.submit_review(
+        &Address::generate(&env),
+        &subject,
+        &5,
+        &String::from_str(&env, "Great"),
+    );
+
+    // Decay is off until configured
+    env.ledger().set_timestamp(1_000 + 65 * 86_400);
+    assert_eq!(client.preview_decay(&subject), (500, 500, 2));
+
+    client.set_decay_config(
+        &admin,
+        &DecayConfig {
+            percent_per_period: 10,
+        },
+    );
+    assert_eq!(client.preview_decay(&subject), (500, 405, 2));
+    // Read-only: the stored score is untouched
+    assert_eq!(client.get_reputation(&subject).average_rating, 500);
+
+    // Fresh activity restarts the clock
+    client.submit_review(
+        &Address::generate(&env),
+        &subject,
+        &5,
+        &String::from_str(&env, "Still great"),
+    );
+    assert_eq!(client.preview_decay(&subject), (500, 500, 0));
+
+    assert_eq!(
+        client.try_set_decay_config(
+            &admin,
+            &DecayConfig {
+                percent_per_period: 101
+            }
+        ),
+        Err(Ok(Error::InvalidInput))
+    );
+}
+
+#[test]
+fn test_apply_decay_demotes_inactive_user() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let contract_id = env.register(ReputationContract, ());
+    let client = ReputationContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_decay_config(
+        &admin,
+        &DecayConfig {
+            percent_per_period: 10,
+        },
+    );
+
+    let subject = Address::generate(&env);
+    client.submit_review(
+        &Address::generate(&env),
+        &subject,
+        &5,
+        &String::from_str(&env, "Top seller"),
+    );
+    assert_eq!(client.get_score_scaled(&subject, &5), 5);
+
+    // Nothing is pending inside the first period
+    env.ledger().set_timestamp(1_000 + 29 * 86_400);
+    assert_eq!(client.apply_decay(&subject).average_rating, 500);
+
+    // A year of inactivity, with half a period left over
+    let year = 1_000 + 12 * 30 * 86_400 + 15 * 86_400;
+    env.ledger().set_timestamp(year);
+    let (current, decayed, periods) = client.preview_decay(&subject);
+    assert_eq!((current, periods), (500, 12));
+
+    let reputation = client.apply_decay(&subject);
+    assert!(env.events().all().events().contains(
+        &ReputationChangedEventData {
+            user: subject.clone(),
+            old_score: 500,
+            new_score: decayed,
+            reason: Symbol::new(&env, REASON_DECAY),
+        }
+        .to_xdr(&env, &contract_id)
+    ));
+    assert_eq!(reputation.average_rating, decayed);
+    assert_eq!(reputation.last_updated, 1_000 + 12 * 30 * 86_400);
+    assert!(decayed < 150);
+    assert_eq!(client.get_score_scaled(&subject, &5), 1);
+
+    // Applying again only counts the leftover partial period
+    assert_eq!(client.preview_decay(&subject), (decayed, decayed, 0));
+    assert_eq!(client.apply_decay(&subject).average_rating, decayed);
+
+    // Never below zero
+    env.ledger().set_timestamp(year + 1_000 * 30 * 86_400);
+    assert_eq!(client.apply_decay(&subject).average_rating, 0);
+}
+
+#[test]
+fn test_respond_to_review() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let contract_id = env.register(ReputationContract, ());
+    let client = ReputationContractClient::new(&env, &contract_id);
+
+    let reviewer = Address::generate(&env);
+    let subject = Address::generate(&env);
+    client.submit_review(
+        &reviewer,
+        &subject,
+        &1,
+        &String::from_str(&env, "Never arrived"),
+    );
+    let review_id = client.get_reviews(&subject).get(0).unwrap().id;
+    let hash = BytesN::from_array(&env, &[7; 32]);
+
+    assert_eq!(client.get_review_response(&review_id), None);
+    assert_eq!(
+        client.try_respond_to_review(&reviewer, &review_id, &hash),
+        Err(Ok(Error::Unauthorized))
+    );
+    assert_eq!(
+        client.try_respond_to_review(&subject, &(review_id + 1), &hash),
+        Err(Ok(Error::ReviewNotFound))
+    );
+
+    client.respond_to_review(&subject, &review_id, &hash);
+    let response = client.get_review_response(&review_id).unwrap();
+    assert_eq!(response.responder, subject);
+    assert_eq!(response.response_hash, hash);
+    assert_eq!(response.timestamp, 1_000);
+
+    assert_eq!(
+        client.try_respond_to_review(&subject, &review_id, &BytesN::from_array(&env, &[8; 32])),
+        Err(Ok(Error::AlreadyResponded))
+    );
+}
+
+#[test]
+fn test_edit_review_within_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let contract_id = env.register(ReputationContract, ());
+    let client = ReputationContractClient::new(&env, &contract_id);
+
+    let angry = Address::generate(&env);
+    let other = Address::generate(&env);
+    let subject = Address::generate(&env);
+    client.submit_review(&angry, &subject, &1, &String::from_str(&env, "Late!"));
+    client.submit_review(&other, &subject, &5, &String::from_str(&env, "Great"));
+    assert_eq!(client.get_reputation(&subject).average_rating, 300);
+    let review_id = client.get_reviews(&subject).get(0).unwrap().id;
+
+    assert_eq!(
+        client.try_edit_review(&other, &review_id, &4, &String::from_str(&env, "x")),
+        Err(Ok(Error::Unauthorized))
+    );
+    assert_eq!(
+        client.try_edit_review(&angry, &review_id, &6, &String::from_str(&env, "x")),
+        Err(Ok(Error::InvalidInput))
+    );
+
+    env.ledger().set_timestamp(1_000 + 3_600);
+    let reputation = client.edit_review(
+        &angry,
+        &review_id,
+        &4,
+        &String::from_str(&env, "Late, but worth it"),
+    );
+    assert_eq!(reputation.total_reviews, 2);
+    assert_eq!(reputation.total_score, 9);
+    assert_eq!(reputation.average_rating, 450);
+
+    let review = client.get_review(&review_id).unwrap();
+    assert_eq!(review.rating, 4);
+    assert_eq!(review.comment, String::from_str(&env, "Late, but worth it"));
+}
+
+#[test]
+fn test_edit_review_outside_window_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let contract_id = env.register(ReputationContract, ());
+    let client = ReputationContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let reviewer = Address::generate(&env);
+    let subject = Address::generate(&env);
+    client.submit_review(&reviewer, &subject, &2, &String::from_str(&env, "Meh"));
+    let review_id = client.get_reviews(&subject).get(0).unwrap().id;
+
+    env.ledger().set_timestamp(1_000 + 86_400 + 1);
+    assert_eq!(
+        client.try_edit_review(&reviewer, &review_id, &5, &String::from_str(&env, "Great")),
+        Err(Ok(Error::EditWindowClosed))
+    );
+
+    // A longer admin-set window reopens it
+    client.set_edit_window(&admin, &(7 * 86_400));
+    client.edit_review(&reviewer, &review_id, &5, &String::from_str(&env, "Great"));
+    assert_eq!(client.get_reputation(&subject).average_rating, 500);
+}
+
+#[test]
+fn test_reviewer_stats_track_removed_reviews() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(ReputationContract, ());
+    let client = ReputationContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let bot = Address::generate(&env);
+    let honest = Address::generate(&env);
+    let subject = Address::generate(&env);
+    let other = Address::generate(&env);
+    client.submit_review(&bot, &subject, &1, &String::from_str(&env, "Scam"));
+    client.submit_review(&honest, &subject, &5, &String::from_str(&env, "Great"));
+    client.submit_review(&bot, &other, &1, &String::from_str(&env, "Scam again"));
+
+    let stats = client.get_reviewer_stats(&bot);
+    assert_eq!((stats.reviews_submitted, stats.reviews_removed), (2, 0));
+
+    let fake_id = client.get_reviews(&subject).get(0).unwrap().id;
+    let honest_id = client.get_reviews(&subject).get(1).unwrap().id;
+    let reputation = client.admin_remove_review(&admin, &fake_id);
+    assert_eq!(reputation.total_reviews, 1);
+    assert_eq!(reputation.average_rating, 500);
+
+    let stats = client.get_reviewer_stats(&bot);
+    assert_eq!((stats.reviews_submitted, stats.reviews_removed), (2, 1));
+    assert_eq!(client.get_reviewer_stats(&honest).reviews_removed, 0);
+
+    // Remaining reviews stay addressable by id
+    assert_eq!(client.get_review(&fake_id), None);
+    assert_eq!(client.get_review(&honest_id).unwrap().reviewer, honest);
+
+    // Removing a user's only review resets their score
+    let other_id = client.get_reviews(&other).get(0).unwrap().id;
+    assert_eq!(
+        client.admin_remove_review(&admin, &other_id).average_rating,
+        0
+    );
+    assert_eq!(client.get_reviewer_stats(&bot).reviews_removed, 2);
+
+    assert_eq!(
+        client.try_admin_remove_review(&admin, &fake_id),
+        Err(Ok(Error::ReviewNotFound))
+    );
+    assert_eq!(
+        client.try_admin_remove_review(&bot, &honest_id),
+        Err(Ok(Error::Unauthorized))
+    );
+}
+
+#[contract]
+pub struct MockEscrow;
+
+#[contractimpl]
+impl MockEscrow {
+    pub fn set_parties(
+        env: Env,
+        id: u128,
+        closed: bool,
+        payers: Vec<Address>,
+        payees: Vec<Address>,
+    ) {
+        env.storage().instance().set(&id, &(closed, payers, payees));
+    }
+
+    pub fn get_escrow_parties(env: Env, id: u128) -> (bool, Vec<Address>, Vec<Address>) {
+        env.storage().instance().get(&id).expect("escrow not found")
+    }
+}
+
+#[test]
+fn test_verified_review_requires_closed_escrow() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let client = ReputationContractClient::new(&env, &env.register(ReputationContract, ()));
+    let escrow_id = env.register(MockEscrow, ());
+    let escrow = MockEscrowClient::new(&env, &escrow_id);
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let comment = String::from_str(&env, "Delivered");
+    client.initialize(&admin);
+
+    // Without an escrow contract reviews are accepted as before
+    assert_eq!(
+        client
+            .submit_verified_review(&buyer, &seller, &9, &4, &comment)
+            .total_reviews,
+        1
+    );
+
+    assert_eq!(
+        client.try_set_escrow_contract(&buyer, &Some(escrow_id.clone())),
+        Err(Ok(Error::Unauthorized))
+    );
+    client.set_escrow_contract(&admin, &Some(escrow_id.clone()));
+    escrow.set_parties(
+        &1,
+        &false,
+        &vec![&env, buyer.clone()],
+        &vec![&env, seller.clone()],
+    );
+
+    // Open escrow, unknown escrow, and outsiders are all rejected
+    assert_eq!(
+        client.try_submit_verified_review(&buyer, &seller, &1, &5, &comment),
+        Err(Ok(Error::TransactionNotVerified))
+    );
+    assert_eq!(
+        client.try_submit_verified_review(&buyer, &seller, &2, &5, &comment),
+        Err(Ok(Error::TransactionNotVerified))
+    );
+    escrow.set_parties(
+        &1,
+        &true,
+        &vec![&env, buyer.clone()],
+        &vec![&env, seller.clone()],
+    );
+    assert_eq!(
+        client.try_submit_verified_review(&stranger, &seller, &1, &5, &comment),
+        Err(Ok(Error::TransactionNotVerified))
+    );
+    assert_eq!(
+        client.try_submit_verified_review(&buyer, &stranger, &1, &5, &comment),
+        Err(Ok(Error::TransactionNotVerified))
+    );
+    assert!(client
+        .try_submit_review(&buyer, &seller, &5, &comment)
+        .is_err());
+
+    // Either side of a closed escrow may review the other
+    let reputation = client.submit_verified_review(&buyer, &seller, &1, &2, &comment);
+    assert_eq!((reputation.total_reviews, reputation.total_score), (2, 6));
+    assert_eq!(
+        client
+            .submit_verified_review(&seller, &buyer, &1, &5, &comment)
+            .total_reviews,
+        1
+    );
+    assert_eq!(
+        client.get_reviews(&seller).get(1).unwrap().transaction_id,
+        Some(1)
+    );
+
+    // A closed escrow backs only one review from each party
+    assert_eq!(
+        client.try_submit_verified_review(&buyer, &seller, &1, &1, &comment),
+        Err(Ok(Error::AlreadyReviewed))
+    );
+    assert_eq!(client.get_reputation(&seller).total_reviews, 2);
+
+    // Clearing the escrow contract restores unverified reviews
+    client.set_escrow_contract(&admin, &None);
+    assert_eq!(
+        client
+            .submit_review(&stranger, &seller, &3, &comment)
+            .total_reviews,
+        3
+    );
+}
+
+#[test]
+fn test_batch_score_queries() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let client = ReputationContractClient::new(&env, &env.register(ReputationContract, ()));
+    let (good, mixed, unknown) = (
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    );
+    let comment = String::from_str(&env, "ok");
+    client.submit_review(&Address::generate(&env), &good, &5, &comment);
+    client.submit_review(&Address::generate(&env), &mixed, &4, &comment);
+    client.submit_review(&Address::generate(&env), &mixed, &3, &comment);
+
+    let users = vec![&env, mixed.clone(), unknown.clone(), good.clone()];
+    assert_eq!(
+        client.get_scores_batch(&users),
+        vec![
+            &env,
+            (mixed.clone(), 350),
+            (unknown.clone(), 0),
+            (good.clone(), 500)
+        ]
+    );
+    assert_eq!(
+        client.get_tiers_batch(&users),
+        vec![
+            &env,
+            (mixed.clone(), 4),
+            (unknown.clone(), 0),
+            (good.clone(), 5)
+        ]
+    );
+    for (user, tier) in client.get_tiers_batch(&users).iter() {
+        assert_eq!(tier, client.get_score_scaled(&user, &5));
+    }
+
+    let mut too_many = Vec::new(&env);
+    for _ in 0..101 {
+        too_many.push_back(unknown.clone());
+    }
+    assert_eq!(
+        client.try_get_scores_batch(&too_many),
+        Err(Ok(Error::InvalidInput))
+    );
+    assert_eq!(
+        client.try_get_tiers_batch(&too_many),
+        Err(Ok(Error::InvalidInput))
+    );
+}
+
+#[test]
+fn test_bayesian_score_ranks_established_users_first() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    let client = ReputationContractClient::new(&env, &env.register(ReputationContract, ()));
+    let admin = Address::generate(&env);
+    let (newcomer, veteran) = (Address::generate(&env), Address::generate(&env));
+    let comment = String::from_str(&env, "ok");
+    client.initialize(&admin);
+
+    client.submit_review(&Address::generate(&env), &newcomer, &5, &comment);
+    for i in 0..50 {
+        let rating = if i % 5 == 0 { 4 } else { 5 };
+        client.submit_review(&Address::generate(&env), &veteran, &rating, &comment);
+    }
+    assert_eq!(client.get_reputation(&newcomer).average_rating, 500);
+    assert_eq!(client.get_reputation(&veteran).average_rating, 480);
+
+    // Without a prior the score is the raw average
+    assert_eq!(client.get_bayesian_score(&newcomer), 500);
+
+    let prior = BayesianPrior {
+        mean: 350,
+        weight: 10,
+    };
+    assert_eq!(
+        client.try_set_bayesian_prior(&newcomer, &prior),
+        Err(Ok(Error::Unauthorized))
+    );
+    assert_eq!(
+        client.try_set_bayesian_prior(
+            &admin,
+            &BayesianPrior {
+                mean: 501,
+                weight: 1
+            }
+        ),
+        Err(Ok(Error::InvalidInput))
+    );
+    client.set_bayesian_prior(&admin, &prior);
+
+    // (500 + 350 * 10) / 11 and (480 * 50 + 350 * 10) / 60
+    assert_eq!(client.get_bayesian_score(&newcomer), 363);
+    assert_eq!(client.get_bayesian_score(&veteran), 458);
+    assert_eq!(client.get_bayesian_score(&Address::generate(&env)), 350);
+
+    // Raw scores are untouched
+    assert_eq!(client.get_reputation(&newcomer).average_rating, 500);
+}
+
+#[test]
+fn test_report_card_shows_improving_trend() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let client = ReputationContractClient::new(&env, &env.register(ReputationContract, ()));
+    let user = Address::generate(&env);
+    let comment = String::from_str(&env, "ok");
+
+    let card = client.get_report_card(&user);
+    assert_eq!((card.score, card.review_count), (0, 0));
+    assert_eq!(card.recent_trend, Trend::Flat);
+
+    // A rough start followed by five strong reviews
+    for rating in [1, 2, 2, 3, 1, 5, 4, 5, 5, 5] {
+        client.submit_review(&Address::generate(&env), &user, &rating, &comment);
+    }
+
+    let card = client.get_report_card(&user);
+    assert_eq!(card.score, 330);
+    assert_eq!(card.tier, 3);
+    assert_eq!(card.review_count, 10);
+    assert_eq!(card.positive_pct, 50);
+    assert_eq!(card.negative_pct, 40);
+    assert_eq!(card.recent_trend, Trend::Up);
+
+    let steady = Address::generate(&env);
+    for _ in 0..6 {
+        client.submit_review(&Address::generate(&env), &steady, &4, &comment);
+    }
+    assert_eq!(client.get_report_card(&steady).recent_trend, Trend::Flat);
+}
+
+#[test]
+fn test_flagged_review_enters_moderation_queue() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(ReputationContract, ());
+    let client = ReputationContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    let subject = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_flag_threshold(&admin, &2);
+
+    let comment = String::from_str(&env, "Scam");
+    client.submit_review(&Address::generate(&env), &subject, &1, &comment);
+    client.submit_review(&Address::generate(&env), &subject, &5, &comment);
+    let flagged_id = client.get_reviews(&subject).get(0).unwrap().id;
+    let other_id = client.get_reviews(&subject).get(1).unwrap().id;
+
+    let reason = BytesN::from_array(&env, &[7; 32]);
+    let (first, second) = (Address::generate(&env), Address::generate(&env));
+    assert_eq!(client.flag_review(&first, &flagged_id, &reason), 1);
+    assert_eq!(client.get_flagged_reviews(&0, &10).len(), 0);
+    assert_eq!(
+        client.try_flag_review(&first, &flagged_id, &reason),
+        Err(Ok(Error::AlreadyFlagged))
+    );
+    assert_eq!(client.flag_review(&second, &flagged_id, &reason), 2);
+    client.flag_review(&first, &other_id, &reason);
+
+    // Further flags bump the count without queueing the review twice
+    client.flag_review(&Address::generate(&env), &flagged_id, &reason);
+    assert_eq!(
+        client.get_flagged_reviews(&0, &10),
+        vec![&env, (flagged_id, 3)]
+    );
+
+    assert_eq!(
+        client.try_flag_review(&first, &999, &reason),
+        Err(Ok(Error::ReviewNotFound))
+    );
+    assert_eq!(
+        client.try_set_flag_threshold(&admin, &0),
+        Err(Ok(Error::InvalidInput))
+    );
+
+    // Removing the review clears it from the queue along with its flags and response
+    client.respond_to_review(&subject, &flagged_id, &reason);
+    client.admin_remove_review(&admin, &flagged_id);
+    assert_eq!(client.get_flagged_reviews(&0, &10).len(), 0);
+    assert_eq!(client.get_review_response(&flagged_id), None);
+    env.as_contract(&contract_id, || {
+        let storage = Storage::new(&env);
+        assert!(!storage.has_flag(&first, flagged_id));
+        assert!(!storage.has_flag(&second, flagged_id));
+        assert_eq!(storage.get_flag_count(flagged_id), 0);
+        // Flags on other reviews are untouched
+        assert!(storage.has_flag(&first, other_id));
+    });
+}
+
+#[test]
+fn test_percentile_follows_score() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let client = ReputationContractClient::new(&env, &env.register(ReputationContract, ()));
+    let comment = String::from_str(&env, "ok");
+    let review = |user: &Address, rating: u32| {
+        client.submit_review(&Address::generate(&env), user, &rating, &comment);
+    };
+
+    // Ten users with averages from 1.0 to 5.0 stars
+    let mut users = Vec::new(&env);
+    for ratings in [
+        [1, 1, 1],
+        [1, 2, 2],
+        [2, 2, 2],
+        [2, 3, 3],
+        [3, 3, 3],
+        [3, 4, 4],
+        [4, 4, 4],
+        [4, 4, 5],
+        [4, 5, 5],
+        [5, 5, 5],
+    ] {
+        let user = Address::generate(&env);
+        for rating in ratings {
+            review(&user, rating);
+        }
+        users.push_back(user);
+    }
+
+    let mut previous = 0;
+    for user in users.iter() {
+        let percentile = client.get_percentile(&user);
+        assert!(percentile >= previous);
+        previous = percentile;
+    }
+    assert_eq!(client.get_percentile(&users.get(0).unwrap()), 10);
+    assert_eq!(client.get_percentile(&users.get(9).unwrap()), 100);
+
+    // Too few reviews to be ranked
+    let newcomer = Address::generate(&env);
+    review(&newcomer, 5);
+    assert_eq!(client.get_percentile(&newcomer), 0);
+
+    // Dropping into a lower bucket lowers the rank
+    let top = users.get(9).unwrap();
+    for _ in 0..6 {
+        review(&top, 1);
+    }
+    // 21 / 9 = 2.33 stars, level with the third user's 2.0-2.49 bucket
+    assert_eq!(client.get_percentile(&top), 40);
+}
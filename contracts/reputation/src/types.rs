@@ -1,12 +1,15 @@
-use soroban_sdk::{contracttype, Address, String};
+use soroban_sdk::{contracttype, Address, BytesN, String, Symbol, Vec};
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Review {
+    pub id: u64,
     pub reviewer: Address,
     pub rating: u32, // 1 to 5
     pub comment: String,
     pub timestamp: u64,
+    pub helpful_count: u32,
+    pub transaction_id: Option<u128>, // escrow the review was verified against
 }
 
 #[contracttype]
@@ -15,6 +18,7 @@ pub struct Reputation {
     pub average_rating: u32, // Scaled by 100 (e.g., 450 = 4.5)
     pub total_reviews: u32,
     pub total_score: u32,
+    pub last_updated: u64, // Last activity, decay counts inactive periods from here
 }
 
 impl Default for Reputation {
@@ -23,6 +27,124 @@ impl Default for Reputation {
             average_rating: 0,
             total_reviews: 0,
             total_score: 0,
+            last_updated: 0,
         }
     }
 }
+
+impl Reputation {
+    /// Average pulled toward `prior_mean` as if `prior_weight` extra reviews
+    /// at that score existed, so few-review users can't outrank established ones.
+    pub fn bayesian_score(&self, prior_mean: u32, prior_weight: u32) -> u32 {
+        let weight = self.total_reviews as u64 + prior_weight as u64;
+        if weight == 0 {
+            return 0;
+        }
+        let sum = self.average_rating as u64 * self.total_reviews as u64
+            + prior_mean as u64 * prior_weight as u64;
+        (sum / weight) as u32
+    }
+}
+
+/// Prior for `get_bayesian_score`: `mean` on the 0-500 scale, counted as
+/// `weight` reviews. The default weight of 0 leaves scores unadjusted.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BayesianPrior {
+    pub mean: u32,
+    pub weight: u32,
+}
+
+/// Sliding-window review limit: at most `max_reviews` per reviewer within
+/// `window` seconds. `max_reviews` of 0 disables it.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ReviewWindow {
+    pub max_reviews: u32,
+    pub window: u64,
+}
+
+/// Inactivity decay: `average_rating` loses `percent_per_period` percent,
+/// compounded, for every full 30-day period without activity. 0 disables it.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DecayConfig {
+    pub percent_per_period: u32,
+}
+
+/// How a user behaves as a reviewer, as opposed to the reviews they receive.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ReviewerStats {
+    pub reviews_submitted: u32,
+    pub reviews_removed: u32, // Removed by an admin as fraudulent
+}
+
+/// A reviewee's public reply to a review; the text lives off-chain under `response_hash`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReviewResponse {
+    pub responder: Address,
+    pub response_hash: BytesN<32>,
+    pub timestamp: u64,
+}
+
+/// Reputation imported from a partner platform. `weight` is the discounted
+/// number of reviews the external score counts for in the average.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Attestation {
+    pub source: Symbol,
+    pub score: u32, // Same 0-500 scale as average_rating
+    pub weight: u32,
+}
+
+/// Direction of a user's most recent ratings relative to their overall average.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Trend {
+    Down = 0,
+    Flat = 1,
+    Up = 2,
+}
+
+/// One-call profile summary for display. Percentages are of the stored reviews.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReportCard {
+    pub score: u32, // Same 0-500 scale as average_rating
+    pub tier: u32,  // Whole stars, as get_score_scaled with scale 5
+    pub review_count: u32,
+    pub positive_pct: u32, // 4 and 5 star reviews
+    pub negative_pct: u32, // 1 and 2 star reviews
+    pub recent_trend: Trend,
+}
+
+/// All admin-set settings, readable in one call.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReputationConfig {
+    pub max_reviews_per_ledger: u32, // 0 = unlimited
+    pub duplicate_comment_check: bool,
+    pub min_event_delta: u32,
+    pub watch_threshold: u32, // 0 = watchlist disabled
+    pub decay: DecayConfig,
+    pub bayesian_prior: BayesianPrior,
+    pub review_window: ReviewWindow,
+    pub edit_window: u64,
+    pub flag_threshold: u32,
+    pub escrow_contract: Option<Address>, // None = reviews need no completed escrow
+}
+
+/// One page of everything the contract stores about the reviews a user has
+/// received. `responses` lines up with `reviews`; `total_reviews` is the
+/// size of the full list, so callers know when they have paged through it.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UserExport {
+    pub reputation: Reputation,
+    pub reviews: Vec<Review>,
+    pub responses: Vec<Option<ReviewResponse>>,
+    pub total_reviews: u32,
+}
@@ -1,6 +1,6 @@
 #![no_std]
 
-use soroban_sdk::{contract, contractimpl, contracttype, symbol_short, Address, BytesN, Env, Symbol, Vec as SVec, Map as SMap, IntoVal};
+use soroban_sdk::{contract, contractevent, contractimpl, contracttype, Address, BytesN, Env, Symbol, Vec as SVec, Map as SMap, IntoVal};
 
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -13,10 +13,37 @@ pub enum DataKey {
     EmergencyThreshold,
     Escrow(u128),
     PendingRelease(u128),
+    PendingRefund(u128),
+    PendingArbiter(u128),
+    PendingEmergency(u128),
     ApprovalsRelease(u128),
     ApprovalsRefund(u128),
     ApprovalsArbiter(u128),
     ApprovalsEmergency(u128),
+    CancelsEmergency(u128),
+    DepositLog(u128),
+    PayeeAcks(u128, u64),
+    MaxArbiters,
+    ActiveEscrows,
+    TotalLocked(Address),
+    FeeMarketplace,
+    RefundRecipient(u128, Address),
+    RoleEscrows(Address, u32),
+    DeliveryConfirms(u128),
+    ReputationContract,
+    MinEscrowAmount,
+    ArbiterCosign(u128, u64),
+    DisputeFeeBps,
+}
+
+// How refund_timeout splits what is left among the payers who deposited
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum RefundMode {
+    // in proportion to each payer's deposits
+    Proportional,
+    // the same amount to every depositing payer, whatever they put in
+    Equal,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -33,6 +60,23 @@ pub struct EscrowInit {
     pub arbiter_threshold: u32,
     pub auto_release_ts: Option<u64>,
     pub expiry_ts: u64,
+    pub require_payee_ack: bool,
+    pub extra_tokens: SVec<Address>,
+    // reject arbiters or emergency admins that are also payers/payees
+    pub strict_roles: bool,
+    // Some(margin): auto_release also needs every payee's confirm_delivery, or auto_release_ts + margin to pass
+    pub delivery_fallback: Option<u64>,
+    // smallest first deposit a payer may make in `token`; None means no minimum
+    pub min_deposit_per_payer: Option<i128>,
+    // Some(tier): every payee paid by a release needs at least this 0-5 star score on the reputation contract
+    pub min_payee_tier: Option<u32>,
+    // Some(ts): a dispute still open at ts can be escalated to the emergency admins
+    pub arbiter_deadline_ts: Option<u64>,
+    // Some(weights): release approvals sum these weights (missing signers weigh 1) and release_threshold is a weight sum
+    pub release_weights: Option<SMap<Address, u32>>,
+    // releases outside a dispute also need one arbiter's cosign_release on the proposal
+    pub require_arbiter_cosign: bool,
+    pub refund_mode: RefundMode,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -54,15 +98,129 @@ pub struct Escrow {
     pub deposits: SVec<(Address, i128)>,
     pub closed: bool,
     pub nonce: u64,
+    pub require_payee_ack: bool,
+    // additional tokens accepted besides `token`; their balances/deposits are tracked per token
+    pub extra_tokens: SVec<Address>,
+    pub extra_balances: SMap<Address, i128>,
+    pub extra_deposits: SMap<Address, SVec<(Address, i128)>>,
+    pub delivery_fallback: Option<u64>,
+    pub min_deposit_per_payer: Option<i128>,
+    pub min_payee_tier: Option<u32>,
+    pub arbiter_deadline_ts: Option<u64>,
+    // dispute handed from arbiters to emergency admins after arbiter_deadline_ts
+    pub escalated: bool,
+    // admin-granted: releases pay payees in full and skip the fee skim
+    pub fee_exempt: bool,
+    pub release_weights: Option<SMap<Address, u32>>,
+    // releases outside a dispute also need one arbiter's cosign_release on the proposal
+    pub require_arbiter_cosign: bool,
+    pub refund_mode: RefundMode,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contracttype]
 pub struct ReleaseProposal {
     pub nonce: u64,
+    pub token: Address,
+    pub dists: SVec<(Address, i128)>,
+    // leave the escrow open for further deposits even if this drains it
+    pub keep_open: bool,
+    // refund whatever is left of `token` to payers pro rata and close
+    pub settle: bool,
+}
+
+// An arbiter or emergency resolution; votes only add up while they are for the same one
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ResolutionProposal {
+    pub refund: bool,
+    pub token: Address,
     pub dists: SVec<(Address, i128)>,
 }
 
+// Lifecycle events, each topic-indexed by escrow id
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowCreatedEventData {
+    #[topic]
+    pub id: u128,
+    pub token: Address,
+    pub payers: SVec<Address>,
+    pub payees: SVec<Address>,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DepositedEventData {
+    #[topic]
+    pub id: u128,
+    pub from: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputeOpenedEventData {
+    #[topic]
+    pub id: u128,
+    pub actor: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputeEscalatedEventData {
+    #[topic]
+    pub id: u128,
+    pub actor: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReleaseProposedEventData {
+    #[topic]
+    pub id: u128,
+    pub signer: Address,
+    pub nonce: u64,
+    pub token: Address,
+    pub total: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReleaseExecutedEventData {
+    #[topic]
+    pub id: u128,
+    pub nonce: u64,
+    pub token: Address,
+    pub total: i128,
+    pub fee: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RefundExecutedEventData {
+    #[topic]
+    pub id: u128,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmergencyCancelledEventData {
+    #[topic]
+    pub id: u128,
+    pub approvers: SVec<Address>,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowClosedEventData {
+    #[topic]
+    pub id: u128,
+}
+
 fn read_u32(env: &Env, key: &DataKey) -> u32 { env.storage().persistent().get::<_, u32>(key).unwrap() }
 fn write_u32(env: &Env, key: &DataKey, v: u32) { env.storage().persistent().set(key, &v) }
 fn read_addr(env: &Env, key: &DataKey) -> Address { env.storage().persistent().get::<_, Address>(key).unwrap() }
@@ -70,20 +228,73 @@ fn write_addr(env: &Env, key: &DataKey, v: &Address) { env.storage().persistent(
 fn read_vec_addr(env: &Env, key: &DataKey) -> SVec<Address> { env.storage().persistent().get::<_, SVec<Address>>(key).unwrap_or_else(|| SVec::new(env)) }
 fn write_vec_addr(env: &Env, key: &DataKey, v: &SVec<Address>) { env.storage().persistent().set(key, v) }
 
+const MAX_DEPOSIT_LOG: u32 = 50;
+const MAX_PAGE: u32 = 50;
+const MAX_BUMP_BATCH: u32 = 50;
+// ~30 days of 5s ledgers
+const BUMP_TTL: u32 = 518_400;
+
+pub const ROLE_PAYER: u32 = 0;
+pub const ROLE_PAYEE: u32 = 1;
+pub const ROLE_SIGNER: u32 = 2;
+pub const ROLE_ARBITER: u32 = 3;
+
 fn read_escrow(env: &Env, id: u128) -> Escrow { env.storage().persistent().get::<_, Escrow>(&DataKey::Escrow(id)).unwrap() }
 fn write_escrow(env: &Env, id: u128, e: &Escrow) { env.storage().persistent().set(&DataKey::Escrow(id), e) }
 
 fn token_client(env: &Env, addr: &Address) -> soroban_sdk::token::Client { soroban_sdk::token::Client::new(env, addr) }
 
+// Send skimmed fees to the collector and report them to the linked marketplace, if any
+fn pay_fees(env: &Env, client: &soroban_sdk::token::Client, fee_total: i128) {
+    if fee_total <= 0 { return; }
+    client.transfer(&env.current_contract_address(), &read_addr(env, &DataKey::FeeCollector), &fee_total);
+    if let Some(mp) = env.storage().persistent().get::<_, Address>(&DataKey::FeeMarketplace) {
+        // this contract reports as itself, so no admin signature is needed mid-release
+        let args = (env.current_contract_address(), client.address.clone(), fee_total as u128);
+        env.invoke_contract::<()>(&mp, &Symbol::new(env, "record_fee_collection_for_asset"), args.into_val(env));
+    }
+}
+
 fn is_member(list: &SVec<Address>, who: &Address) -> bool { list.iter().any(|a| a == who.clone()) }
 
+fn overlaps_parties(list: &SVec<Address>, payers: &SVec<Address>, payees: &SVec<Address>) -> bool {
+    list.iter().any(|a| is_member(payers, &a) || is_member(payees, &a))
+}
+
+fn signer_weight(weights: &Option<SMap<Address, u32>>, who: &Address) -> u32 { weights.as_ref().and_then(|w| w.get(who.clone())).unwrap_or(1) }
+// Release approvals reach the threshold by weight, which is a head count when no weights are set
+fn release_approved(e: &Escrow, approvers: &SVec<Address>) -> bool {
+    approvers.iter().fold(0u32, |acc, a| acc.saturating_add(signer_weight(&e.release_weights, &a))) >= e.release_threshold
+}
 fn push_unique(list: &mut SVec<Address>, who: &Address) {
     if !is_member(list, who) { list.push_back(who.clone()); }
 }
+fn remove_member(list: &mut SVec<Address>, who: &Address) {
+    if let Some(i) = list.first_index_of(who.clone()) { list.remove(i); }
+}
+
+// `amount` split evenly over `to`, earlier entries taking the remainder; zero legs are left out
+fn equal_split(env: &Env, to: &SVec<Address>, amount: i128) -> SVec<(Address, i128)> {
+    let mut dists = SVec::new(env);
+    let n = to.len() as i128;
+    if n == 0 || amount <= 0 { return dists; }
+    let base = amount / n;
+    let mut rem = amount - base * n;
+    for a in to.iter() {
+        let mut amt = base;
+        if rem > 0 { amt += 1; rem -= 1; }
+        if amt > 0 { dists.push_back((a, amt)); }
+    }
+    dists
+}
 
+// Every entry must be positive so a bad leg can't hide inside a valid total
 fn sum_amounts(dists: &SVec<(Address, i128)>) -> i128 {
     let mut s: i128 = 0;
-    for (_, a) in dists.iter() { s += a; }
+    for (_, a) in dists.iter() {
+        if a <= 0 { panic!("bad-amount"); }
+        s += a;
+    }
     s
 }
 
@@ -94,6 +305,168 @@ fn ensure_payees_valid(payees: &SVec<Address>, dists: &SVec<(Address, i128)>) ->
 
 fn now(env: &Env) -> u64 { env.ledger().timestamp() }
 
+fn is_escrow_token(e: &Escrow, token: &Address) -> bool { *token == e.token || is_member(&e.extra_tokens, token) }
+
+// Balance held for `token`; the primary token uses the `balance` fast path
+fn token_balance(e: &Escrow, token: &Address) -> i128 {
+    if *token == e.token { e.balance } else { e.extra_balances.get(token.clone()).unwrap_or(0) }
+}
+
+// Every balance change goes through here so the contract-wide TVL stays in sync
+fn adjust_token_balance(env: &Env, e: &mut Escrow, token: &Address, delta: i128) {
+    if *token == e.token { e.balance += delta; } else {
+        let cur = e.extra_balances.get(token.clone()).unwrap_or(0);
+        e.extra_balances.set(token.clone(), cur + delta);
+    }
+    let key = DataKey::TotalLocked(token.clone());
+    let locked = env.storage().persistent().get::<_, i128>(&key).unwrap_or(0);
+    env.storage().persistent().set(&key, &(locked + delta));
+}
+
+fn adjust_active_escrows(env: &Env, delta: i32) {
+    let cur = env.storage().persistent().get::<_, u32>(&DataKey::ActiveEscrows).unwrap_or(0);
+    env.storage().persistent().set(&DataKey::ActiveEscrows, &((cur as i32 + delta) as u32));
+}
+
+// Escrow is drained once the primary and every extra token balance is zero
+fn is_drained(e: &Escrow) -> bool { e.balance == 0 && e.extra_balances.values().iter().all(|b| b == 0) }
+
+fn add_deposit(env: &Env, deposits: &SVec<(Address, i128)>, from: &Address, amount: i128) -> SVec<(Address, i128)> {
+    let mut found = false;
+    let mut out = SVec::new(env);
+    for (p, a) in deposits.iter() {
+        if p == *from { out.push_back((p, a + amount)); found = true; } else { out.push_back((p, a)); }
+    }
+    if !found { out.push_back((from.clone(), amount)); }
+    out
+}
+
+fn bump_key(env: &Env, key: &DataKey) {
+    let store = env.storage().persistent();
+    if store.has(key) { store.extend_ttl(key, BUMP_TTL, BUMP_TTL); }
+}
+
+// Contract-wide settings every escrow operation may read
+fn bump_config(env: &Env) {
+    for key in [DataKey::Admin, DataKey::FeeBps, DataKey::FeeCollector, DataKey::EmergencyAdmins, DataKey::EmergencyThreshold, DataKey::MaxArbiters,
+                DataKey::ActiveEscrows, DataKey::FeeMarketplace, DataKey::ReputationContract, DataKey::MinEscrowAmount, DataKey::DisputeFeeBps] {
+        bump_key(env, &key);
+    }
+}
+
+// An escrow together with every live auxiliary entry its release, refund or dispute paths read
+fn bump_escrow(env: &Env, id: u128) {
+    let e = read_escrow(env, id);
+    for key in [DataKey::Escrow(id), DataKey::PendingRelease(id), DataKey::PendingRefund(id), DataKey::PendingArbiter(id), DataKey::PendingEmergency(id),
+                DataKey::ApprovalsRelease(id), DataKey::ApprovalsRefund(id), DataKey::ApprovalsArbiter(id), DataKey::ApprovalsEmergency(id),
+                DataKey::CancelsEmergency(id), DataKey::DepositLog(id), DataKey::DeliveryConfirms(id)] {
+        bump_key(env, &key);
+    }
+    if let Some(prop) = env.storage().persistent().get::<_, ReleaseProposal>(&DataKey::PendingRelease(id)) {
+        bump_key(env, &DataKey::PayeeAcks(id, prop.nonce));
+        bump_key(env, &DataKey::ArbiterCosign(id, prop.nonce));
+    }
+    for p in e.payers.iter() { bump_key(env, &DataKey::RefundRecipient(id, p)); }
+    bump_key(env, &DataKey::TotalLocked(e.token.clone()));
+    for t in e.extra_tokens.iter() { bump_key(env, &DataKey::TotalLocked(t)); }
+    for (list, role) in [(&e.payers, ROLE_PAYER), (&e.payees, ROLE_PAYEE), (&e.release_signers, ROLE_SIGNER), (&e.refund_signers, ROLE_SIGNER), (&e.arbiters, ROLE_ARBITER)] {
+        for a in list.iter() { bump_key(env, &DataKey::RoleEscrows(a, role)); }
+    }
+}
+
+// Drop every per-escrow auxiliary entry once the escrow is closed
+fn cleanup_on_close(env: &Env, id: u128) {
+    let store = env.storage().persistent();
+    if let Some(prop) = store.get::<_, ReleaseProposal>(&DataKey::PendingRelease(id)) {
+        store.remove(&DataKey::PayeeAcks(id, prop.nonce));
+        store.remove(&DataKey::ArbiterCosign(id, prop.nonce));
+    }
+    store.remove(&DataKey::PendingRelease(id));
+    store.remove(&DataKey::PendingRefund(id));
+    store.remove(&DataKey::PendingArbiter(id));
+    store.remove(&DataKey::PendingEmergency(id));
+    store.remove(&DataKey::ApprovalsRelease(id));
+    store.remove(&DataKey::ApprovalsRefund(id));
+    store.remove(&DataKey::ApprovalsArbiter(id));
+    store.remove(&DataKey::ApprovalsEmergency(id));
+    store.remove(&DataKey::CancelsEmergency(id));
+    store.remove(&DataKey::DeliveryConfirms(id));
+    for p in read_escrow(env, id).payers.iter() { store.remove(&DataKey::RefundRecipient(id, p)); }
+    adjust_active_escrows(env, -1);
+    EscrowClosedEventData { id }.publish(env);
+}
+
+fn index_role(env: &Env, who: &SVec<Address>, role: u32, id: u128) {
+    for a in who.iter() {
+        let key = DataKey::RoleEscrows(a, role);
+        let mut ids = env.storage().persistent().get::<_, SVec<u128>>(&key).unwrap_or_else(|| SVec::new(env));
+        if !ids.contains(id) { ids.push_back(id); }
+        env.storage().persistent().set(&key, &ids);
+    }
+}
+
+// Return the whole remaining `token` balance to the depositing payers, split per `mode`
+fn refund_remaining(env: &Env, id: u128, e: &mut Escrow, token: &Address, mode: RefundMode) {
+    let amount = token_balance(e, token);
+    if amount <= 0 { return; }
+    let deposits = if *token == e.token { e.deposits.clone() } else { e.extra_deposits.get(token.clone()).unwrap_or_else(|| SVec::new(env)) };
+    let mut deposited: i128 = 0;
+    for (_, a) in deposits.iter() { deposited += a; }
+    if deposited <= 0 { panic!("no-deposits"); }
+    let client = token_client(env, token);
+    let mut left = amount;
+    let n = deposits.len();
+    for (i, (p, a)) in deposits.iter().enumerate() {
+        // last payer absorbs rounding dust
+        let share = if i as u32 == n - 1 { left } else {
+            match mode { RefundMode::Proportional => amount * a / deposited, RefundMode::Equal => amount / n as i128 }
+        };
+        if share > 0 { client.transfer(&env.current_contract_address(), &refund_target(env, id, &p), &share); }
+        left -= share;
+    }
+    adjust_token_balance(env, e, token, -amount);
+    RefundExecutedEventData { id, token: token.clone(), amount }.publish(env);
+}
+
+// Where a payer's refunds are sent; defaults to the payer itself
+fn refund_target(env: &Env, id: u128, payer: &Address) -> Address {
+    env.storage().persistent().get::<_, Address>(&DataKey::RefundRecipient(id, payer.clone())).unwrap_or_else(|| payer.clone())
+}
+
+// Tier is the payee's reputation rounded to whole stars (get_score_scaled with scale 5)
+fn payees_meet_tier(env: &Env, e: &Escrow, dists: &SVec<(Address, i128)>) -> bool {
+    let min = match e.min_payee_tier { Some(t) => t, None => return true };
+    let rep = match env.storage().persistent().get::<_, Address>(&DataKey::ReputationContract) { Some(r) => r, None => return true };
+    dists.iter().all(|(to, _)| env.invoke_contract::<u32>(&rep, &Symbol::new(env, "get_score_scaled"), (to, 5u32).into_val(env)) >= min)
+}
+
+fn payee_acks_complete(env: &Env, id: u128, e: &Escrow, prop: &ReleaseProposal) -> bool {
+    if !e.require_payee_ack { return true; }
+    let acks = read_vec_addr(env, &DataKey::PayeeAcks(id, prop.nonce));
+    prop.dists.iter().all(|(to, _)| is_member(&acks, &to))
+}
+
+// Signers settle one proposal at a time; a pending one must execute or be cancelled first
+fn has_pending_proposal(env: &Env, id: u128) -> bool {
+    let store = env.storage().persistent();
+    store.has(&DataKey::PendingRelease(id)) || store.has(&DataKey::PendingRefund(id))
+}
+
+// Record `signer`'s vote for `prop` under its own keys; a vote for a different proposal replaces it and restarts the count
+fn vote_resolution(env: &Env, pending: &DataKey, approvals: &DataKey, prop: &ResolutionProposal, signer: &Address) -> SVec<Address> {
+    let same = env.storage().persistent().get::<_, ResolutionProposal>(pending).as_ref() == Some(prop);
+    let mut approvers = if same { read_vec_addr(env, approvals) } else { SVec::new(env) };
+    push_unique(&mut approvers, signer);
+    env.storage().persistent().set(pending, prop);
+    write_vec_addr(env, approvals, &approvers);
+    approvers
+}
+
+// Disputed releases are already arbiter-driven, so the co-sign only gates normal ones
+fn arbiter_cosigned(env: &Env, id: u128, e: &Escrow, prop: &ReleaseProposal) -> bool {
+    !e.require_arbiter_cosign || e.disputed || env.storage().persistent().has(&DataKey::ArbiterCosign(id, prop.nonce))
+}
+
 #[contract]
 pub struct MarketXEscrow;
 
@@ -120,6 +493,19 @@ impl MarketXEscrow {
         write_addr(&env, &DataKey::FeeCollector, &fee_collector);
     }
 
+    // Fee on releases and arbiter refunds that resolve a dispute; None falls back to fee_bps for releases and no fee on refunds
+    pub fn set_dispute_fee_bps(env: Env, admin: Address, dispute_fee_bps: Option<u32>) {
+        let a = read_addr(&env, &DataKey::Admin);
+        if admin != a { panic!("not-admin"); }
+        admin.require_auth();
+        match dispute_fee_bps {
+            Some(bps) => { if bps > 10_000 { panic!("fee-bps-range"); } write_u32(&env, &DataKey::DisputeFeeBps, bps); }
+            None => env.storage().persistent().remove(&DataKey::DisputeFeeBps),
+        }
+    }
+
+    pub fn get_dispute_fee_bps(env: Env) -> Option<u32> { env.storage().persistent().get::<_, u32>(&DataKey::DisputeFeeBps) }
+
     pub fn set_emergency(env: Env, admin: Address, admins: SVec<Address>, threshold: u32) {
         let a = read_addr(&env, &DataKey::Admin);
         if admin != a { panic!("not-admin"); }
@@ -129,13 +515,89 @@ impl MarketXEscrow {
         write_u32(&env, &DataKey::EmergencyThreshold, threshold);
     }
 
+    // Report skimmed fees to a marketplace's record_fee_collection_for_asset; None detaches it.
+    // The marketplace admin must register this contract via set_fee_reporter first.
+    pub fn set_fee_marketplace(env: Env, admin: Address, marketplace: Option<Address>) {
+        let a = read_addr(&env, &DataKey::Admin);
+        if admin != a { panic!("not-admin"); }
+        admin.require_auth();
+        match marketplace {
+            Some(m) => write_addr(&env, &DataKey::FeeMarketplace, &m),
+            None => env.storage().persistent().remove(&DataKey::FeeMarketplace),
+        }
+    }
+
+    // Reputation contract consulted for escrows with min_payee_tier; None detaches it
+    pub fn set_reputation_contract(env: Env, admin: Address, reputation: Option<Address>) {
+        let a = read_addr(&env, &DataKey::Admin);
+        if admin != a { panic!("not-admin"); }
+        admin.require_auth();
+        match reputation {
+            Some(r) => write_addr(&env, &DataKey::ReputationContract, &r),
+            None => env.storage().persistent().remove(&DataKey::ReputationContract),
+        }
+    }
+
+    // Admin override: drop an escrow's payee tier requirement
+    pub fn waive_payee_tier(env: Env, admin: Address, id: u128) {
+        let a = read_addr(&env, &DataKey::Admin);
+        if admin != a { panic!("not-admin"); }
+        admin.require_auth();
+        let mut e = read_escrow(&env, id);
+        if e.closed { panic!("closed"); }
+        e.min_payee_tier = None;
+        write_escrow(&env, id, &e);
+    }
+
+    // Admin override for internal transfers and promotions: no fee on this escrow's releases
+    pub fn set_fee_exempt(env: Env, admin: Address, id: u128, exempt: bool) {
+        let a = read_addr(&env, &DataKey::Admin);
+        if admin != a { panic!("not-admin"); }
+        admin.require_auth();
+        let mut e = read_escrow(&env, id);
+        if e.closed { panic!("closed"); }
+        e.fee_exempt = exempt;
+        write_escrow(&env, id, &e);
+    }
+
+    // Smallest cumulative primary-token deposit an escrow needs before a release can be proposed; 0 disables
+    pub fn set_min_escrow_amount(env: Env, admin: Address, amount: i128) {
+        let a = read_addr(&env, &DataKey::Admin);
+        if admin != a { panic!("not-admin"); }
+        if amount < 0 { panic!("bad-amount"); }
+        admin.require_auth();
+        env.storage().persistent().set(&DataKey::MinEscrowAmount, &amount);
+    }
+
+    pub fn get_min_escrow_amount(env: Env) -> i128 { env.storage().persistent().get::<_, i128>(&DataKey::MinEscrowAmount).unwrap_or(0) }
+
+    // 0 disables the cap
+    pub fn set_max_arbiters(env: Env, admin: Address, max: u32) {
+        let a = read_addr(&env, &DataKey::Admin);
+        if admin != a { panic!("not-admin"); }
+        admin.require_auth();
+        write_u32(&env, &DataKey::MaxArbiters, max);
+    }
+
     // Escrow lifecycle
     pub fn create_escrow(env: Env, id: u128, params: EscrowInit) {
         if env.storage().persistent().has(&DataKey::Escrow(id)) { panic!("exists"); }
-        if params.release_threshold == 0 || params.release_threshold as usize > params.release_signers.len() { panic!("bad-release-thresh"); }
+        if let Some(weights) = &params.release_weights {
+            if weights.iter().any(|(s, w)| w == 0 || !is_member(&params.release_signers, &s)) { panic!("bad-release-weight"); }
+        }
+        let total_weight = params.release_signers.iter().fold(0u32, |acc, s| acc.saturating_add(signer_weight(&params.release_weights, &s)));
+        if params.release_threshold == 0 || params.release_threshold > total_weight { panic!("bad-release-thresh"); }
         if params.refund_threshold == 0 || params.refund_threshold as usize > params.refund_signers.len() { panic!("bad-refund-thresh"); }
         if params.arbiter_threshold == 0 || params.arbiter_threshold as usize > params.arbiters.len() { panic!("bad-arb-thresh"); }
         if params.payers.is_empty() || params.payees.is_empty() { panic!("empty-parties"); }
+        if params.min_payee_tier.is_some() && !env.storage().persistent().has(&DataKey::ReputationContract) { panic!("no-reputation-contract"); }
+        let max_arbiters = env.storage().persistent().get::<_, u32>(&DataKey::MaxArbiters).unwrap_or(0);
+        if max_arbiters > 0 && params.arbiters.len() > max_arbiters { panic!("too-many-arbiters"); }
+        if params.strict_roles {
+            if overlaps_parties(&params.arbiters, &params.payers, &params.payees) { panic!("arbiter-conflict"); }
+            let eadmins = read_vec_addr(&env, &DataKey::EmergencyAdmins);
+            if overlaps_parties(&eadmins, &params.payers, &params.payees) { panic!("emergency-admin-conflict"); }
+        }
         let e = Escrow {
             token: params.token,
             payers: params.payers,
@@ -153,28 +615,73 @@ impl MarketXEscrow {
             deposits: SVec::new(&env),
             closed: false,
             nonce: 0,
+            require_payee_ack: params.require_payee_ack,
+            extra_tokens: params.extra_tokens,
+            extra_balances: SMap::new(&env),
+            extra_deposits: SMap::new(&env),
+            delivery_fallback: params.delivery_fallback,
+            min_deposit_per_payer: params.min_deposit_per_payer,
+            min_payee_tier: params.min_payee_tier,
+            arbiter_deadline_ts: params.arbiter_deadline_ts,
+            escalated: false,
+            fee_exempt: false,
+            release_weights: params.release_weights,
+            require_arbiter_cosign: params.require_arbiter_cosign,
+            refund_mode: params.refund_mode,
         };
+        adjust_active_escrows(&env, 1);
+        index_role(&env, &e.payers, ROLE_PAYER, id);
+        index_role(&env, &e.payees, ROLE_PAYEE, id);
+        index_role(&env, &e.release_signers, ROLE_SIGNER, id);
+        index_role(&env, &e.refund_signers, ROLE_SIGNER, id);
+        index_role(&env, &e.arbiters, ROLE_ARBITER, id);
+        EscrowCreatedEventData { id, token: e.token.clone(), payers: e.payers.clone(), payees: e.payees.clone() }.publish(&env);
         write_escrow(&env, id, &e);
     }
 
     pub fn deposit(env: Env, id: u128, from: Address, amount: i128) {
+        let token = read_escrow(&env, id).token;
+        Self::deposit_token(env, id, from, token, amount);
+    }
+
+    pub fn deposit_token(env: Env, id: u128, from: Address, token: Address, amount: i128) {
+        Self::credit_deposit(env, id, from.clone(), from, token, amount);
+    }
+
+    // Third-party funding: `funder` pays and authorizes, the deposit (and any refund) belongs to payer `on_behalf_of`
+    pub fn deposit_for(env: Env, id: u128, funder: Address, on_behalf_of: Address, amount: i128) {
+        let token = read_escrow(&env, id).token;
+        Self::credit_deposit(env, id, funder, on_behalf_of, token, amount);
+    }
+
+    fn credit_deposit(env: Env, id: u128, funder: Address, from: Address, token: Address, amount: i128) {
         if amount <= 0 { panic!("bad-amount"); }
         let mut e = read_escrow(&env, id);
         if e.closed { panic!("closed"); }
         if !is_member(&e.payers, &from) { panic!("not-payer"); }
-        from.require_auth();
-        let client = token_client(&env, &e.token);
-        client.transfer(&from, &env.current_contract_address(), &amount);
-        e.balance += amount;
-        // update deposits
-        let mut found = false;
-        let mut out = SVec::new(&env);
-        for (p, a) in e.deposits.iter() {
-            if p == from { out.push_back((p, a + amount)); found = true; } else { out.push_back((p, a)); }
+        if !is_escrow_token(&e, &token) { panic!("unknown-token"); }
+        if let Some(min) = e.min_deposit_per_payer {
+            let first = !e.deposits.iter().any(|(p, _)| p == from);
+            if token == e.token && first && amount < min { panic!("below-min-deposit"); }
+        }
+        funder.require_auth();
+        let client = token_client(&env, &token);
+        client.transfer(&funder, &env.current_contract_address(), &amount);
+        adjust_token_balance(&env, &mut e, &token, amount);
+        DepositedEventData { id, from: from.clone(), token: token.clone(), amount }.publish(&env);
+        if token != e.token {
+            let prev = e.extra_deposits.get(token.clone()).unwrap_or_else(|| SVec::new(&env));
+            e.extra_deposits.set(token, add_deposit(&env, &prev, &from, amount));
+            write_escrow(&env, id, &e);
+            return;
         }
-        if !found { out.push_back((from, amount)); }
-        e.deposits = out;
+        e.deposits = add_deposit(&env, &e.deposits, &from, amount);
         write_escrow(&env, id, &e);
+        // append to timestamped log, dropping the oldest entry once full
+        let mut log = env.storage().persistent().get::<_, SVec<(Address, i128, u64)>>(&DataKey::DepositLog(id)).unwrap_or_else(|| SVec::new(&env));
+        if log.len() >= MAX_DEPOSIT_LOG { log.pop_front(); }
+        log.push_back((from, amount, now(&env)));
+        env.storage().persistent().set(&DataKey::DepositLog(id), &log);
     }
 
     pub fn open_dispute(env: Env, id: u128, actor: Address) {
@@ -184,20 +691,56 @@ impl MarketXEscrow {
         actor.require_auth();
         e.disputed = true;
         write_escrow(&env, id, &e);
+        DisputeOpenedEventData { id, actor }.publish(&env);
+    }
+
+    // Payer/payee objects to the pending release: dispute the escrow and drop the proposal
+    pub fn contest_release(env: Env, id: u128, actor: Address) {
+        let mut e = read_escrow(&env, id);
+        if e.closed { panic!("closed"); }
+        if !(is_member(&e.payers, &actor) || is_member(&e.payees, &actor)) { panic!("no-right"); }
+        actor.require_auth();
+        let prop: ReleaseProposal = env.storage().persistent().get(&DataKey::PendingRelease(id)).unwrap_or_else(|| panic!("no-pending"));
+        env.storage().persistent().remove(&DataKey::PendingRelease(id));
+        env.storage().persistent().remove(&DataKey::ApprovalsRelease(id));
+        env.storage().persistent().remove(&DataKey::PayeeAcks(id, prop.nonce));
+        env.storage().persistent().remove(&DataKey::ArbiterCosign(id, prop.nonce));
+        e.disputed = true;
+        write_escrow(&env, id, &e);
+        DisputeOpenedEventData { id, actor }.publish(&env);
     }
 
     pub fn propose_release(env: Env, id: u128, signer: Address, dists: SVec<(Address, i128)>) {
+        let token = read_escrow(&env, id).token;
+        Self::propose_release_token(env, id, signer, token, dists, false);
+    }
+
+    pub fn propose_release_token(env: Env, id: u128, signer: Address, token: Address, dists: SVec<(Address, i128)>, keep_open: bool) {
+        Self::open_release_proposal(env, id, signer, token, dists, keep_open, false);
+    }
+
+    // Pay `dists` and return the remainder to payers pro rata, closing the escrow once approved
+    pub fn release_and_settle(env: Env, id: u128, signer: Address, dists: SVec<(Address, i128)>) {
+        let token = read_escrow(&env, id).token;
+        Self::open_release_proposal(env, id, signer, token, dists, false, true);
+    }
+
+    fn open_release_proposal(env: Env, id: u128, signer: Address, token: Address, dists: SVec<(Address, i128)>, keep_open: bool, settle: bool) {
         let mut e = read_escrow(&env, id);
         if e.closed { panic!("closed"); }
         if e.disputed { panic!("disputed"); }
         if !is_member(&e.release_signers, &signer) { panic!("not-release-signer"); }
+        if !is_escrow_token(&e, &token) { panic!("unknown-token"); }
+        if sum_amounts(&e.deposits) < Self::get_min_escrow_amount(env.clone()) { panic!("below-min-escrow"); }
+        if has_pending_proposal(&env, id) { panic!("proposal-pending"); }
         signer.require_auth();
         if !ensure_payees_valid(&e.payees, &dists) { panic!("bad-payee"); }
         let total = sum_amounts(&dists);
-        if total <= 0 || total > e.balance { panic!("bad-total"); }
+        if total <= 0 || total > token_balance(&e, &token) { panic!("bad-total"); }
         e.nonce += 1;
-        let prop = ReleaseProposal { nonce: e.nonce, dists: dists.clone() };
+        let prop = ReleaseProposal { nonce: e.nonce, token: token.clone(), dists: dists.clone(), keep_open, settle };
         env.storage().persistent().set(&DataKey::PendingRelease(id), &prop);
+        ReleaseProposedEventData { id, signer: signer.clone(), nonce: e.nonce, token, total }.publish(&env);
         let mut approvers = SVec::new(&env);
         approvers.push_back(signer);
         env.storage().persistent().set(&DataKey::ApprovalsRelease(id), &approvers);
@@ -213,50 +756,116 @@ impl MarketXEscrow {
         let mut approvers = env.storage().persistent().get::<_, SVec<Address>>(&DataKey::ApprovalsRelease(id)).unwrap_or_else(|| SVec::new(&env));
         push_unique(&mut approvers, &signer);
         env.storage().persistent().set(&DataKey::ApprovalsRelease(id), &approvers);
-        if approvers.len() as u32 >= e.release_threshold {
+        let prop: ReleaseProposal = env.storage().persistent().get(&DataKey::PendingRelease(id)).unwrap();
+        if release_approved(&e, &approvers) && payee_acks_complete(&env, id, &e, &prop) && arbiter_cosigned(&env, id, &e, &prop) {
+            Self::execute_pending_release(env, id);
+        }
+    }
+
+    // Payee accepts the terms of the current release proposal
+    pub fn acknowledge_release(env: Env, id: u128, payee: Address) {
+        let e = read_escrow(&env, id);
+        if e.closed { panic!("closed"); }
+        if !e.require_payee_ack { panic!("ack-not-required"); }
+        if !is_member(&e.payees, &payee) { panic!("not-payee"); }
+        payee.require_auth();
+        let prop: ReleaseProposal = env.storage().persistent().get(&DataKey::PendingRelease(id)).unwrap_or_else(|| panic!("no-pending"));
+        let mut acks = read_vec_addr(&env, &DataKey::PayeeAcks(id, prop.nonce));
+        push_unique(&mut acks, &payee);
+        write_vec_addr(&env, &DataKey::PayeeAcks(id, prop.nonce), &acks);
+        // execute if signers already reached threshold and this was the last missing ack
+        let approvers = read_vec_addr(&env, &DataKey::ApprovalsRelease(id));
+        if release_approved(&e, &approvers) && payee_acks_complete(&env, id, &e, &prop) && arbiter_cosigned(&env, id, &e, &prop) {
+            Self::execute_pending_release(env, id);
+        }
+    }
+
+    // Arbiter signs off on the current release proposal of an escrow with require_arbiter_cosign
+    pub fn cosign_release(env: Env, id: u128, arbiter: Address) {
+        let e = read_escrow(&env, id);
+        if e.closed { panic!("closed"); }
+        if e.disputed { panic!("disputed"); }
+        if !e.require_arbiter_cosign { panic!("cosign-not-required"); }
+        if !is_member(&e.arbiters, &arbiter) { panic!("not-arbiter"); }
+        arbiter.require_auth();
+        let prop: ReleaseProposal = env.storage().persistent().get(&DataKey::PendingRelease(id)).unwrap_or_else(|| panic!("no-pending"));
+        env.storage().persistent().set(&DataKey::ArbiterCosign(id, prop.nonce), &arbiter);
+        // execute if signers and payees were only waiting on the arbiter
+        let approvers = read_vec_addr(&env, &DataKey::ApprovalsRelease(id));
+        if release_approved(&e, &approvers) && payee_acks_complete(&env, id, &e, &prop) {
             Self::execute_pending_release(env, id);
         }
     }
 
     fn execute_pending_release(env: Env, id: u128) {
-        let mut e = read_escrow(&env, id);
+        let e = read_escrow(&env, id);
         let prop: ReleaseProposal = env.storage().persistent().get(&DataKey::PendingRelease(id)).unwrap();
-        let dists = prop.dists;
-        let total = sum_amounts(&dists);
-        if total <= 0 || total > e.balance { panic!("bad-total"); }
-        let fee_bps = read_u32(&env, &DataKey::FeeBps) as i128;
-        let fee_collector = read_addr(&env, &DataKey::FeeCollector);
-        let client = token_client(&env, &e.token);
-        // Transfer per distribution after fee
+        // payee acks and the arbiter co-sign gate signer releases only; callers on that path check them first
+        if !payees_meet_tier(&env, &e, &prop.dists) { panic!("payee-tier-low"); }
+        Self::do_release(&env, id, prop.nonce, &prop.token, &prop.dists, prop.keep_open, prop.settle);
+        // clear pending
+        env.storage().persistent().remove(&DataKey::PendingRelease(id));
+        env.storage().persistent().remove(&DataKey::ApprovalsRelease(id));
+        env.storage().persistent().remove(&DataKey::PayeeAcks(id, prop.nonce));
+        env.storage().persistent().remove(&DataKey::ArbiterCosign(id, prop.nonce));
+    }
+
+    // Pay out `dists` net of fees and close the escrow if drained; leaves proposal and approval storage alone
+    fn do_release(env: &Env, id: u128, nonce: u64, token: &Address, dists: &SVec<(Address, i128)>, keep_open: bool, settle: bool) {
+        let mut e = read_escrow(env, id);
+        let total = sum_amounts(dists);
+        if total <= 0 || total > token_balance(&e, token) { panic!("bad-total"); }
+        // arbiter- and emergency-resolved releases happen while disputed and may carry the dispute fee
+        let fee_bps = if e.fee_exempt { 0 } else if e.disputed { Self::get_dispute_fee_bps(env.clone()).unwrap_or(read_u32(env, &DataKey::FeeBps)) as i128 } else { read_u32(env, &DataKey::FeeBps) as i128 };
+        let client = token_client(env, token);
+        // Pre-flight: compute and validate every leg before moving any tokens
         let mut fee_total: i128 = 0;
+        let mut nets: SVec<(Address, i128)> = SVec::new(env);
         for (to, amt) in dists.iter() {
             let fee = amt * fee_bps / 10_000;
             let net = amt - fee;
             if net < 0 { panic!("fee-too-high"); }
             if fee > 0 { fee_total += fee; }
-            client.transfer(&env.current_contract_address(), &to, &net);
+            nets.push_back((to, net));
         }
-        if fee_total > 0 { client.transfer(&env.current_contract_address(), &fee_collector, &fee_total); }
-        e.balance -= total;
-        if e.balance == 0 { e.closed = true; }
-        // clear pending
-        env.storage().persistent().remove(&DataKey::PendingRelease(id));
-        env.storage().persistent().remove(&DataKey::ApprovalsRelease(id));
-        write_escrow(&env, id, &e);
+        // Transfer per distribution after fee
+        for (to, net) in nets.iter() { client.transfer(&env.current_contract_address(), &to, &net); }
+        pay_fees(env, &client, fee_total);
+        adjust_token_balance(env, &mut e, token, -total);
+        ReleaseExecutedEventData { id, nonce, token: token.clone(), total, fee: fee_total }.publish(env);
+        if settle { refund_remaining(env, id, &mut e, token, RefundMode::Proportional); }
+        if is_drained(&e) && !keep_open { e.closed = true; cleanup_on_close(env, id); }
+        write_escrow(env, id, &e);
+    }
+
+    // Pay out a release that needs no signer proposal (arbiter, emergency or timeout) under a fresh nonce
+    fn release_now(env: &Env, id: u128, token: &Address, dists: &SVec<(Address, i128)>) {
+        let mut e = read_escrow(env, id);
+        if !payees_meet_tier(env, &e, dists) { panic!("payee-tier-low"); }
+        e.nonce += 1;
+        write_escrow(env, id, &e);
+        Self::do_release(env, id, e.nonce, token, dists, false, false);
     }
 
     pub fn propose_refund(env: Env, id: u128, signer: Address, dists: SVec<(Address, i128)>) {
+        let token = read_escrow(&env, id).token;
+        Self::propose_refund_token(env, id, signer, token, dists);
+    }
+
+    pub fn propose_refund_token(env: Env, id: u128, signer: Address, token: Address, dists: SVec<(Address, i128)>) {
         // dists target payers
         let e = read_escrow(&env, id);
         if e.closed { panic!("closed"); }
         if e.disputed { panic!("disputed"); }
         if !is_member(&e.refund_signers, &signer) { panic!("not-refund-signer"); }
+        if !is_escrow_token(&e, &token) { panic!("unknown-token"); }
+        if has_pending_proposal(&env, id) { panic!("proposal-pending"); }
         signer.require_auth();
         // validate recipients are payers
         for (p, _) in dists.iter() { if !is_member(&e.payers, &p) { panic!("bad-payer"); } }
         let total = sum_amounts(&dists);
-        if total <= 0 || total > e.balance { panic!("bad-total"); }
-        env.storage().persistent().set(&DataKey::PendingRelease(id), &ReleaseProposal { nonce: e.nonce + 1, dists: dists.clone() });
+        if total <= 0 || total > token_balance(&e, &token) { panic!("bad-total"); }
+        env.storage().persistent().set(&DataKey::PendingRefund(id), &ReleaseProposal { nonce: e.nonce + 1, token, dists: dists.clone(), keep_open: false, settle: false });
         let mut approvers = SVec::new(&env);
         approvers.push_back(signer);
         env.storage().persistent().set(&DataKey::ApprovalsRefund(id), &approvers);
@@ -268,149 +877,324 @@ impl MarketXEscrow {
         if e.disputed { panic!("disputed"); }
         if !is_member(&e.refund_signers, &signer) { panic!("not-refund-signer"); }
         signer.require_auth();
+        let prop: ReleaseProposal = env.storage().persistent().get(&DataKey::PendingRefund(id)).unwrap_or_else(|| panic!("no-pending"));
         let mut approvers = env.storage().persistent().get::<_, SVec<Address>>(&DataKey::ApprovalsRefund(id)).unwrap_or_else(|| SVec::new(&env));
         push_unique(&mut approvers, &signer);
         env.storage().persistent().set(&DataKey::ApprovalsRefund(id), &approvers);
         if approvers.len() as u32 >= e.refund_threshold {
             // execute pending refund
-            let prop: ReleaseProposal = env.storage().persistent().get(&DataKey::PendingRelease(id)).unwrap();
-            let dists = prop.dists;
+            let (token, dists) = (prop.token, prop.dists);
             let total = sum_amounts(&dists);
-            if total <= 0 || total > e.balance { panic!("bad-total"); }
-            let client = token_client(&env, &e.token);
+            if total <= 0 || total > token_balance(&e, &token) { panic!("bad-total"); }
+            let client = token_client(&env, &token);
             for (to, amt) in dists.iter() {
-                client.transfer(&env.current_contract_address(), &to, &amt);
+                client.transfer(&env.current_contract_address(), &refund_target(&env, id, &to), &amt);
             }
-            e.balance -= total;
-            if e.balance == 0 { e.closed = true; }
-            env.storage().persistent().remove(&DataKey::PendingRelease(id));
+            adjust_token_balance(&env, &mut e, &token, -total);
+            RefundExecutedEventData { id, token, amount: total }.publish(&env);
+            if is_drained(&e) { e.closed = true; cleanup_on_close(&env, id); }
+            env.storage().persistent().remove(&DataKey::PendingRefund(id));
             env.storage().persistent().remove(&DataKey::ApprovalsRefund(id));
             write_escrow(&env, id, &e);
         }
     }
 
+    // Drop a release proposal that cannot or should not execute, freeing the slot for a new one
+    pub fn cancel_release_proposal(env: Env, id: u128, signer: Address) {
+        let e = read_escrow(&env, id);
+        if e.closed { panic!("closed"); }
+        // while disputed the pending release belongs to the arbiters
+        if e.disputed { panic!("disputed"); }
+        if !is_member(&e.release_signers, &signer) { panic!("not-release-signer"); }
+        signer.require_auth();
+        let store = env.storage().persistent();
+        let prop: ReleaseProposal = store.get(&DataKey::PendingRelease(id)).unwrap_or_else(|| panic!("no-pending"));
+        store.remove(&DataKey::PendingRelease(id));
+        store.remove(&DataKey::ApprovalsRelease(id));
+        store.remove(&DataKey::PayeeAcks(id, prop.nonce));
+        store.remove(&DataKey::ArbiterCosign(id, prop.nonce));
+    }
+
+    pub fn cancel_refund_proposal(env: Env, id: u128, signer: Address) {
+        let e = read_escrow(&env, id);
+        if e.closed { panic!("closed"); }
+        if !is_member(&e.refund_signers, &signer) { panic!("not-refund-signer"); }
+        signer.require_auth();
+        let store = env.storage().persistent();
+        if !store.has(&DataKey::PendingRefund(id)) { panic!("no-pending"); }
+        store.remove(&DataKey::PendingRefund(id));
+        store.remove(&DataKey::ApprovalsRefund(id));
+    }
+
+    // Discard arbiter votes so far, e.g. after a wrong distribution was put forward
+    pub fn cancel_arbiter_proposal(env: Env, id: u128, arbiter: Address) {
+        let e = read_escrow(&env, id);
+        if e.closed { panic!("closed"); }
+        if !e.disputed { panic!("not-disputed"); }
+        if !is_member(&e.arbiters, &arbiter) { panic!("not-arbiter"); }
+        arbiter.require_auth();
+        let store = env.storage().persistent();
+        if !store.has(&DataKey::PendingArbiter(id)) { panic!("no-pending"); }
+        store.remove(&DataKey::PendingArbiter(id));
+        store.remove(&DataKey::ApprovalsArbiter(id));
+    }
+
+    // Withdraw an approval before the threshold is met; the proposal itself stays open
+    pub fn revoke_release_approval(env: Env, id: u128, signer: Address) {
+        let e = read_escrow(&env, id);
+        if e.closed { panic!("closed"); }
+        if !env.storage().persistent().has(&DataKey::PendingRelease(id)) { panic!("no-pending"); }
+        signer.require_auth();
+        let mut approvers = read_vec_addr(&env, &DataKey::ApprovalsRelease(id));
+        remove_member(&mut approvers, &signer);
+        write_vec_addr(&env, &DataKey::ApprovalsRelease(id), &approvers);
+    }
+
+    pub fn revoke_refund_approval(env: Env, id: u128, signer: Address) {
+        let e = read_escrow(&env, id);
+        if e.closed { panic!("closed"); }
+        if !env.storage().persistent().has(&DataKey::PendingRefund(id)) { panic!("no-pending"); }
+        signer.require_auth();
+        let mut approvers = read_vec_addr(&env, &DataKey::ApprovalsRefund(id));
+        remove_member(&mut approvers, &signer);
+        write_vec_addr(&env, &DataKey::ApprovalsRefund(id), &approvers);
+    }
+
+    // Route this payer's future refunds to another address (e.g. after key rotation)
+    pub fn set_refund_recipient(env: Env, id: u128, payer: Address, recipient: Address) {
+        let e = read_escrow(&env, id);
+        if e.closed { panic!("closed"); }
+        if !is_member(&e.payers, &payer) { panic!("not-payer"); }
+        payer.require_auth();
+        env.storage().persistent().set(&DataKey::RefundRecipient(id, payer), &recipient);
+    }
+
     pub fn refund_timeout(env: Env, id: u128) {
         let mut e = read_escrow(&env, id);
         if e.closed { panic!("closed"); }
         if e.disputed { panic!("disputed"); }
         if now(&env) < e.expiry_ts { panic!("not-expired"); }
-        let client = token_client(&env, &e.token);
-        let mut remaining = e.balance;
-        for (p, a) in e.deposits.iter() {
-            if remaining <= 0 { break; }
-            let amt = if a <= remaining { a } else { remaining };
-            if amt > 0 { client.transfer(&env.current_contract_address(), &p, &amt); }
-            remaining -= amt;
+        let (token, mode) = (e.token.clone(), e.refund_mode);
+        refund_remaining(&env, id, &mut e, &token, mode);
+        for token in e.extra_tokens.clone().iter() {
+            refund_remaining(&env, id, &mut e, &token, mode);
         }
-        e.balance = remaining;
-        if e.balance == 0 { e.closed = true; }
+        if is_drained(&e) { e.closed = true; cleanup_on_close(&env, id); }
         write_escrow(&env, id, &e);
     }
 
+    // Payee attests their obligations are met; gates auto_release when delivery_fallback is set
+    pub fn confirm_delivery(env: Env, id: u128, payee: Address) {
+        let e = read_escrow(&env, id);
+        if e.closed { panic!("closed"); }
+        if !is_member(&e.payees, &payee) { panic!("not-payee"); }
+        payee.require_auth();
+        let mut confirmed = read_vec_addr(&env, &DataKey::DeliveryConfirms(id));
+        push_unique(&mut confirmed, &payee);
+        write_vec_addr(&env, &DataKey::DeliveryConfirms(id), &confirmed);
+    }
+
     pub fn auto_release(env: Env, id: u128) {
         let e = read_escrow(&env, id);
         if e.closed { panic!("closed"); }
         if e.disputed { panic!("disputed"); }
         match e.auto_release_ts { Some(t) => if now(&env) < t { panic!("too-early"); }, None => panic!("no-auto-release") }
-        // equal split among payees
-        let n = e.payees.len() as i128;
-        if n <= 0 { panic!("no-payees"); }
-        let mut dists: SVec<(Address, i128)> = SVec::new(&env);
-        let base = e.balance / n;
-        let mut rem = e.balance - base * n;
-        for i in 0..e.payees.len() {
-            let mut amt = base;
-            if rem > 0 { amt += 1; rem -= 1; }
-            dists.push_back((e.payees.get_unchecked(i).unwrap(), amt));
+        if let Some(margin) = e.delivery_fallback {
+            let confirmed = read_vec_addr(&env, &DataKey::DeliveryConfirms(id));
+            let all_confirmed = e.payees.iter().all(|p| is_member(&confirmed, &p));
+            if !all_confirmed && now(&env) < e.auto_release_ts.unwrap().saturating_add(margin) { panic!("delivery-unconfirmed"); }
+        }
+        // the timeout stands in for signer approval and payee acks, but not for an arbiter co-sign
+        if e.require_arbiter_cosign { panic!("arbiter-cosign-missing"); }
+        if is_drained(&e) { panic!("bad-total"); }
+        // every token held is split equally among payees
+        let mut tokens = SVec::new(&env);
+        tokens.push_back(e.token.clone());
+        tokens.append(&e.extra_tokens);
+        for token in tokens.iter() {
+            let dists = equal_split(&env, &e.payees, token_balance(&e, &token));
+            if !dists.is_empty() { Self::release_now(&env, id, &token, &dists); }
         }
-        drop(e);
-        // reuse propose->execute path: set pending and approvals as threshold satisfied
-        let mut e2 = read_escrow(&env, id);
-        e2.nonce += 1;
-        let prop = ReleaseProposal { nonce: e2.nonce, dists: dists.clone() };
-        env.storage().persistent().set(&DataKey::PendingRelease(id), &prop);
-        let mut approvers = SVec::new(&env);
-        // fake approvals: set len == threshold
-        for i in 0..e2.release_threshold { approvers.push_back(env.current_contract_address()); }
-        env.storage().persistent().set(&DataKey::ApprovalsRelease(id), &approvers);
-        write_escrow(&env, id, &e2);
-        Self::execute_pending_release(env, id);
     }
 
     pub fn arbiter_release(env: Env, id: u128, signer: Address, dists: SVec<(Address, i128)>) {
+        let token = read_escrow(&env, id).token;
+        Self::arbiter_release_token(env, id, signer, token, dists);
+    }
+
+    // Resolve a dispute per token; the escrow stays disputed until every token balance is paid out
+    pub fn arbiter_release_token(env: Env, id: u128, signer: Address, token: Address, dists: SVec<(Address, i128)>) {
         let e = read_escrow(&env, id);
         if e.closed { panic!("closed"); }
         if !e.disputed { panic!("not-disputed"); }
+        if e.escalated { panic!("escalated"); }
         if !is_member(&e.arbiters, &signer) { panic!("not-arbiter"); }
+        if !is_escrow_token(&e, &token) { panic!("unknown-token"); }
         signer.require_auth();
         if !ensure_payees_valid(&e.payees, &dists) { panic!("bad-payee"); }
         let total = sum_amounts(&dists);
-        if total <= 0 || total > e.balance { panic!("bad-total"); }
-        env.storage().persistent().set(&DataKey::PendingRelease(id), &ReleaseProposal { nonce: e.nonce + 1, dists: dists.clone() });
-        let mut approvers = env.storage().persistent().get::<_, SVec<Address>>(&DataKey::ApprovalsArbiter(id)).unwrap_or_else(|| SVec::new(&env));
-        push_unique(&mut approvers, &signer);
-        env.storage().persistent().set(&DataKey::ApprovalsArbiter(id), &approvers);
+        if total <= 0 || total > token_balance(&e, &token) { panic!("bad-total"); }
+        let prop = ResolutionProposal { refund: false, token, dists: dists.clone() };
+        let approvers = vote_resolution(&env, &DataKey::PendingArbiter(id), &DataKey::ApprovalsArbiter(id), &prop, &signer);
         if approvers.len() as u32 >= e.arbiter_threshold {
-            // execute like normal release
-            Self::execute_pending_release(env, id);
-            // clear arbiter approvals and undispute if closed
+            Self::release_now(&env, id, &prop.token, &dists);
+            // clear arbiter votes and undispute if closed
+            env.storage().persistent().remove(&DataKey::PendingArbiter(id));
             env.storage().persistent().remove(&DataKey::ApprovalsArbiter(id));
             let mut e2 = read_escrow(&env, id);
-            if e2.balance == 0 { e2.disputed = false; e2.closed = true; write_escrow(&env, id, &e2); }
+            if e2.closed && e2.disputed { e2.disputed = false; write_escrow(&env, id, &e2); }
         }
     }
 
     pub fn arbiter_refund(env: Env, id: u128, signer: Address, dists: SVec<(Address, i128)>) {
+        let token = read_escrow(&env, id).token;
+        Self::arbiter_refund_token(env, id, signer, token, dists);
+    }
+
+    pub fn arbiter_refund_token(env: Env, id: u128, signer: Address, token: Address, dists: SVec<(Address, i128)>) {
         let mut e = read_escrow(&env, id);
         if e.closed { panic!("closed"); }
         if !e.disputed { panic!("not-disputed"); }
+        if e.escalated { panic!("escalated"); }
         if !is_member(&e.arbiters, &signer) { panic!("not-arbiter"); }
+        if !is_escrow_token(&e, &token) { panic!("unknown-token"); }
         signer.require_auth();
         // validate recipients are payers
         for (p, _) in dists.iter() { if !is_member(&e.payers, &p) { panic!("bad-payer"); } }
         let total = sum_amounts(&dists);
-        if total <= 0 || total > e.balance { panic!("bad-total"); }
-        let mut approvers = env.storage().persistent().get::<_, SVec<Address>>(&DataKey::ApprovalsArbiter(id)).unwrap_or_else(|| SVec::new(&env));
-        push_unique(&mut approvers, &signer);
-        env.storage().persistent().set(&DataKey::ApprovalsArbiter(id), &approvers);
+        if total <= 0 || total > token_balance(&e, &token) { panic!("bad-total"); }
+        let prop = ResolutionProposal { refund: true, token: token.clone(), dists: dists.clone() };
+        let approvers = vote_resolution(&env, &DataKey::PendingArbiter(id), &DataKey::ApprovalsArbiter(id), &prop, &signer);
         if approvers.len() as u32 >= e.arbiter_threshold {
-            let client = token_client(&env, &e.token);
-            for (to, amt) in dists.iter() { client.transfer(&env.current_contract_address(), &to, &amt); }
-            e.balance -= total;
-            if e.balance == 0 { e.closed = true; e.disputed = false; }
-            env.storage().persistent().remove(&DataKey::ApprovalsArbiter(id));
+            let client = token_client(&env, &token);
+            // refunds are fee-free unless a dispute fee is configured
+            let fee_bps = if e.fee_exempt { 0 } else { Self::get_dispute_fee_bps(env.clone()).unwrap_or(0) as i128 };
+            let mut fee_total: i128 = 0;
+            for (to, amt) in dists.iter() {
+                let fee = amt * fee_bps / 10_000;
+                fee_total += fee;
+                client.transfer(&env.current_contract_address(), &refund_target(&env, id, &to), &(amt - fee));
+            }
+            pay_fees(&env, &client, fee_total);
+            adjust_token_balance(&env, &mut e, &token, -total);
+            RefundExecutedEventData { id, token, amount: total }.publish(&env);
+            if is_drained(&e) { e.closed = true; e.disputed = false; cleanup_on_close(&env, id); }
+            env.storage().persistent().remove(&DataKey::PendingArbiter(id));
+            env.storage().persistent().remove(&DataKey::ApprovalsArbiter(id));
             write_escrow(&env, id, &e);
         }
     }
 
     pub fn emergency_release(env: Env, id: u128, signer: Address, dists: SVec<(Address, i128)>) {
+        let token = read_escrow(&env, id).token;
+        Self::emergency_release_token(env, id, signer, token, dists);
+    }
+
+    pub fn emergency_release_token(env: Env, id: u128, signer: Address, token: Address, dists: SVec<(Address, i128)>) {
         let eadmins = read_vec_addr(&env, &DataKey::EmergencyAdmins);
         let ethresh = read_u32(&env, &DataKey::EmergencyThreshold);
         if !is_member(&eadmins, &signer) { panic!("not-emergency"); }
         signer.require_auth();
-        let mut e = read_escrow(&env, id);
+        let e = read_escrow(&env, id);
         if e.closed { panic!("closed"); }
+        // arbiters keep a deadline-bound dispute until it is escalated
+        if e.disputed && e.arbiter_deadline_ts.is_some() && !e.escalated { panic!("not-escalated"); }
+        if !is_escrow_token(&e, &token) { panic!("unknown-token"); }
         let total = sum_amounts(&dists);
-        if total <= 0 || total > e.balance { panic!("bad-total"); }
-        env.storage().persistent().set(&DataKey::PendingRelease(id), &ReleaseProposal { nonce: e.nonce + 1, dists: dists.clone() });
-        let mut approvers = env.storage().persistent().get::<_, SVec<Address>>(&DataKey::ApprovalsEmergency(id)).unwrap_or_else(|| SVec::new(&env));
-        push_unique(&mut approvers, &signer);
-        env.storage().persistent().set(&DataKey::ApprovalsEmergency(id), &approvers);
+        if total <= 0 || total > token_balance(&e, &token) { panic!("bad-total"); }
+        let prop = ResolutionProposal { refund: false, token, dists: dists.clone() };
+        // cancel votes were cast against the proposal being replaced
+        if env.storage().persistent().get::<_, ResolutionProposal>(&DataKey::PendingEmergency(id)).as_ref() != Some(&prop) {
+            env.storage().persistent().remove(&DataKey::CancelsEmergency(id));
+        }
+        let approvers = vote_resolution(&env, &DataKey::PendingEmergency(id), &DataKey::ApprovalsEmergency(id), &prop, &signer);
         if approvers.len() as u32 >= ethresh {
-            Self::execute_pending_release(env, id);
+            Self::release_now(&env, id, &prop.token, &dists);
+            env.storage().persistent().remove(&DataKey::PendingEmergency(id));
             env.storage().persistent().remove(&DataKey::ApprovalsEmergency(id));
+            env.storage().persistent().remove(&DataKey::CancelsEmergency(id));
+            // an escalated dispute is resolved by the emergency release
+            let mut e2 = read_escrow(&env, id);
+            if e2.escalated { e2.escalated = false; e2.disputed = false; write_escrow(&env, id, &e2); }
+        }
+    }
+
+    // Hand a dispute the arbiters failed to resolve by arbiter_deadline_ts to the emergency admins
+    pub fn escalate_dispute(env: Env, id: u128, actor: Address) {
+        let mut e = read_escrow(&env, id);
+        if e.closed { panic!("closed"); }
+        if !e.disputed { panic!("not-disputed"); }
+        if e.escalated { panic!("escalated"); }
+        if !(is_member(&e.payers, &actor) || is_member(&e.payees, &actor)) { panic!("no-right"); }
+        match e.arbiter_deadline_ts { Some(t) => if now(&env) < t { panic!("too-early"); }, None => panic!("no-arbiter-deadline") }
+        actor.require_auth();
+        e.escalated = true;
+        write_escrow(&env, id, &e);
+        DisputeEscalatedEventData { id, actor }.publish(&env);
+    }
+
+    // Vote to abort an emergency release that has not reached threshold yet; it is
+    // dropped once the emergency threshold of admins has voted to cancel
+    pub fn cancel_emergency_release(env: Env, id: u128, signer: Address) {
+        let eadmins = read_vec_addr(&env, &DataKey::EmergencyAdmins);
+        let ethresh = read_u32(&env, &DataKey::EmergencyThreshold);
+        if !is_member(&eadmins, &signer) { panic!("not-emergency"); }
+        signer.require_auth();
+        let e = read_escrow(&env, id);
+        if e.closed { panic!("closed"); }
+        if !env.storage().persistent().has(&DataKey::PendingEmergency(id)) { panic!("no-pending"); }
+        let mut cancels = read_vec_addr(&env, &DataKey::CancelsEmergency(id));
+        push_unique(&mut cancels, &signer);
+        if (cancels.len() as u32) < ethresh {
+            write_vec_addr(&env, &DataKey::CancelsEmergency(id), &cancels);
+            return;
         }
+        env.storage().persistent().remove(&DataKey::PendingEmergency(id));
+        env.storage().persistent().remove(&DataKey::ApprovalsEmergency(id));
+        env.storage().persistent().remove(&DataKey::CancelsEmergency(id));
+        EmergencyCancelledEventData { id, approvers: cancels }.publish(&env);
     }
 
     // Views
     pub fn get_escrow(env: Env, id: u128) -> Escrow { read_escrow(&env, id) }
+    // Closed flag and parties only, so other contracts can verify a completed deal without decoding Escrow
+    pub fn get_escrow_parties(env: Env, id: u128) -> (bool, SVec<Address>, SVec<Address>) { let e = read_escrow(&env, id); (e.closed, e.payers, e.payees) }
+    // Seconds left until auto-release (None if unset) and expiry, 0 once elapsed
+    pub fn get_escrow_timers(env: Env, id: u128) -> (Option<u64>, u64) {
+        let e = read_escrow(&env, id);
+        let t = now(&env);
+        (e.auto_release_ts.map(|ts| ts.saturating_sub(t)), e.expiry_ts.saturating_sub(t))
+    }
+    // Permissionless keeper hook: keeps the contract config and the listed escrows (with their pending
+    // proposals, approvals and indexes) alive, skipping unknown ids; returns how many were bumped
+    pub fn bump_ttl(env: Env, ids: SVec<u128>) -> u32 {
+        if ids.len() > MAX_BUMP_BATCH { panic!("batch-too-large"); }
+        // the instance holds no data, but the contract instance and code must stay live too
+        env.storage().instance().extend_ttl(BUMP_TTL, BUMP_TTL);
+        bump_config(&env);
+        let mut bumped = 0;
+        for id in ids.iter() {
+            if env.storage().persistent().has(&DataKey::Escrow(id)) { bump_escrow(&env, id); bumped += 1; }
+        }
+        bumped
+    }
     pub fn get_fee_params(env: Env) -> (u32, Address) { (read_u32(&env, &DataKey::FeeBps), read_addr(&env, &DataKey::FeeCollector)) }
+    pub fn get_active_escrow_count(env: Env) -> u32 { env.storage().persistent().get::<_, u32>(&DataKey::ActiveEscrows).unwrap_or(0) }
+    pub fn get_total_locked(env: Env, token: Address) -> i128 { env.storage().persistent().get::<_, i128>(&DataKey::TotalLocked(token)).unwrap_or(0) }
+    pub fn get_escrows_by_role(env: Env, who: Address, role: u32, offset: u32, limit: u32) -> SVec<u128> {
+        if role > ROLE_ARBITER { panic!("bad-role"); }
+        let ids = env.storage().persistent().get::<_, SVec<u128>>(&DataKey::RoleEscrows(who, role)).unwrap_or_else(|| SVec::new(&env));
+        let end = offset.saturating_add(limit.min(MAX_PAGE)).min(ids.len());
+        if offset >= end { return SVec::new(&env); }
+        ids.slice(offset..end)
+    }
+    pub fn get_deposit_log(env: Env, id: u128) -> SVec<(Address, i128, u64)> { env.storage().persistent().get::<_, SVec<(Address, i128, u64)>>(&DataKey::DepositLog(id)).unwrap_or_else(|| SVec::new(&env)) }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use soroban_sdk::{testutils::{Address as _, Ledger}, Address, Env, Vec as SVec, String};
+    use soroban_sdk::{symbol_short, testutils::{Address as _, Events, Ledger}, vec, Address, Env, Event, Vec as SVec, String};
 
     fn deploy_token(e: &Env, admin: &Address) -> Address {
         use soroban_token_contract::{Token, Client as TokenClient};
@@ -420,6 +1204,38 @@ mod test {
         token_id
     }
 
+    // Stands in for the marketplace's fee ledger
+    #[soroban_sdk::contract]
+    struct MockMarketplace;
+
+    #[soroban_sdk::contractimpl]
+    impl MockMarketplace {
+        pub fn record_fee_collection_for_asset(e: Env, reporter: Address, asset: Address, amount: u128) {
+            reporter.require_auth();
+            let total: u128 = e.storage().instance().get(&asset).unwrap_or(0);
+            e.storage().instance().set(&asset, &(total + amount));
+            e.storage().instance().set(&symbol_short!("reporter"), &reporter);
+        }
+
+        pub fn get_total_fees_for_asset(e: Env, asset: Address) -> u128 { e.storage().instance().get(&asset).unwrap_or(0) }
+
+        pub fn get_reporter(e: Env) -> Option<Address> { e.storage().instance().get(&symbol_short!("reporter")) }
+    }
+
+    // Stands in for the reputation contract's scaled score lookup
+    #[soroban_sdk::contract]
+    struct MockReputation;
+
+    #[soroban_sdk::contractimpl]
+    impl MockReputation {
+        pub fn set_score(e: Env, user: Address, stars: u32) { e.storage().instance().set(&user, &stars); }
+
+        pub fn get_score_scaled(e: Env, user: Address, scale: u32) -> u32 {
+            let stars: u32 = e.storage().instance().get(&user).unwrap_or(0);
+            stars * scale / 5
+        }
+    }
+
     fn deploy_contract(e: &Env) -> (Address, super::MarketXEscrowClient) {
         let id = e.register_contract(None, MarketXEscrow);
         let client = MarketXEscrowClient::new(e, &id);
@@ -459,7 +1275,7 @@ mod test {
         let mut refs = SVec::new(&e); refs.push_back(payer1.clone()); refs.push_back(payer2.clone());
         let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e)); arbs.push_back(Address::generate(&e));
 
-        client.create_escrow(&1u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: rels.clone(), release_threshold: 2, refund_signers: refs.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 2, auto_release_ts: Some(2000), expiry_ts: 3000 });
+        client.create_escrow(&1u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: rels.clone(), release_threshold: 2, refund_signers: refs.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 2, auto_release_ts: Some(2000), expiry_ts: 3000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
 
         // deposit
         client.deposit(&1u128, &payer1, &600);
@@ -538,7 +1354,7 @@ mod test {
         let mut refs = SVec::new(&e); refs.push_back(payer.clone());
         let arbs = SVec::new(&e);
 
-        client.create_escrow(&2u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: rels.clone(), release_threshold: 1, refund_signers: refs.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: Some(1500), expiry_ts: 2000 });
+        client.create_escrow(&2u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: rels.clone(), release_threshold: 1, refund_signers: refs.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: Some(1500), expiry_ts: 2000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
 
         client.deposit(&2u128, &payer, &1000);
 
@@ -550,10 +1366,1556 @@ mod test {
         assert_eq!(token_client.balance(&payee2), 500);
 
         // New escrow to test refund timeout
-        client.create_escrow(&3u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: rels.clone(), release_threshold: 1, refund_signers: refs.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 1200 });
+        client.create_escrow(&3u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: rels.clone(), release_threshold: 1, refund_signers: refs.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 1200, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
         client.deposit(&3u128, &payer, &600);
         e.ledger().set_timestamp(1300);
         client.refund_timeout(&3u128);
         assert_eq!(token_client.balance(&payer), 1000 - 1000 + 600); // original balance after auto-release was 0, refunded 600
     }
+
+    #[test]
+    fn test_deposit_log() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &fee_collector, &eadmins, &1u32);
+
+        let token_admin = Address::generate(&e);
+        let token_addr = deploy_token(&e, &token_admin);
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+
+        let payer1 = Address::generate(&e);
+        let payer2 = Address::generate(&e);
+        let payee = Address::generate(&e);
+        token_client.mint(&payer1, &1000);
+        token_client.mint(&payer2, &1000);
+
+        let mut payers = SVec::new(&e); payers.push_back(payer1.clone()); payers.push_back(payer2.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        client.create_escrow(&4u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+
+        client.deposit(&4u128, &payer1, &100);
+        e.ledger().set_timestamp(1100);
+        client.deposit(&4u128, &payer2, &200);
+        e.ledger().set_timestamp(1250);
+        client.deposit(&4u128, &payer1, &50);
+
+        let log = client.get_deposit_log(&4u128);
+        assert_eq!(log.len(), 3);
+        assert_eq!(log.get_unchecked(0).unwrap(), (payer1.clone(), 100, 1000));
+        assert_eq!(log.get_unchecked(1).unwrap(), (payer2.clone(), 200, 1100));
+        assert_eq!(log.get_unchecked(2).unwrap(), (payer1.clone(), 50, 1250));
+
+        // aggregate deposits are still kept per payer
+        let esc = client.get_escrow(&4u128);
+        assert_eq!(esc.balance, 350);
+        assert_eq!(esc.deposits.get_unchecked(0).unwrap(), (payer1, 150));
+    }
+
+    #[test]
+    fn test_release_waits_for_payee_ack() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &fee_collector, &eadmins, &1u32);
+
+        let token_admin = Address::generate(&e);
+        let token_addr = deploy_token(&e, &token_admin);
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+
+        let payer = Address::generate(&e);
+        let payee = Address::generate(&e);
+        token_client.mint(&payer, &1000);
+
+        let mut payers = SVec::new(&e); payers.push_back(payer.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        client.create_escrow(&5u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: true, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+        client.deposit(&5u128, &payer, &1000);
+
+        let mut dists = SVec::new(&e); dists.push_back((payee.clone(), 600));
+        client.propose_release(&5u128, &payer, &dists);
+        // threshold met, but the payee has not accepted the terms yet
+        client.approve_release(&5u128, &payer);
+        assert_eq!(token_client.balance(&payee), 0);
+        assert_eq!(client.get_escrow(&5u128).balance, 1000);
+
+        client.acknowledge_release(&5u128, &payee);
+        assert_eq!(token_client.balance(&payee), 600);
+        assert_eq!(client.get_escrow(&5u128).balance, 400);
+    }
+
+    #[test]
+    fn test_contest_release() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (contract_id, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &fee_collector, &eadmins, &1u32);
+
+        let token_admin = Address::generate(&e);
+        let token_addr = deploy_token(&e, &token_admin);
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+
+        let payer1 = Address::generate(&e);
+        let payer2 = Address::generate(&e);
+        let payee = Address::generate(&e);
+        token_client.mint(&payer1, &1000);
+
+        let mut payers = SVec::new(&e); payers.push_back(payer1.clone()); payers.push_back(payer2.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        client.create_escrow(&6u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 2, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: true, refund_mode: RefundMode::Proportional });
+        client.deposit(&6u128, &payer1, &1000);
+
+        let mut dists = SVec::new(&e); dists.push_back((payee.clone(), 1000));
+        client.propose_release(&6u128, &payer1, &dists);
+        client.cosign_release(&6u128, &arbs.get_unchecked(0));
+        let nonce = e.as_contract(&contract_id, || e.storage().persistent().get::<_, ReleaseProposal>(&DataKey::PendingRelease(6)).unwrap().nonce);
+        client.contest_release(&6u128, &payer2);
+
+        assert!(client.get_escrow(&6u128).disputed);
+        e.as_contract(&contract_id, || {
+            assert!(!e.storage().persistent().has(&DataKey::PendingRelease(6)));
+            assert!(!e.storage().persistent().has(&DataKey::ApprovalsRelease(6)));
+            // the contested proposal's co-sign goes with it
+            assert!(!e.storage().persistent().has(&DataKey::ArbiterCosign(6, nonce)));
+        });
+        assert_eq!(token_client.balance(&payee), 0);
+    }
+
+    #[test]
+    fn test_close_cleans_auxiliary_keys() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (contract_id, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &fee_collector, &eadmins, &1u32);
+
+        let token_admin = Address::generate(&e);
+        let token_addr = deploy_token(&e, &token_admin);
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+
+        let payer1 = Address::generate(&e);
+        let payer2 = Address::generate(&e);
+        let payee = Address::generate(&e);
+        token_client.mint(&payer1, &1000);
+
+        let mut payers = SVec::new(&e); payers.push_back(payer1.clone()); payers.push_back(payer2.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        client.create_escrow(&7u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 2, refund_signers: payers.clone(), refund_threshold: 2, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+        client.deposit(&7u128, &payer1, &1000);
+
+        // leave a refund proposal hanging, then refund everything at expiry
+        let mut refund = SVec::new(&e); refund.push_back((payer1.clone(), 100));
+        client.propose_refund(&7u128, &payer1, &refund);
+        e.ledger().set_timestamp(5000);
+        client.refund_timeout(&7u128);
+
+        assert!(client.get_escrow(&7u128).closed);
+        e.as_contract(&contract_id, || {
+            let store = e.storage().persistent();
+            assert!(!store.has(&DataKey::PendingRelease(7)));
+            assert!(!store.has(&DataKey::PendingRefund(7)));
+            assert!(!store.has(&DataKey::PendingArbiter(7)));
+            assert!(!store.has(&DataKey::PendingEmergency(7)));
+            assert!(!store.has(&DataKey::ApprovalsRelease(7)));
+            assert!(!store.has(&DataKey::ApprovalsRefund(7)));
+            assert!(!store.has(&DataKey::ApprovalsArbiter(7)));
+            assert!(!store.has(&DataKey::ApprovalsEmergency(7)));
+        });
+    }
+
+    #[test]
+    fn test_multi_token_escrow() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &fee_collector, &eadmins, &1u32);
+
+        let token_admin = Address::generate(&e);
+        let token_a = deploy_token(&e, &token_admin);
+        let token_b = deploy_token(&e, &token_admin);
+        let client_a = soroban_token_contract::Client::new(&e, &token_a);
+        let client_b = soroban_token_contract::Client::new(&e, &token_b);
+
+        let payer = Address::generate(&e);
+        let payee = Address::generate(&e);
+        client_a.mint(&payer, &500);
+        client_b.mint(&payer, &300);
+
+        let mut payers = SVec::new(&e); payers.push_back(payer.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        let mut extra = SVec::new(&e); extra.push_back(token_b.clone());
+        client.create_escrow(&8u128, &EscrowInit { token: token_a.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: extra, strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+
+        client.deposit(&8u128, &payer, &500);
+        client.deposit_token(&8u128, &payer, &token_b, &300);
+        let esc = client.get_escrow(&8u128);
+        assert_eq!(esc.balance, 500);
+        assert_eq!(esc.extra_balances.get(token_b.clone()), Some(300));
+
+        // unregistered tokens are rejected
+        let stray = deploy_token(&e, &token_admin);
+        assert!(client.try_deposit_token(&8u128, &payer, &stray, &1).is_err());
+
+        let mut dists = SVec::new(&e); dists.push_back((payee.clone(), 500));
+        client.propose_release(&8u128, &payer, &dists);
+        client.approve_release(&8u128, &payer);
+        assert_eq!(client_a.balance(&payee), 500);
+        // primary drained but the extra token is still held, so the escrow stays open
+        assert!(!client.get_escrow(&8u128).closed);
+
+        let mut dists_b = SVec::new(&e); dists_b.push_back((payee.clone(), 300));
+        client.propose_release_token(&8u128, &payer, &token_b, &dists_b, &false);
+        client.approve_release(&8u128, &payer);
+        assert_eq!(client_b.balance(&payee), 300);
+        assert!(client.get_escrow(&8u128).closed);
+    }
+
+    #[test]
+    fn test_strict_role_conflicts() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let eadmin = Address::generate(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(eadmin.clone());
+        client.init(&admin, &0u32, &fee_collector, &eadmins, &1u32);
+
+        let token_admin = Address::generate(&e);
+        let token_addr = deploy_token(&e, &token_admin);
+        let payer = Address::generate(&e);
+        let payee = Address::generate(&e);
+        let mut payers = SVec::new(&e); payers.push_back(payer.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut self_arb = SVec::new(&e); self_arb.push_back(payer.clone());
+        let init = |arbiters: SVec<Address>, payees: SVec<Address>, strict: bool| EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees, release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters, arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: strict, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional };
+
+        // flexible setups still allow a payer to arbitrate
+        client.create_escrow(&9u128, &init(self_arb.clone(), payees.clone(), false));
+        assert!(client.try_create_escrow(&10u128, &init(self_arb.clone(), payees.clone(), true)).is_err());
+
+        // emergency admin acting as payee is rejected in strict mode
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        let mut admin_payee = SVec::new(&e); admin_payee.push_back(eadmin.clone());
+        assert!(client.try_create_escrow(&10u128, &init(arbs.clone(), admin_payee, true)).is_err());
+        client.create_escrow(&10u128, &init(arbs.clone(), payees.clone(), true));
+
+        // arbiter cap
+        client.set_max_arbiters(&admin, &1u32);
+        let mut two = arbs.clone(); two.push_back(Address::generate(&e));
+        assert!(client.try_create_escrow(&11u128, &init(two, payees.clone(), false)).is_err());
+    }
+
+    #[test]
+    fn test_cancel_emergency_release() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (contract_id, client) = deploy_contract(&e);
+        let emergency1 = Address::generate(&e);
+        let emergency2 = Address::generate(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(emergency1.clone()); eadmins.push_back(emergency2.clone());
+        client.init(&admin, &0u32, &fee_collector, &eadmins, &2u32);
+
+        let token_admin = Address::generate(&e);
+        let token_addr = deploy_token(&e, &token_admin);
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer = Address::generate(&e);
+        let payee = Address::generate(&e);
+        token_client.mint(&payer, &1000);
+
+        let mut payers = SVec::new(&e); payers.push_back(payer.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        client.create_escrow(&12u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+        client.deposit(&12u128, &payer, &1000);
+
+        let mut dists = SVec::new(&e); dists.push_back((payee.clone(), 1000));
+        client.emergency_release(&12u128, &emergency1, &dists);
+        // one admin alone can't cancel under a threshold of 2, even by voting twice
+        client.cancel_emergency_release(&12u128, &emergency2);
+        client.cancel_emergency_release(&12u128, &emergency2);
+        e.as_contract(&contract_id, || assert!(e.storage().persistent().has(&DataKey::PendingEmergency(12))));
+        client.cancel_emergency_release(&12u128, &emergency1);
+        e.as_contract(&contract_id, || {
+            let store = e.storage().persistent();
+            assert!(!store.has(&DataKey::PendingEmergency(12)));
+            assert!(!store.has(&DataKey::ApprovalsEmergency(12)));
+            assert!(!store.has(&DataKey::CancelsEmergency(12)));
+        });
+        assert_eq!(client.get_escrow(&12u128).balance, 1000);
+        assert!(client.try_cancel_emergency_release(&12u128, &emergency1).is_err());
+    }
+
+    #[test]
+    fn test_active_count_and_total_locked() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &fee_collector, &eadmins, &1u32);
+
+        let token_admin = Address::generate(&e);
+        let token_addr = deploy_token(&e, &token_admin);
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer = Address::generate(&e);
+        let payee = Address::generate(&e);
+        token_client.mint(&payer, &1000);
+
+        let mut payers = SVec::new(&e); payers.push_back(payer.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        for id in [13u128, 14u128] {
+            client.create_escrow(&id, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+        }
+        client.deposit(&13u128, &payer, &600);
+        client.deposit(&14u128, &payer, &400);
+        assert_eq!(client.get_active_escrow_count(), 2);
+        assert_eq!(client.get_total_locked(&token_addr), 1000);
+
+        // release one escrow, refund the other after expiry
+        let mut dists = SVec::new(&e); dists.push_back((payee.clone(), 600));
+        client.propose_release(&13u128, &payer, &dists);
+        client.approve_release(&13u128, &payer);
+        assert_eq!(client.get_active_escrow_count(), 1);
+        assert_eq!(client.get_total_locked(&token_addr), 400);
+
+        e.ledger().with_mut(|li| li.timestamp = 6000);
+        client.refund_timeout(&14u128);
+        assert_eq!(client.get_active_escrow_count(), 0);
+        assert_eq!(client.get_total_locked(&token_addr), 0);
+    }
+
+    #[test]
+    fn test_fees_reported_to_marketplace() {
+        let e = Env::default();
+        // root auths only: the escrow reports as itself, not through a nested admin signature
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (escrow_id, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &250u32, &fee_collector, &eadmins, &1u32);
+        let mp_id = e.register_contract(None, MockMarketplace);
+        let mp = MockMarketplaceClient::new(&e, &mp_id);
+        client.set_fee_marketplace(&admin, &Some(mp_id.clone()));
+
+        let token_admin = Address::generate(&e);
+        let token_addr = deploy_token(&e, &token_admin);
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer = Address::generate(&e);
+        let payee = Address::generate(&e);
+        token_client.mint(&payer, &1000);
+
+        let mut payers = SVec::new(&e); payers.push_back(payer.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        client.create_escrow(&15u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+        client.deposit(&15u128, &payer, &1000);
+
+        let mut dists = SVec::new(&e); dists.push_back((payee.clone(), 1000));
+        client.propose_release(&15u128, &payer, &dists);
+        client.approve_release(&15u128, &payer);
+
+        // 2.5% of 1000 goes to the collector and is recorded on the marketplace
+        assert_eq!(token_client.balance(&fee_collector), 25);
+        assert_eq!(mp.get_total_fees_for_asset(&token_addr), 25);
+        assert_eq!(mp.get_reporter(), Some(escrow_id));
+    }
+
+    #[test]
+    fn test_refund_recipient_override() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &fee_collector, &eadmins, &1u32);
+
+        let token_admin = Address::generate(&e);
+        let token_addr = deploy_token(&e, &token_admin);
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer1 = Address::generate(&e);
+        let payer2 = Address::generate(&e);
+        let new_wallet = Address::generate(&e);
+        let payee = Address::generate(&e);
+        token_client.mint(&payer1, &700);
+        token_client.mint(&payer2, &300);
+
+        let mut payers = SVec::new(&e); payers.push_back(payer1.clone()); payers.push_back(payer2.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        client.create_escrow(&16u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+        client.deposit(&16u128, &payer1, &700);
+        client.deposit(&16u128, &payer2, &300);
+        client.set_refund_recipient(&16u128, &payer1, &new_wallet);
+        assert!(client.try_set_refund_recipient(&16u128, &payee, &new_wallet).is_err());
+
+        e.ledger().with_mut(|li| li.timestamp = 6000);
+        client.refund_timeout(&16u128);
+        assert_eq!(token_client.balance(&new_wallet), 700);
+        assert_eq!(token_client.balance(&payer1), 0);
+        assert_eq!(token_client.balance(&payer2), 300);
+    }
+
+    #[test]
+    fn test_escrows_by_role() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &fee_collector, &eadmins, &1u32);
+
+        let token_admin = Address::generate(&e);
+        let token_addr = deploy_token(&e, &token_admin);
+        let alice = Address::generate(&e);
+        let other = Address::generate(&e);
+        let payee = Address::generate(&e);
+        let mut payee_list = SVec::new(&e); payee_list.push_back(payee.clone());
+        let mut alice_list = SVec::new(&e); alice_list.push_back(alice.clone());
+        let mut other_list = SVec::new(&e); other_list.push_back(other.clone());
+        let init = |payers: SVec<Address>, arbiters: SVec<Address>| EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payee_list.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters, arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional };
+
+        // alice pays into 17 and arbitrates 18
+        client.create_escrow(&17u128, &init(alice_list.clone(), other_list.clone()));
+        client.create_escrow(&18u128, &init(other_list.clone(), alice_list.clone()));
+
+        let mut only17 = SVec::new(&e); only17.push_back(17u128);
+        let mut only18 = SVec::new(&e); only18.push_back(18u128);
+        assert_eq!(client.get_escrows_by_role(&alice, &ROLE_PAYER, &0, &10), only17);
+        assert_eq!(client.get_escrows_by_role(&alice, &ROLE_SIGNER, &0, &10), only17);
+        assert_eq!(client.get_escrows_by_role(&alice, &ROLE_ARBITER, &0, &10), only18);
+        assert_eq!(client.get_escrows_by_role(&alice, &ROLE_PAYEE, &0, &10).len(), 0);
+        assert_eq!(client.get_escrows_by_role(&payee, &ROLE_PAYEE, &0, &10).len(), 2);
+        assert_eq!(client.get_escrows_by_role(&payee, &ROLE_PAYEE, &1, &10), only18);
+        assert!(client.try_get_escrows_by_role(&alice, &7, &0, &10).is_err());
+    }
+
+    #[test]
+    fn test_release_keep_open() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &fee_collector, &eadmins, &1u32);
+
+        let token_admin = Address::generate(&e);
+        let token_addr = deploy_token(&e, &token_admin);
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer = Address::generate(&e);
+        let payee = Address::generate(&e);
+        token_client.mint(&payer, &1000);
+
+        let mut payers = SVec::new(&e); payers.push_back(payer.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        client.create_escrow(&19u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+
+        // first tranche drains the escrow but keeps it open
+        client.deposit(&19u128, &payer, &400);
+        let mut dists = SVec::new(&e); dists.push_back((payee.clone(), 400));
+        client.propose_release_token(&19u128, &payer, &token_addr, &dists, &true);
+        client.approve_release(&19u128, &payer);
+        let esc = client.get_escrow(&19u128);
+        assert_eq!(esc.balance, 0);
+        assert!(!esc.closed);
+
+        // second tranche uses the default auto-close
+        client.deposit(&19u128, &payer, &600);
+        let mut dists = SVec::new(&e); dists.push_back((payee.clone(), 600));
+        client.propose_release(&19u128, &payer, &dists);
+        client.approve_release(&19u128, &payer);
+        assert_eq!(token_client.balance(&payee), 1000);
+        assert!(client.get_escrow(&19u128).closed);
+    }
+
+    #[test]
+    fn test_auto_release_waits_for_delivery_confirmation() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &fee_collector, &eadmins, &1u32);
+
+        let token_admin = Address::generate(&e);
+        let token_addr = deploy_token(&e, &token_admin);
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer = Address::generate(&e);
+        let payee1 = Address::generate(&e);
+        let payee2 = Address::generate(&e);
+        token_client.mint(&payer, &1000);
+
+        let mut payers = SVec::new(&e); payers.push_back(payer.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee1.clone()); payees.push_back(payee2.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        client.create_escrow(&20u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: Some(2000), expiry_ts: 9000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: Some(3000), min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+        client.deposit(&20u128, &payer, &1000);
+
+        e.ledger().with_mut(|li| li.timestamp = 2500);
+        assert!(client.try_auto_release(&20u128).is_err());
+        client.confirm_delivery(&20u128, &payee1);
+        assert!(client.try_auto_release(&20u128).is_err());
+        client.confirm_delivery(&20u128, &payee2);
+        client.auto_release(&20u128);
+        assert_eq!(token_client.balance(&payee1), 500);
+        assert_eq!(token_client.balance(&payee2), 500);
+        assert!(client.get_escrow(&20u128).closed);
+    }
+
+    #[test]
+    fn test_invalid_leg_rejected_before_transfers() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &fee_collector, &eadmins, &1u32);
+
+        let token_admin = Address::generate(&e);
+        let token_addr = deploy_token(&e, &token_admin);
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer = Address::generate(&e);
+        let payee1 = Address::generate(&e);
+        let payee2 = Address::generate(&e);
+        token_client.mint(&payer, &1000);
+
+        let mut payers = SVec::new(&e); payers.push_back(payer.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee1.clone()); payees.push_back(payee2.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        client.create_escrow(&21u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+        client.deposit(&21u128, &payer, &1000);
+
+        // the first leg alone is valid, the second would fail mid-loop
+        let mut dists = SVec::new(&e); dists.push_back((payee1.clone(), 600)); dists.push_back((payee2.clone(), -100));
+        assert!(client.try_propose_release(&21u128, &payer, &dists).is_err());
+
+        assert_eq!(token_client.balance(&payee1), 0);
+        let esc = client.get_escrow(&21u128);
+        assert_eq!(esc.balance, 1000);
+        assert!(!esc.closed);
+        assert_eq!(client.get_total_locked(&token_addr), 1000);
+    }
+
+    #[test]
+    fn test_release_and_settle() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &fee_collector, &eadmins, &1u32);
+
+        let token_admin = Address::generate(&e);
+        let token_addr = deploy_token(&e, &token_admin);
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer1 = Address::generate(&e);
+        let payer2 = Address::generate(&e);
+        let payee = Address::generate(&e);
+        token_client.mint(&payer1, &750);
+        token_client.mint(&payer2, &250);
+
+        let mut payers = SVec::new(&e); payers.push_back(payer1.clone()); payers.push_back(payer2.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        client.create_escrow(&22u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 2, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+        client.deposit(&22u128, &payer1, &750);
+        client.deposit(&22u128, &payer2, &250);
+
+        let mut dists = SVec::new(&e); dists.push_back((payee.clone(), 400));
+        client.release_and_settle(&22u128, &payer1, &dists);
+        client.approve_release(&22u128, &payer2);
+
+        // 600 left over goes back 75/25
+        assert_eq!(token_client.balance(&payee), 400);
+        assert_eq!(token_client.balance(&payer1), 450);
+        assert_eq!(token_client.balance(&payer2), 150);
+        let esc = client.get_escrow(&22u128);
+        assert_eq!(esc.balance, 0);
+        assert!(esc.closed);
+    }
+
+    #[test]
+    fn test_min_deposit_per_payer() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &fee_collector, &eadmins, &1u32);
+
+        let token_admin = Address::generate(&e);
+        let token_addr = deploy_token(&e, &token_admin);
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer = Address::generate(&e);
+        token_client.mint(&payer, &1000);
+
+        let mut payers = SVec::new(&e); payers.push_back(payer.clone());
+        let mut payees = SVec::new(&e); payees.push_back(Address::generate(&e));
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        client.create_escrow(&23u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: Some(100), min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+
+        assert!(client.try_deposit(&23u128, &payer, &99).is_err());
+        assert_eq!(token_client.balance(&payer), 1000);
+
+        client.deposit(&23u128, &payer, &100);
+        // top-ups after the first deposit may be smaller
+        client.deposit(&23u128, &payer, &5);
+        assert_eq!(client.get_escrow(&23u128).balance, 105);
+    }
+
+    #[test]
+    fn test_min_payee_tier_blocks_release() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &fee_collector, &eadmins, &1u32);
+        let rep_id = e.register_contract(None, MockReputation);
+        let rep = MockReputationClient::new(&e, &rep_id);
+
+        let token_admin = Address::generate(&e);
+        let token_addr = deploy_token(&e, &token_admin);
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer = Address::generate(&e);
+        let payee = Address::generate(&e);
+        token_client.mint(&payer, &1000);
+
+        let mut payers = SVec::new(&e); payers.push_back(payer.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        let init = EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: Some(4), arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional };
+        // a tier requirement needs a reputation contract to check against
+        assert!(client.try_create_escrow(&24u128, &init).is_err());
+        client.set_reputation_contract(&admin, &Some(rep_id.clone()));
+        client.create_escrow(&24u128, &init);
+        client.deposit(&24u128, &payer, &1000);
+
+        rep.set_score(&payee, &2);
+        let mut dists = SVec::new(&e); dists.push_back((payee.clone(), 600));
+        client.propose_release(&24u128, &payer, &dists);
+        assert!(client.try_approve_release(&24u128, &payer).is_err());
+        assert_eq!(token_client.balance(&payee), 0);
+
+        rep.set_score(&payee, &4);
+        client.approve_release(&24u128, &payer);
+        assert_eq!(token_client.balance(&payee), 600);
+
+        // admin override lets a low-tier payee be paid
+        rep.set_score(&payee, &1);
+        client.waive_payee_tier(&admin, &24u128);
+        let mut rest = SVec::new(&e); rest.push_back((payee.clone(), 400));
+        client.propose_release(&24u128, &payer, &rest);
+        client.approve_release(&24u128, &payer);
+        assert_eq!(token_client.balance(&payee), 1000);
+    }
+
+    #[test]
+    fn test_stalled_dispute_escalates_to_emergency_admins() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let emergency = Address::generate(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(emergency.clone());
+        client.init(&admin, &0u32, &fee_collector, &eadmins, &1u32);
+
+        let token_admin = Address::generate(&e);
+        let token_addr = deploy_token(&e, &token_admin);
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer = Address::generate(&e);
+        let payee = Address::generate(&e);
+        let arbiter = Address::generate(&e);
+        token_client.mint(&payer, &1000);
+
+        let mut payers = SVec::new(&e); payers.push_back(payer.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(arbiter.clone());
+        client.create_escrow(&25u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 9000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: Some(2000), release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+        client.deposit(&25u128, &payer, &1000);
+        client.open_dispute(&25u128, &payee);
+
+        let mut dists = SVec::new(&e); dists.push_back((payee.clone(), 1000));
+        // arbiters still own the dispute before the deadline
+        assert!(client.try_emergency_release(&25u128, &emergency, &dists).is_err());
+        assert!(client.try_escalate_dispute(&25u128, &payee).is_err());
+
+        e.ledger().set_timestamp(2000);
+        client.escalate_dispute(&25u128, &payee);
+        assert!(client.get_escrow(&25u128).escalated);
+        assert!(client.try_arbiter_release(&25u128, &arbiter, &dists).is_err());
+
+        client.emergency_release(&25u128, &emergency, &dists);
+        assert_eq!(token_client.balance(&payee), 1000);
+        let esc = client.get_escrow(&25u128);
+        assert!(esc.closed);
+        assert!(!esc.disputed);
+        assert!(!esc.escalated);
+    }
+
+    #[test]
+    fn test_escrow_timers() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &Address::generate(&e), &eadmins, &1u32);
+
+        let token_addr = deploy_token(&e, &Address::generate(&e));
+        let mut payers = SVec::new(&e); payers.push_back(Address::generate(&e));
+        let mut payees = SVec::new(&e); payees.push_back(Address::generate(&e));
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        let init = |auto_release_ts: Option<u64>| EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts, expiry_ts: 3000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional };
+        client.create_escrow(&26u128, &init(Some(1500)));
+        client.create_escrow(&27u128, &init(None));
+
+        assert_eq!(client.get_escrow_timers(&26u128), (Some(500), 2000));
+        assert_eq!(client.get_escrow_timers(&27u128), (None, 2000));
+
+        // elapsed timers saturate at zero
+        e.ledger().set_timestamp(2000);
+        assert_eq!(client.get_escrow_timers(&26u128), (Some(0), 1000));
+        e.ledger().set_timestamp(4000);
+        assert_eq!(client.get_escrow_timers(&26u128), (Some(0), 0));
+    }
+
+    #[test]
+    fn test_bump_ttl_keeps_escrows_alive() {
+        use soroban_sdk::testutils::storage::Persistent as _;
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let (contract_id, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &Address::generate(&e), &eadmins, &1u32);
+
+        let token_addr = deploy_token(&e, &Address::generate(&e));
+        let mut payers = SVec::new(&e); payers.push_back(Address::generate(&e));
+        let mut payees = SVec::new(&e); payees.push_back(Address::generate(&e));
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        let init = EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 3000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional };
+        client.create_escrow(&28u128, &init);
+        client.create_escrow(&29u128, &init);
+        client.create_escrow(&30u128, &init);
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        token_client.mint(&payers.get_unchecked(0), &500);
+        client.deposit(&28u128, &payers.get_unchecked(0), &500);
+        let mut dists = SVec::new(&e); dists.push_back((payees.get_unchecked(0), 500));
+        client.propose_release(&28u128, &payers.get_unchecked(0), &dists);
+
+        // unknown ids are skipped
+        let mut ids = SVec::new(&e); ids.push_back(28u128); ids.push_back(29u128); ids.push_back(99u128);
+        assert_eq!(client.bump_ttl(&ids), 2);
+        let ttl = |id: u128| e.as_contract(&contract_id, || e.storage().persistent().get_ttl(&DataKey::Escrow(id)));
+        assert_eq!(ttl(28), BUMP_TTL);
+        assert!(ttl(30) < 100_000);
+        // config and the escrow's pending proposal, approvals, deposits and indexes are kept alive with it
+        let key_ttl = |key: DataKey| e.as_contract(&contract_id, || e.storage().persistent().get_ttl(&key));
+        for key in [DataKey::Admin, DataKey::FeeBps, DataKey::FeeCollector, DataKey::EmergencyAdmins, DataKey::EmergencyThreshold,
+                    DataKey::PendingRelease(28), DataKey::ApprovalsRelease(28), DataKey::DepositLog(28), DataKey::TotalLocked(token_addr.clone()),
+                    DataKey::RoleEscrows(payers.get_unchecked(0), ROLE_PAYER), DataKey::RoleEscrows(arbs.get_unchecked(0), ROLE_ARBITER)] {
+            assert_eq!(key_ttl(key), BUMP_TTL);
+        }
+
+        // well past the default entry lifetime only the bumped escrows are still live
+        e.ledger().with_mut(|li| li.sequence_number += 100_000);
+        assert_eq!(ttl(29), BUMP_TTL - 100_000);
+        assert_eq!(client.get_escrow(&28u128).expiry_ts, 3000);
+        assert_eq!(client.get_escrow(&29u128).expiry_ts, 3000);
+
+        let mut too_many = SVec::new(&e);
+        for id in 0..=MAX_BUMP_BATCH as u128 { too_many.push_back(id); }
+        assert!(client.try_bump_ttl(&too_many).is_err());
+    }
+
+    #[test]
+    fn test_release_blocked_below_min_escrow_amount() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &Address::generate(&e), &eadmins, &1u32);
+        client.set_min_escrow_amount(&admin, &500);
+        assert_eq!(client.get_min_escrow_amount(), 500);
+        assert!(client.try_set_min_escrow_amount(&Address::generate(&e), &0).is_err());
+
+        let token_addr = deploy_token(&e, &Address::generate(&e));
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer = Address::generate(&e);
+        let payee = Address::generate(&e);
+        token_client.mint(&payer, &1_000);
+        let mut payers = SVec::new(&e); payers.push_back(payer.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        client.create_escrow(&31u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 3000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+
+        client.deposit(&31u128, &payer, &300);
+        let mut dists = SVec::new(&e); dists.push_back((payee.clone(), 300));
+        assert!(client.try_propose_release(&31u128, &payer, &dists).is_err());
+        assert!(client.try_release_and_settle(&31u128, &payer, &dists).is_err());
+
+        // topping up past the minimum unlocks the release
+        client.deposit(&31u128, &payer, &200);
+        client.propose_release(&31u128, &payer, &dists);
+        client.approve_release(&31u128, &payer);
+        assert_eq!(token_client.balance(&payee), 300);
+    }
+
+    #[test]
+    fn test_fee_exempt_escrow_pays_in_full() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &250u32, &fee_collector, &eadmins, &1u32);
+
+        let token_addr = deploy_token(&e, &Address::generate(&e));
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer = Address::generate(&e);
+        let payee1 = Address::generate(&e);
+        let payee2 = Address::generate(&e);
+        token_client.mint(&payer, &1_000);
+        let mut payers = SVec::new(&e); payers.push_back(payer.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee1.clone()); payees.push_back(payee2.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        client.create_escrow(&32u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 3000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+        assert!(!client.get_escrow(&32u128).fee_exempt);
+
+        // only the admin can grant the exemption
+        assert!(client.try_set_fee_exempt(&payer, &32u128, &true).is_err());
+        client.set_fee_exempt(&admin, &32u128, &true);
+        assert!(client.get_escrow(&32u128).fee_exempt);
+
+        client.deposit(&32u128, &payer, &1_000);
+        let mut dists = SVec::new(&e); dists.push_back((payee1.clone(), 600)); dists.push_back((payee2.clone(), 400));
+        client.propose_release(&32u128, &payer, &dists);
+        client.approve_release(&32u128, &payer);
+
+        assert_eq!(token_client.balance(&payee1), 600);
+        assert_eq!(token_client.balance(&payee2), 400);
+        assert_eq!(token_client.balance(&fee_collector), 0);
+    }
+
+    #[test]
+    fn test_deposit_for_credits_payer() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &Address::generate(&e), &eadmins, &1u32);
+
+        let token_addr = deploy_token(&e, &Address::generate(&e));
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let employer = Address::generate(&e);
+        let employee = Address::generate(&e);
+        token_client.mint(&employer, &1_000);
+        let mut payers = SVec::new(&e); payers.push_back(employee.clone());
+        let mut payees = SVec::new(&e); payees.push_back(Address::generate(&e));
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        client.create_escrow(&33u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 3000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+
+        // the beneficiary must be a payer
+        assert!(client.try_deposit_for(&33u128, &employer, &employer, &100).is_err());
+
+        client.deposit_for(&33u128, &employer, &employee, &400);
+        assert_eq!(token_client.balance(&employer), 600);
+        let esc = client.get_escrow(&33u128);
+        assert_eq!(esc.balance, 400);
+        assert_eq!(esc.deposits.get_unchecked(0).unwrap(), (employee.clone(), 400));
+
+        // refunds follow the credited payer, not the funder
+        e.ledger().set_timestamp(3000);
+        client.refund_timeout(&33u128);
+        assert_eq!(token_client.balance(&employee), 400);
+        assert_eq!(token_client.balance(&employer), 600);
+    }
+
+    #[test]
+    fn test_weighted_release_signers() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &Address::generate(&e), &eadmins, &1u32);
+
+        let token_addr = deploy_token(&e, &Address::generate(&e));
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let lead = Address::generate(&e);
+        let member = Address::generate(&e);
+        let payee = Address::generate(&e);
+        token_client.mint(&lead, &1_000);
+        let mut payers = SVec::new(&e); payers.push_back(lead.clone()); payers.push_back(member.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        let init = |release_threshold: u32, release_weights: Option<SMap<Address, u32>>| EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional };
+
+        // weights must be positive, belong to release signers, and cover the threshold
+        let mut zero = SMap::new(&e); zero.set(lead.clone(), 0u32);
+        assert!(client.try_create_escrow(&34u128, &init(1, Some(zero))).is_err());
+        let mut outsider = SMap::new(&e); outsider.set(payee.clone(), 2u32);
+        assert!(client.try_create_escrow(&34u128, &init(1, Some(outsider))).is_err());
+        let mut weights = SMap::new(&e); weights.set(lead.clone(), 3u32);
+        assert!(client.try_create_escrow(&34u128, &init(5, Some(weights.clone()))).is_err());
+        // without weights the threshold is still a head count
+        assert!(client.try_create_escrow(&34u128, &init(3, None)).is_err());
+
+        client.create_escrow(&34u128, &init(3, Some(weights)));
+        client.deposit(&34u128, &lead, &1_000);
+        let mut dists = SVec::new(&e); dists.push_back((payee.clone(), 1_000));
+        client.propose_release(&34u128, &member, &dists);
+
+        // lead's weight of 3 meets the threshold without any other approval
+        client.approve_release(&34u128, &lead);
+        assert_eq!(token_client.balance(&payee), 1_000);
+        assert!(client.get_escrow(&34u128).closed);
+    }
+
+    #[test]
+    fn test_arbiter_cosign_gates_release() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &Address::generate(&e), &eadmins, &1u32);
+
+        let token_addr = deploy_token(&e, &Address::generate(&e));
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer = Address::generate(&e);
+        let payee = Address::generate(&e);
+        let arbiter = Address::generate(&e);
+        token_client.mint(&payer, &1_000);
+        let mut payers = SVec::new(&e); payers.push_back(payer.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(arbiter.clone());
+        client.create_escrow(&35u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: true, refund_mode: RefundMode::Proportional });
+        client.deposit(&35u128, &payer, &1_000);
+
+        // the signer threshold is met but nothing moves without the arbiter
+        let mut dists = SVec::new(&e); dists.push_back((payee.clone(), 1_000));
+        client.propose_release(&35u128, &payer, &dists);
+        client.approve_release(&35u128, &payer);
+        assert_eq!(token_client.balance(&payee), 0);
+
+        assert!(client.try_cosign_release(&35u128, &payer).is_err());
+        client.cosign_release(&35u128, &arbiter);
+        assert_eq!(token_client.balance(&payee), 1_000);
+        assert!(client.get_escrow(&35u128).closed);
+    }
+
+    #[test]
+    fn test_lifecycle_events() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let (contract_id, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &Address::generate(&e), &eadmins, &1u32);
+        // token transfers emit their own events; keep only the escrow's
+        let events = || e.events().all().filter_by_contract(&contract_id);
+
+        let token_addr = deploy_token(&e, &Address::generate(&e));
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer = Address::generate(&e);
+        let payee = Address::generate(&e);
+        token_client.mint(&payer, &1_000);
+        let mut payers = SVec::new(&e); payers.push_back(payer.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        let init = EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 3000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional };
+
+        client.create_escrow(&36u128, &init);
+        assert_eq!(events(), [EscrowCreatedEventData { id: 36, token: token_addr.clone(), payers: payers.clone(), payees: payees.clone() }.to_xdr(&e, &contract_id)]);
+
+        client.deposit(&36u128, &payer, &1_000);
+        assert_eq!(events(), [DepositedEventData { id: 36, from: payer.clone(), token: token_addr.clone(), amount: 1_000 }.to_xdr(&e, &contract_id)]);
+
+        // a partial release leaves the escrow open
+        let mut dists = SVec::new(&e); dists.push_back((payee.clone(), 600));
+        client.propose_release(&36u128, &payer, &dists);
+        assert_eq!(events(), [ReleaseProposedEventData { id: 36, signer: payer.clone(), nonce: 1, token: token_addr.clone(), total: 600 }.to_xdr(&e, &contract_id)]);
+        client.approve_release(&36u128, &payer);
+        assert_eq!(events(), [ReleaseExecutedEventData { id: 36, nonce: 1, token: token_addr.clone(), total: 600, fee: 0 }.to_xdr(&e, &contract_id)]);
+
+        client.open_dispute(&36u128, &payee);
+        assert_eq!(events(), [DisputeOpenedEventData { id: 36, actor: payee.clone() }.to_xdr(&e, &contract_id)]);
+
+        // the arbiter refunds the rest, which drains and closes the escrow
+        let mut refund = SVec::new(&e); refund.push_back((payer.clone(), 400));
+        client.arbiter_refund(&36u128, &arbs.get_unchecked(0), &refund);
+        assert_eq!(events(), [
+            RefundExecutedEventData { id: 36, token: token_addr.clone(), amount: 400 }.to_xdr(&e, &contract_id),
+            EscrowClosedEventData { id: 36 }.to_xdr(&e, &contract_id),
+        ]);
+        assert_eq!(token_client.balance(&payer), 400);
+    }
+
+    #[test]
+    fn test_release_and_refund_proposals_stay_separate() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let (contract_id, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &Address::generate(&e), &eadmins, &1u32);
+
+        let token_addr = deploy_token(&e, &Address::generate(&e));
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer1 = Address::generate(&e);
+        let payer2 = Address::generate(&e);
+        let payee = Address::generate(&e);
+        token_client.mint(&payer1, &1_000);
+        let mut payers = SVec::new(&e); payers.push_back(payer1.clone()); payers.push_back(payer2.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        client.create_escrow(&37u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 2, refund_signers: payers.clone(), refund_threshold: 2, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+        client.deposit(&37u128, &payer1, &1_000);
+
+        // while the release is pending no other proposal can replace it
+        let mut dists = SVec::new(&e); dists.push_back((payee.clone(), 600));
+        let mut refund = SVec::new(&e); refund.push_back((payer1.clone(), 1_000));
+        client.propose_release(&37u128, &payer1, &dists);
+        assert!(client.try_propose_refund(&37u128, &payer2, &refund).is_err());
+        assert!(client.try_propose_release(&37u128, &payer2, &refund).is_err());
+        assert!(client.try_approve_refund(&37u128, &payer2).is_err());
+
+        // the release pays exactly what was proposed
+        client.approve_release(&37u128, &payer2);
+        assert_eq!(token_client.balance(&payee), 600);
+
+        let mut refund = SVec::new(&e); refund.push_back((payer1.clone(), 400));
+        client.propose_refund(&37u128, &payer1, &refund);
+        assert!(client.try_propose_release(&37u128, &payer1, &dists).is_err());
+        e.as_contract(&contract_id, || {
+            assert!(e.storage().persistent().has(&DataKey::PendingRefund(37)));
+            assert!(!e.storage().persistent().has(&DataKey::PendingRelease(37)));
+        });
+
+        // a cancelled refund frees the slot without moving funds
+        assert!(client.try_cancel_refund_proposal(&37u128, &payee).is_err());
+        client.cancel_refund_proposal(&37u128, &payer2);
+        assert!(client.try_approve_refund(&37u128, &payer2).is_err());
+        assert_eq!(client.get_escrow(&37u128).balance, 400);
+
+        client.propose_refund(&37u128, &payer1, &refund);
+        client.approve_refund(&37u128, &payer2);
+        assert_eq!(token_client.balance(&payer1), 400);
+        assert_eq!(token_client.balance(&payee), 600);
+        assert!(client.get_escrow(&37u128).closed);
+    }
+
+    #[test]
+    fn test_dispute_fee_on_arbiter_resolution() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &100u32, &fee_collector, &eadmins, &1u32);
+
+        let token_addr = deploy_token(&e, &Address::generate(&e));
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer = Address::generate(&e);
+        let payee = Address::generate(&e);
+        let arbiter = Address::generate(&e);
+        token_client.mint(&payer, &3_000);
+        let mut payers = SVec::new(&e); payers.push_back(payer.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(arbiter.clone());
+        let init = EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional };
+        for id in [38u128, 39, 40] { client.create_escrow(&id, &init); client.deposit(&id, &payer, &1_000); }
+
+        assert!(client.try_set_dispute_fee_bps(&admin, &Some(10_001)).is_err());
+        assert!(client.try_set_dispute_fee_bps(&payer, &Some(500)).is_err());
+        client.set_dispute_fee_bps(&admin, &Some(500));
+        assert_eq!(client.get_dispute_fee_bps(), Some(500));
+
+        // a normal release pays the 1% fee
+        let mut dists = SVec::new(&e); dists.push_back((payee.clone(), 1_000));
+        client.propose_release(&38u128, &payer, &dists);
+        client.approve_release(&38u128, &payer);
+        assert_eq!(token_client.balance(&fee_collector), 10);
+        assert_eq!(token_client.balance(&payee), 990);
+
+        // an arbiter-resolved release pays the 5% dispute fee
+        client.open_dispute(&39u128, &payer);
+        client.arbiter_release(&39u128, &arbiter, &dists);
+        assert_eq!(token_client.balance(&fee_collector), 60);
+        assert_eq!(token_client.balance(&payee), 1_940);
+
+        // so does an arbiter refund
+        client.open_dispute(&40u128, &payee);
+        let mut refund = SVec::new(&e); refund.push_back((payer.clone(), 1_000));
+        client.arbiter_refund(&40u128, &arbiter, &refund);
+        assert_eq!(token_client.balance(&fee_collector), 110);
+        assert_eq!(token_client.balance(&payer), 950);
+        assert!(client.get_escrow(&40u128).closed);
+    }
+
+    #[test]
+    fn test_auto_release_leaves_no_approvals() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (contract_id, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &fee_collector, &eadmins, &1u32);
+
+        let token_admin = Address::generate(&e);
+        let token_addr = deploy_token(&e, &token_admin);
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+
+        let payer1 = Address::generate(&e);
+        let payer2 = Address::generate(&e);
+        let payee1 = Address::generate(&e);
+        let payee2 = Address::generate(&e);
+        token_client.mint(&payer1, &2001);
+
+        let mut payers = SVec::new(&e); payers.push_back(payer1.clone()); payers.push_back(payer2.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee1.clone()); payees.push_back(payee2.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        client.create_escrow(&41u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 2, refund_signers: payers.clone(), refund_threshold: 2, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 9000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+        client.create_escrow(&42u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 2, refund_signers: payers.clone(), refund_threshold: 2, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: Some(2000), expiry_ts: 9000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+        client.deposit(&41u128, &payer1, &1000);
+        client.deposit(&42u128, &payer1, &1001);
+
+        // multi-sig release on 41
+        let mut dists = SVec::new(&e); dists.push_back((payee1.clone(), 1000));
+        client.propose_release(&41u128, &payer1, &dists);
+        client.approve_release(&41u128, &payer2);
+
+        // timeout release on 42 splits equally, odd unit to the first payee
+        e.ledger().set_timestamp(2000);
+        client.auto_release(&42u128);
+
+        assert_eq!(token_client.balance(&payee1), 1501);
+        assert_eq!(token_client.balance(&payee2), 500);
+        assert!(client.get_escrow(&41u128).closed);
+        assert!(client.get_escrow(&42u128).closed);
+        e.as_contract(&contract_id, || {
+            let store = e.storage().persistent();
+            for id in [41u128, 42u128] {
+                assert!(!store.has(&DataKey::PendingRelease(id)));
+                assert!(!store.has(&DataKey::ApprovalsRelease(id)));
+            }
+        });
+    }
+
+    #[test]
+    fn test_revoke_approvals() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let fee_collector = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &fee_collector, &eadmins, &1u32);
+
+        let token_admin = Address::generate(&e);
+        let token_addr = deploy_token(&e, &token_admin);
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+
+        let payer1 = Address::generate(&e);
+        let payer2 = Address::generate(&e);
+        let payer3 = Address::generate(&e);
+        let payee = Address::generate(&e);
+        token_client.mint(&payer1, &2000);
+
+        let mut payers = SVec::new(&e); payers.push_back(payer1.clone()); payers.push_back(payer2.clone()); payers.push_back(payer3.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        client.create_escrow(&43u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 3, refund_signers: payers.clone(), refund_threshold: 3, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+        client.create_escrow(&44u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 3, refund_signers: payers.clone(), refund_threshold: 3, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+        client.deposit(&43u128, &payer1, &1000);
+        client.deposit(&44u128, &payer1, &1000);
+
+        // release: payer2 backs out, so payer3's approval leaves it one short
+        let mut dists = SVec::new(&e); dists.push_back((payee.clone(), 400));
+        client.propose_release(&43u128, &payer1, &dists);
+        client.approve_release(&43u128, &payer2);
+        client.revoke_release_approval(&43u128, &payer2);
+        client.approve_release(&43u128, &payer3);
+        assert_eq!(token_client.balance(&payee), 0);
+        client.approve_release(&43u128, &payer2);
+        assert_eq!(token_client.balance(&payee), 400);
+        // nothing left to revoke once executed
+        assert!(client.try_revoke_release_approval(&43u128, &payer2).is_err());
+
+        // refund: same story
+        let mut refund = SVec::new(&e); refund.push_back((payer1.clone(), 300));
+        client.propose_refund(&44u128, &payer1, &refund);
+        client.approve_refund(&44u128, &payer2);
+        client.revoke_refund_approval(&44u128, &payer2);
+        client.approve_refund(&44u128, &payer3);
+        assert_eq!(token_client.balance(&payer1), 0);
+        client.approve_refund(&44u128, &payer2);
+        assert_eq!(token_client.balance(&payer1), 300);
+        assert!(client.try_revoke_refund_approval(&44u128, &payer2).is_err());
+    }
+
+    #[test]
+    fn test_cancel_and_repropose() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &Address::generate(&e), &eadmins, &1u32);
+
+        let token_addr = deploy_token(&e, &Address::generate(&e));
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer1 = Address::generate(&e);
+        let payer2 = Address::generate(&e);
+        let payee1 = Address::generate(&e);
+        let payee2 = Address::generate(&e);
+        let arb1 = Address::generate(&e);
+        let arb2 = Address::generate(&e);
+        token_client.mint(&payer1, &2_000);
+        let mut payers = SVec::new(&e); payers.push_back(payer1.clone()); payers.push_back(payer2.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee1.clone()); payees.push_back(payee2.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(arb1.clone()); arbs.push_back(arb2.clone());
+        client.create_escrow(&45u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 2, refund_signers: payers.clone(), refund_threshold: 2, arbiters: arbs.clone(), arbiter_threshold: 2, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+        client.create_escrow(&46u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 2, refund_signers: payers.clone(), refund_threshold: 2, arbiters: arbs.clone(), arbiter_threshold: 2, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+        client.deposit(&45u128, &payer1, &1_000);
+        client.deposit(&46u128, &payer1, &1_000);
+
+        let mut wrong = SVec::new(&e); wrong.push_back((payee1.clone(), 1_000));
+        let mut right = SVec::new(&e); right.push_back((payee2.clone(), 1_000));
+
+        // a stuck release is cancelled and replaced
+        client.propose_release(&45u128, &payer1, &wrong);
+        assert!(client.try_cancel_release_proposal(&45u128, &payee1).is_err());
+        client.cancel_release_proposal(&45u128, &payer2);
+        assert!(client.try_approve_release(&45u128, &payer2).is_err());
+        client.propose_release(&45u128, &payer1, &right);
+        client.approve_release(&45u128, &payer2);
+        assert_eq!(token_client.balance(&payee2), 1_000);
+
+        // arbiters drop a wrong vote and agree on a different split
+        client.open_dispute(&46u128, &payer1);
+        client.arbiter_release(&46u128, &arb1, &wrong);
+        assert!(client.try_cancel_release_proposal(&46u128, &payer1).is_err());
+        assert!(client.try_cancel_arbiter_proposal(&46u128, &payer1).is_err());
+        client.cancel_arbiter_proposal(&46u128, &arb2);
+        client.arbiter_release(&46u128, &arb2, &right);
+        assert_eq!(token_client.balance(&payee2), 1_000);
+        client.arbiter_release(&46u128, &arb1, &right);
+        assert_eq!(token_client.balance(&payee1), 0);
+        assert_eq!(token_client.balance(&payee2), 2_000);
+        assert!(client.get_escrow(&46u128).closed);
+    }
+
+    #[test]
+    fn test_refund_timeout_modes() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(Address::generate(&e));
+        client.init(&admin, &0u32, &Address::generate(&e), &eadmins, &1u32);
+
+        let token_addr = deploy_token(&e, &Address::generate(&e));
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer1 = Address::generate(&e);
+        let payer2 = Address::generate(&e);
+        let payee = Address::generate(&e);
+        token_client.mint(&payer1, &600);
+        token_client.mint(&payer2, &200);
+        let mut payers = SVec::new(&e); payers.push_back(payer1.clone()); payers.push_back(payer2.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(Address::generate(&e));
+        client.create_escrow(&47u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 2000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+        client.create_escrow(&48u128, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: None, expiry_ts: 2000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Equal });
+
+        // 300/100 deposits on each, half paid out before expiry
+        let mut dists = SVec::new(&e); dists.push_back((payee.clone(), 200));
+        for id in [47u128, 48u128] {
+            client.deposit(&id, &payer1, &300);
+            client.deposit(&id, &payer2, &100);
+            client.propose_release(&id, &payer1, &dists);
+            client.approve_release(&id, &payer2);
+        }
+        assert_eq!(client.get_escrow(&47u128).balance, 200);
+
+        e.ledger().set_timestamp(2000);
+        client.refund_timeout(&47u128);
+        assert_eq!(token_client.balance(&payer1), 150);
+        assert_eq!(token_client.balance(&payer2), 50);
+        assert!(client.get_escrow(&47u128).closed);
+
+        client.refund_timeout(&48u128);
+        assert_eq!(token_client.balance(&payer1), 250);
+        assert_eq!(token_client.balance(&payer2), 150);
+        assert!(client.get_escrow(&48u128).closed);
+    }
+
+    #[test]
+    fn test_payee_ack_does_not_block_resolutions() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let eadmin = Address::generate(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(eadmin.clone());
+        client.init(&admin, &0u32, &Address::generate(&e), &eadmins, &1u32);
+
+        let token_addr = deploy_token(&e, &Address::generate(&e));
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer = Address::generate(&e);
+        let payee = Address::generate(&e);
+        let arb = Address::generate(&e);
+        token_client.mint(&payer, &3_000);
+        let mut payers = SVec::new(&e); payers.push_back(payer.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(arb.clone());
+        for id in [49u128, 50u128, 51u128] {
+            client.create_escrow(&id, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: Some(2000), expiry_ts: 5000, require_payee_ack: true, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+            client.deposit(&id, &payer, &1_000);
+        }
+        let mut dists = SVec::new(&e); dists.push_back((payee.clone(), 1_000));
+
+        // arbiter resolution of a dispute
+        client.open_dispute(&49u128, &payer);
+        client.arbiter_release(&49u128, &arb, &dists);
+        assert!(client.get_escrow(&49u128).closed);
+
+        // emergency release
+        client.emergency_release(&50u128, &eadmin, &dists);
+        assert!(client.get_escrow(&50u128).closed);
+
+        // timeout release
+        e.ledger().set_timestamp(2000);
+        client.auto_release(&51u128);
+        assert!(client.get_escrow(&51u128).closed);
+        assert_eq!(token_client.balance(&payee), 3_000);
+    }
+
+    #[test]
+    fn test_proposals_isolated_by_role() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let eadmin1 = Address::generate(&e);
+        let eadmin2 = Address::generate(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(eadmin1.clone()); eadmins.push_back(eadmin2.clone());
+        client.init(&admin, &0u32, &Address::generate(&e), &eadmins, &2u32);
+
+        let token_addr = deploy_token(&e, &Address::generate(&e));
+        let token_client = soroban_token_contract::Client::new(&e, &token_addr);
+        let payer1 = Address::generate(&e);
+        let payer2 = Address::generate(&e);
+        let payee = Address::generate(&e);
+        let arb1 = Address::generate(&e);
+        let arb2 = Address::generate(&e);
+        token_client.mint(&payer1, &2_000);
+        let mut payers = SVec::new(&e); payers.push_back(payer1.clone()); payers.push_back(payer2.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(arb1.clone()); arbs.push_back(arb2.clone());
+        for id in [52u128, 53u128] {
+            client.create_escrow(&id, &EscrowInit { token: token_addr.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 2, refund_signers: payers.clone(), refund_threshold: 2, arbiters: arbs.clone(), arbiter_threshold: 2, auto_release_ts: None, expiry_ts: 5000, require_payee_ack: false, extra_tokens: SVec::new(&e), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+            client.deposit(&id, &payer1, &1_000);
+        }
+        let mut small = SVec::new(&e); small.push_back((payee.clone(), 100));
+        let mut large = SVec::new(&e); large.push_back((payee.clone(), 900));
+
+        // an emergency vote neither replaces the signers' proposal nor borrows their approvals
+        client.propose_release(&52u128, &payer1, &small);
+        client.emergency_release(&52u128, &eadmin1, &large);
+        client.cancel_release_proposal(&52u128, &payer2);
+        client.propose_release(&52u128, &payer1, &small);
+        client.approve_release(&52u128, &payer2);
+        assert_eq!(token_client.balance(&payee), 100);
+        client.emergency_release(&52u128, &eadmin2, &large);
+        assert_eq!(token_client.balance(&payee), 1_000);
+        assert!(client.get_escrow(&52u128).closed);
+
+        // arbiter votes for a refund don't count toward a release, and signers can't cancel them
+        client.open_dispute(&53u128, &payer1);
+        let mut refund = SVec::new(&e); refund.push_back((payer1.clone(), 1_000));
+        client.arbiter_release(&53u128, &arb1, &large);
+        client.arbiter_refund(&53u128, &arb2, &refund);
+        assert_eq!(client.get_escrow(&53u128).balance, 1_000);
+        assert!(client.try_cancel_release_proposal(&53u128, &payer1).is_err());
+        assert!(client.try_cancel_emergency_release(&53u128, &eadmin1).is_err());
+        client.arbiter_refund(&53u128, &arb1, &refund);
+        assert_eq!(token_client.balance(&payer1), 1_000);
+        assert!(client.get_escrow(&53u128).closed);
+    }
+
+    #[test]
+    fn test_extra_tokens_leave_through_every_path() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(Ledger { timestamp: 1000, protocol_version: 21, sequence_number: 1, network_passphrase: Default::default(), base_reserve: 0 });
+
+        let admin = Address::generate(&e);
+        let (_, client) = deploy_contract(&e);
+        let eadmin = Address::generate(&e);
+        let mut eadmins = SVec::new(&e); eadmins.push_back(eadmin.clone());
+        client.init(&admin, &0u32, &Address::generate(&e), &eadmins, &1u32);
+
+        let token_a = deploy_token(&e, &Address::generate(&e));
+        let token_b = deploy_token(&e, &Address::generate(&e));
+        let client_a = soroban_token_contract::Client::new(&e, &token_a);
+        let client_b = soroban_token_contract::Client::new(&e, &token_b);
+        let payer = Address::generate(&e);
+        let payee1 = Address::generate(&e);
+        let payee2 = Address::generate(&e);
+        let arb = Address::generate(&e);
+        client_a.mint(&payer, &3_000);
+        client_b.mint(&payer, &1_500);
+        let mut payers = SVec::new(&e); payers.push_back(payer.clone());
+        let mut payees = SVec::new(&e); payees.push_back(payee1.clone()); payees.push_back(payee2.clone());
+        let mut arbs = SVec::new(&e); arbs.push_back(arb.clone());
+        let mut extras = SVec::new(&e); extras.push_back(token_b.clone());
+        for id in [54u128, 55u128, 56u128] {
+            client.create_escrow(&id, &EscrowInit { token: token_a.clone(), payers: payers.clone(), payees: payees.clone(), release_signers: payers.clone(), release_threshold: 1, refund_signers: payers.clone(), refund_threshold: 1, arbiters: arbs.clone(), arbiter_threshold: 1, auto_release_ts: Some(2000), expiry_ts: 5000, require_payee_ack: false, extra_tokens: extras.clone(), strict_roles: false, delivery_fallback: None, min_deposit_per_payer: None, min_payee_tier: None, arbiter_deadline_ts: None, release_weights: None, require_arbiter_cosign: false, refund_mode: RefundMode::Proportional });
+            client.deposit(&id, &payer, &1_000);
+            client.deposit_token(&id, &payer, &token_b, &500);
+        }
+        let mut dists_a = SVec::new(&e); dists_a.push_back((payee1.clone(), 1_000));
+        let mut dists_b = SVec::new(&e); dists_b.push_back((payee1.clone(), 500));
+
+        // arbiters settle each token; the dispute stays open until both are paid
+        client.open_dispute(&54u128, &payer);
+        client.arbiter_release(&54u128, &arb, &dists_a);
+        assert!(!client.get_escrow(&54u128).closed);
+        assert!(client.try_arbiter_release_token(&54u128, &arb, &token_b, &dists_a).is_err());
+        let mut refund_b = SVec::new(&e); refund_b.push_back((payer.clone(), 500));
+        client.arbiter_refund_token(&54u128, &arb, &token_b, &refund_b);
+        let esc = client.get_escrow(&54u128);
+        assert!(esc.closed && !esc.disputed);
+        assert_eq!(client_b.balance(&payer), 500);
+
+        // signers refund and emergency admins release the extra token by name
+        client.propose_refund_token(&55u128, &payer, &token_b, &refund_b);
+        client.approve_refund(&55u128, &payer);
+        assert_eq!(client_b.balance(&payer), 1_000);
+        client.emergency_release(&55u128, &eadmin, &dists_a);
+        assert!(client.get_escrow(&55u128).closed);
+
+        client.emergency_release_token(&56u128, &eadmin, &token_b, &dists_b);
+        assert_eq!(client_b.balance(&payee1), 500);
+
+        // the timeout pays out every token and closes the escrow
+        client.deposit_token(&56u128, &payer, &token_b, &500);
+        e.ledger().set_timestamp(2000);
+        client.auto_release(&56u128);
+        assert!(client.get_escrow(&56u128).closed);
+        assert_eq!(client_a.balance(&payee1), 2_500);
+        assert_eq!(client_a.balance(&payee2), 500);
+        assert_eq!(client_b.balance(&payee1), 750);
+        assert_eq!(client_b.balance(&payee2), 250);
+    }
 }
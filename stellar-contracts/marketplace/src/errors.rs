@@ -67,4 +67,28 @@ pub enum Error {
     PriceOutOfRange = 526,
     /// Payment asset is not supported by the oracle
     PaymentAssetNotSupported = 527,
+
+    // ========================================================================
+    // ADMIN ERRORS (530-539)
+    // ========================================================================
+
+    /// Admin action requires an approved, unused multisig proposal
+    MultisigNotApproved = 530,
+
+    // ========================================================================
+    // CATALOG ERRORS (540-549)
+    // ========================================================================
+
+    /// Category has been deactivated and accepts no new listings
+    CategoryInactive = 540,
+
+    /// Product price was changed too recently to change again
+    PriceUpdateTooSoon = 541,
+
+    // ========================================================================
+    // REPUTATION ERRORS (550-559)
+    // ========================================================================
+
+    /// No reputation contract has been configured
+    ReputationNotConfigured = 550,
 }
@@ -15,6 +15,22 @@ pub struct SellerRegisteredEventData {
     pub seller: Address,
 }
 
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SellerDeregisteredEventData {
+    #[topic]
+    pub seller: Address,
+    pub refunded: u128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BondForfeitedEventData {
+    #[topic]
+    pub seller: Address,
+    pub amount: u128,
+}
+
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct SellerVerifiedEventData {
@@ -36,6 +52,21 @@ pub struct SellerUnsuspendedEventData {
     pub seller: Address,
 }
 
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SellerMetadataEventData {
+    #[topic]
+    pub seller: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SellerVacationEventData {
+    #[topic]
+    pub seller: Address,
+    pub on_vacation: bool,
+}
+
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CategoryCreatedEventData {
@@ -44,6 +75,14 @@ pub struct CategoryCreatedEventData {
     pub name: String,
 }
 
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CategoryActiveEventData {
+    #[topic]
+    pub category_id: u32,
+    pub is_active: bool,
+}
+
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ProductListedEventData {
@@ -58,6 +97,44 @@ pub struct ProductUpdatedEventData {
     pub seller: Address,
 }
 
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProductPurchasedEventData {
+    #[topic]
+    pub buyer: Address,
+    #[topic]
+    pub seller: Address,
+    pub product_id: u64,
+    pub quantity: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProductSaleEventData {
+    #[topic]
+    pub seller: Address,
+    pub product_id: u64,
+    pub sale_price: u128,
+    pub ends_at: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProductRestockedEventData {
+    #[topic]
+    pub seller: Address,
+    pub product_id: u64,
+    pub stock_quantity: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StockBatchUpdatedEventData {
+    #[topic]
+    pub seller: Address,
+    pub count: u32,
+}
+
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ProductDelistedEventData {
@@ -88,6 +165,14 @@ pub struct FeeCollectedEventData {
     pub admin: Address,
 }
 
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeesWithdrawnEventData {
+    #[topic]
+    pub to: Address,
+    pub amount: u128,
+}
+
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct SellerRatingUpdatedEventData {
@@ -130,3 +215,38 @@ pub struct OracleAddressUpdateEventData {
     pub oracle_type: u32,
     pub new_address: Address,
 }
+
+/// Published by `validate_listing_price` when a price falls outside the oracle tolerance.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceRejectedEventData {
+    #[topic]
+    pub seller: Address,
+    pub proposed_price: u128,
+    pub oracle_price: i128,
+    pub tolerance: u32,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LowStockEventData {
+    #[topic]
+    pub product_id: u64,
+    pub remaining: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminProposedEventData {
+    #[topic]
+    pub admin: Address,
+    pub pending_admin: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminTransferredEventData {
+    #[topic]
+    pub old_admin: Address,
+    pub new_admin: Address,
+}
@@ -2,16 +2,20 @@
 
 mod errors;
 mod events;
+mod multisig;
 mod oracle;
 mod reflector;
+mod reputation;
 mod storage;
 mod types;
 
-use soroban_sdk::{contract, contractimpl, Address, Env, String, Symbol, Vec};
+use soroban_sdk::{contract, contractimpl, token, Address, Bytes, Env, String, Symbol, Vec};
 
 use crate::errors::Error;
 use crate::events::*;
+use crate::multisig::{MultisigClient, ACTION_TRANSFER_ADMIN};
 use crate::oracle::OracleService;
+use crate::reputation::ReputationClient;
 use crate::storage::*;
 use crate::types::*;
 
@@ -30,10 +34,15 @@ const INSTANCE_TTL_THRESHOLD: u32 = INSTANCE_TTL_AMOUNT - DAY_IN_LEDGERS;
 
 /// Maximum rating value (5 stars * 100 for precision)
 const MAX_RATING: u32 = 500;
+/// Reputation tiers are star ratings on a 0-5 scale
+const MAX_TIER: u32 = 5;
 
 /// Maximum basis points for fees
 const MAX_FEE_RATE: u32 = 10000; // 100%
 
+/// Maximum number of products updated by a single `set_stock_batch` call
+const MAX_STOCK_BATCH: u32 = 50;
+
 // ============================================================================
 // Contract
 // ============================================================================
@@ -159,6 +168,156 @@ impl MarketX {
         Ok(())
     }
 
+    /// Route admin transfers through an access-control multisig (admin only)
+    ///
+    /// Passing `None` falls back to single-admin transfers.
+    pub fn set_admin_multisig(
+        e: &Env,
+        admin: Address,
+        multisig: Option<Address>,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+
+        let config = get_config(e).ok_or(Error::NotInitialized)?;
+
+        if admin != config.admin {
+            return Err(Error::Unauthorized);
+        }
+
+        set_admin_multisig(e, &multisig);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    /// Get the multisig gating admin transfers, if any
+    pub fn get_admin_multisig(e: &Env) -> Option<Address> {
+        get_admin_multisig(e)
+    }
+
+    /// Hand marketplace control to a new admin
+    ///
+    /// When a multisig is configured, `proposal_id` must reference a proposal
+    /// it reports as approved for a transfer to exactly `new_admin`. Each
+    /// proposal authorizes a single transfer.
+    ///
+    /// # Errors
+    /// * `Error::Unauthorized` - If caller is not admin
+    /// * `Error::MultisigNotApproved` - If the proposal is missing, unapproved or already used
+    pub fn transfer_admin(
+        e: &Env,
+        admin: Address,
+        new_admin: Address,
+        proposal_id: Option<u64>,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+
+        let mut config = get_config(e).ok_or(Error::NotInitialized)?;
+
+        if admin != config.admin {
+            return Err(Error::Unauthorized);
+        }
+
+        if let Some(multisig) = get_admin_multisig(e) {
+            let proposal_id = proposal_id.ok_or(Error::MultisigNotApproved)?;
+            if is_admin_proposal_consumed(e, proposal_id)
+                || !MultisigClient::new(e, &multisig).is_proposal_approved(
+                    &proposal_id,
+                    &ACTION_TRANSFER_ADMIN,
+                    &new_admin,
+                )
+            {
+                return Err(Error::MultisigNotApproved);
+            }
+            consume_admin_proposal(e, proposal_id);
+        }
+
+        config.admin = new_admin.clone();
+        config.updated_at = e.ledger().timestamp();
+        set_config(e, &config);
+        remove_pending_admin(e);
+
+        AdminTransferredEventData {
+            old_admin: admin,
+            new_admin,
+        }
+        .publish(e);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    /// Nominate a new admin, who must call `accept_admin_transfer` (admin only)
+    ///
+    /// A later proposal replaces an unaccepted one. Marketplaces gated by a
+    /// multisig must use `transfer_admin` with an approved proposal instead.
+    ///
+    /// # Errors
+    /// * `Error::Unauthorized` - If caller is not admin
+    /// * `Error::MultisigNotApproved` - If an admin multisig is configured
+    pub fn propose_admin_transfer(
+        e: &Env,
+        admin: Address,
+        new_admin: Address,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+
+        let config = get_config(e).ok_or(Error::NotInitialized)?;
+
+        if admin != config.admin {
+            return Err(Error::Unauthorized);
+        }
+
+        if get_admin_multisig(e).is_some() {
+            return Err(Error::MultisigNotApproved);
+        }
+
+        set_pending_admin(e, &new_admin);
+
+        AdminProposedEventData {
+            admin,
+            pending_admin: new_admin,
+        }
+        .publish(e);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    /// Complete a proposed admin transfer (proposed admin only)
+    ///
+    /// # Errors
+    /// * `Error::Unauthorized` - If `new_admin` is not the pending admin
+    pub fn accept_admin_transfer(e: &Env, new_admin: Address) -> Result<(), Error> {
+        new_admin.require_auth();
+
+        let mut config = get_config(e).ok_or(Error::NotInitialized)?;
+
+        if get_pending_admin(e) != Some(new_admin.clone()) {
+            return Err(Error::Unauthorized);
+        }
+
+        let old_admin = config.admin;
+        config.admin = new_admin.clone();
+        config.updated_at = e.ledger().timestamp();
+        set_config(e, &config);
+        remove_pending_admin(e);
+
+        AdminTransferredEventData {
+            old_admin,
+            new_admin,
+        }
+        .publish(e);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    /// Get the admin nominated by `propose_admin_transfer`, if any
+    pub fn get_pending_admin(e: &Env) -> Option<Address> {
+        get_pending_admin(e)
+    }
+
     /// Check if marketplace is paused
     pub fn is_paused(e: &Env) -> Result<bool, Error> {
         let config = get_config(e).ok_or(Error::NotInitialized)?;
@@ -171,6 +330,9 @@ impl MarketX {
 
     /// Register a new seller
     ///
+    /// When a seller bond is configured it is transferred from the seller
+    /// into the contract and recorded on the seller.
+    ///
     /// # Arguments
     /// * `seller` - Address registering as seller
     /// * `metadata` - JSON encoded seller information (name, description, etc.)
@@ -194,6 +356,14 @@ impl MarketX {
             return Err(Error::InvalidMetadata);
         }
 
+        let (bond_token, bond) = match get_seller_bond(e) {
+            Some((token, amount)) => {
+                Self::transfer_token(e, &token, &seller, &e.current_contract_address(), amount)?;
+                (Some(token), amount)
+            }
+            None => (None, 0),
+        };
+
         let seller_data = Seller {
             address: seller.clone(),
             status: SellerStatus::Unverified,
@@ -202,9 +372,14 @@ impl MarketX {
             total_revenue: 0,
             created_at: e.ledger().timestamp(),
             metadata,
+            on_vacation: false,
+            bond,
+            bond_token,
+            rating_synced_at: 0,
         };
 
         set_seller(e, &seller_data);
+        add_seller_to_index(e, &seller);
 
         let mut updated_config = config;
         updated_config.total_sellers += 1;
@@ -220,6 +395,127 @@ impl MarketX {
         Ok(())
     }
 
+    /// Leave the marketplace and reclaim the registration bond (seller only)
+    ///
+    /// The seller's products are delisted.
+    ///
+    /// # Errors
+    /// * `Error::SellerSuspended` - If the seller is suspended
+    pub fn deregister_seller(e: &Env, seller: Address) -> Result<(), Error> {
+        seller.require_auth();
+
+        let mut config = get_config(e).ok_or(Error::NotInitialized)?;
+        let seller_data = get_seller(e, &seller).ok_or(Error::SellerNotFound)?;
+
+        if seller_data.status == SellerStatus::Suspended {
+            return Err(Error::SellerSuspended);
+        }
+
+        for product_id in get_seller_products(e, &seller).iter() {
+            if let Some(mut product) = get_product(e, product_id) {
+                if product.status != ProductStatus::Delisted {
+                    product.status = ProductStatus::Delisted;
+                    set_product(e, &product);
+                }
+            }
+        }
+
+        if let Some(token) = &seller_data.bond_token {
+            if seller_data.bond > 0 {
+                Self::transfer_token(
+                    e,
+                    token,
+                    &e.current_contract_address(),
+                    &seller,
+                    seller_data.bond,
+                )?;
+            }
+        }
+
+        remove_seller(e, &seller);
+        config.total_sellers = config.total_sellers.saturating_sub(1);
+        config.updated_at = e.ledger().timestamp();
+        set_config(e, &config);
+
+        SellerDeregisteredEventData {
+            seller,
+            refunded: seller_data.bond,
+        }
+        .publish(e);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    /// Set the bond new sellers pay at registration (admin only)
+    ///
+    /// An amount of 0 disables the bond. Existing sellers keep the bond they paid.
+    pub fn set_seller_bond(
+        e: &Env,
+        admin: Address,
+        token: Address,
+        amount: u128,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+
+        let config = get_config(e).ok_or(Error::NotInitialized)?;
+
+        if admin != config.admin {
+            return Err(Error::Unauthorized);
+        }
+
+        set_seller_bond(e, &token, amount);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    /// Get the bond token and amount charged at registration, if enabled
+    pub fn get_seller_bond(e: &Env) -> Option<(Address, u128)> {
+        get_seller_bond(e)
+    }
+
+    /// Seize a seller's bond after a severe violation (admin only)
+    ///
+    /// # Arguments
+    /// * `seller_address` - Seller whose bond is forfeited
+    /// * `to` - Recipient of the forfeited bond
+    pub fn forfeit_seller_bond(
+        e: &Env,
+        admin: Address,
+        seller_address: Address,
+        to: Address,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+
+        let config = get_config(e).ok_or(Error::NotInitialized)?;
+
+        if admin != config.admin {
+            return Err(Error::Unauthorized);
+        }
+
+        let mut seller = get_seller(e, &seller_address).ok_or(Error::SellerNotFound)?;
+        let token = seller.bond_token.clone().ok_or(Error::InsufficientBalance)?;
+
+        if seller.bond == 0 {
+            return Err(Error::InsufficientBalance);
+        }
+
+        let amount = seller.bond;
+        seller.bond = 0;
+        set_seller(e, &seller);
+        Self::transfer_token(e, &token, &e.current_contract_address(), &to, amount)?;
+
+        BondForfeitedEventData {
+            seller: seller_address,
+            amount,
+        }
+        .publish(e);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
     /// Get seller information
     pub fn get_seller(e: &Env, seller_address: Address) -> Result<Seller, Error> {
         get_seller(e, &seller_address).ok_or(Error::SellerNotFound)
@@ -342,19 +638,40 @@ impl MarketX {
         Ok(())
     }
 
-    // ========================================================================
-    // CATEGORY MANAGEMENT
-    // ========================================================================
+    /// Refresh a seller's cached rating from the reputation contract
+    ///
+    /// # Returns
+    /// * The refreshed rating (0-500)
+    ///
+    /// # Errors
+    /// * `Error::ReputationNotConfigured` - If no reputation contract is set
+    pub fn sync_seller_rating(e: &Env, seller_address: Address) -> Result<u32, Error> {
+        let mut seller = get_seller(e, &seller_address).ok_or(Error::SellerNotFound)?;
+        Self::refresh_seller_rating(e, &mut seller)?;
 
-    /// Create a new product category (admin only)
-    pub fn create_category(
-        e: &Env,
-        admin: Address,
-        id: u32,
-        name: String,
-        description: String,
-        commission_rate: u32,
-    ) -> Result<(), Error> {
+        Self::extend_instance_ttl(e);
+        Ok(seller.rating)
+    }
+
+    /// Get a seller's rating for gating decisions
+    ///
+    /// Uses the cached rating while it is within the sync interval and
+    /// refreshes it from the reputation contract once stale. Without a
+    /// reputation contract the stored rating is returned as is.
+    pub fn get_seller_rating(e: &Env, seller_address: Address) -> Result<u32, Error> {
+        let mut seller = get_seller(e, &seller_address).ok_or(Error::SellerNotFound)?;
+
+        let age = e.ledger().timestamp().saturating_sub(seller.rating_synced_at);
+        let stale = seller.rating_synced_at == 0 || age >= get_rating_sync_interval(e);
+        if stale && get_reputation_contract(e).is_some() {
+            Self::refresh_seller_rating(e, &mut seller)?;
+        }
+
+        Ok(seller.rating)
+    }
+
+    /// Set how long a synced seller rating stays fresh, in seconds (admin only)
+    pub fn set_rating_sync_interval(e: &Env, admin: Address, interval: u64) -> Result<(), Error> {
         admin.require_auth();
 
         let config = get_config(e).ok_or(Error::NotInitialized)?;
@@ -363,31 +680,50 @@ impl MarketX {
             return Err(Error::Unauthorized);
         }
 
-        if category_exists(e, id) {
-            return Err(Error::CategoryAlreadyExists);
-        }
+        set_rating_sync_interval(e, interval);
 
-        if commission_rate > MAX_FEE_RATE {
-            return Err(Error::InvalidInput);
-        }
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
 
-        if name.is_empty() || description.is_empty() {
-            return Err(Error::InvalidMetadata);
+    /// Set the minimum seconds between price changes on a product (admin only)
+    ///
+    /// Stock and status updates are not limited. A cooldown of 0 disables it.
+    pub fn set_price_update_cooldown(e: &Env, admin: Address, cooldown: u64) -> Result<(), Error> {
+        admin.require_auth();
+
+        let config = get_config(e).ok_or(Error::NotInitialized)?;
+
+        if admin != config.admin {
+            return Err(Error::Unauthorized);
         }
 
-        let category = Category {
-            id,
-            name: name.clone(),
-            description,
-            commission_rate,
-            is_active: true,
-        };
+        set_price_update_cooldown(e, cooldown);
 
-        set_category(e, &category);
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
 
-        CategoryCreatedEventData {
-            category_id: id,
-            name,
+    /// Get the minimum seconds between price changes on a product
+    pub fn get_price_update_cooldown(e: &Env) -> u64 {
+        get_price_update_cooldown(e)
+    }
+
+    /// Put a seller's storefront on vacation, or bring it back (seller only)
+    ///
+    /// While on vacation the seller's products keep their stored status but are
+    /// treated as unavailable by listing queries and purchases.
+    pub fn set_seller_vacation(e: &Env, seller: Address, on: bool) -> Result<(), Error> {
+        seller.require_auth();
+
+        let mut seller_data = get_seller(e, &seller).ok_or(Error::SellerNotFound)?;
+
+        seller_data.on_vacation = on;
+        set_seller(e, &seller_data);
+
+        SellerVacationEventData {
+            seller: seller.clone(),
+            on_vacation: on,
         }
         .publish(e);
 
@@ -395,14 +731,160 @@ impl MarketX {
         Ok(())
     }
 
-    /// Get category information
-    pub fn get_category(e: &Env, id: u32) -> Result<Category, Error> {
-        get_category(e, id).ok_or(Error::CategoryNotFound)
-    }
+    /// Replace a seller's storefront metadata (seller only)
+    ///
+    /// # Errors
+    /// * `Error::InvalidMetadata` - If metadata is empty
+    /// * `Error::SellerSuspended` - If the seller is suspended
+    pub fn update_seller_metadata(
+        e: &Env,
+        seller: Address,
+        new_metadata: String,
+    ) -> Result<(), Error> {
+        seller.require_auth();
 
-    // ========================================================================
-    // PRODUCT LISTING
-    // ========================================================================
+        let mut seller_data = get_seller(e, &seller).ok_or(Error::SellerNotFound)?;
+
+        if seller_data.status == SellerStatus::Suspended {
+            return Err(Error::SellerSuspended);
+        }
+
+        if new_metadata.is_empty() {
+            return Err(Error::InvalidMetadata);
+        }
+
+        seller_data.metadata = new_metadata;
+        set_seller(e, &seller_data);
+
+        SellerMetadataEventData {
+            seller: seller.clone(),
+        }
+        .publish(e);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    // ========================================================================
+    // CATEGORY MANAGEMENT
+    // ========================================================================
+
+    /// Create a new product category with an explicit ID (admin only)
+    ///
+    /// Prefer `create_category_auto`; explicit IDs are kept for migrations.
+    pub fn create_category(
+        e: &Env,
+        admin: Address,
+        id: u32,
+        name: String,
+        description: String,
+        commission_rate: u32,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+
+        let config = get_config(e).ok_or(Error::NotInitialized)?;
+
+        if admin != config.admin {
+            return Err(Error::Unauthorized);
+        }
+
+        if category_exists(e, id) {
+            return Err(Error::CategoryAlreadyExists);
+        }
+
+        if commission_rate > MAX_FEE_RATE {
+            return Err(Error::InvalidInput);
+        }
+
+        if name.is_empty() || description.is_empty() {
+            return Err(Error::InvalidMetadata);
+        }
+
+        let category = Category {
+            id,
+            name: name.clone(),
+            description,
+            commission_rate,
+            is_active: true,
+        };
+
+        set_category(e, &category);
+        add_category_to_index(e, id);
+
+        CategoryCreatedEventData {
+            category_id: id,
+            name,
+        }
+        .publish(e);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    /// Create a category with the next sequential ID (admin only)
+    ///
+    /// IDs already taken through `create_category` are skipped.
+    ///
+    /// # Returns
+    /// * The assigned category ID
+    pub fn create_category_auto(
+        e: &Env,
+        admin: Address,
+        name: String,
+        description: String,
+        commission_rate: u32,
+    ) -> Result<u32, Error> {
+        let id = get_next_category_id(e);
+        Self::create_category(e, admin, id, name, description, commission_rate)?;
+        set_category_counter(e, id);
+        Ok(id)
+    }
+
+    /// Get every category ID in creation order
+    pub fn get_categories(e: &Env) -> Vec<u32> {
+        get_category_index(e)
+    }
+
+    /// Retire a category or bring it back (admin only)
+    ///
+    /// Inactive categories reject new listings; existing products are untouched.
+    pub fn set_category_active(
+        e: &Env,
+        admin: Address,
+        category_id: u32,
+        active: bool,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+
+        let config = get_config(e).ok_or(Error::NotInitialized)?;
+
+        if admin != config.admin {
+            return Err(Error::Unauthorized);
+        }
+
+        let mut category = get_category(e, category_id).ok_or(Error::CategoryNotFound)?;
+
+        category.is_active = active;
+        set_category(e, &category);
+
+        CategoryActiveEventData {
+            category_id,
+            is_active: active,
+        }
+        .publish(e);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    /// Get category information
+    pub fn get_category(e: &Env, id: u32) -> Result<Category, Error> {
+        get_category(e, id).ok_or(Error::CategoryNotFound)
+    }
+
+    // ========================================================================
+    // PRODUCT LISTING
+    // ========================================================================
 
     /// Add a new product (verified sellers only)
     ///
@@ -415,6 +897,7 @@ impl MarketX {
     /// * `stock_quantity` - Available quantity
     /// * `metadata` - Optional JSON metadata
     /// * `payment_asset` - Optional payment asset address for oracle price validation
+    /// * `expires_at` - Listing expiry timestamp (0 = never expires)
     ///
     /// # Returns
     /// * Product ID if successful
@@ -432,6 +915,7 @@ impl MarketX {
         price: u128,
         stock_quantity: u64,
         metadata: String,
+        expires_at: u64,
     ) -> Result<u64, Error> {
         seller.require_auth();
 
@@ -453,7 +937,11 @@ impl MarketX {
         }
 
         // Verify category exists
-        let _category = get_category(e, category_id).ok_or(Error::CategoryNotFound)?;
+        let category = get_category(e, category_id).ok_or(Error::CategoryNotFound)?;
+
+        if !category.is_active {
+            return Err(Error::CategoryInactive);
+        }
 
         if name.is_empty() || description.is_empty() {
             return Err(Error::InvalidMetadata);
@@ -463,6 +951,10 @@ impl MarketX {
             return Err(Error::InvalidInput);
         }
 
+        if expires_at != 0 && expires_at <= e.ledger().timestamp() {
+            return Err(Error::InvalidInput);
+        }
+
         let product_id = get_next_product_id(e);
 
         let product = Product {
@@ -478,6 +970,10 @@ impl MarketX {
             purchase_count: 0,
             created_at: e.ledger().timestamp(),
             metadata,
+            expires_at,
+            last_price_change: 0,
+            sale_price: None,
+            sale_ends_at: None,
         };
 
         set_product(e, &product);
@@ -509,14 +1005,17 @@ impl MarketX {
     /// * `price` - Price in the payment asset
     /// * `stock_quantity` - Available quantity
     /// * `metadata` - Optional JSON metadata
-    /// * `payment_asset` - Payment asset address for oracle price validation
+    /// * `payment_asset` - Payment asset for oracle price validation
+    ///   (None = the category's reference asset)
+    /// * `expires_at` - Listing expiry timestamp (0 = never expires)
     ///
     /// # Returns
     /// * Product ID if successful
     ///
     /// # Errors
     /// * `Error::PriceOutOfRange` - If price deviates more than tolerance from oracle
-    /// * `Error::PaymentAssetNotSupported` - If payment asset is not tracked by oracle
+    /// * `Error::PaymentAssetNotSupported` - If payment asset is not tracked by oracle,
+    ///   or none is given and the category has no reference asset
     pub fn add_product_with_validation(
         e: &Env,
         seller: Address,
@@ -526,7 +1025,8 @@ impl MarketX {
         price: u128,
         stock_quantity: u64,
         metadata: String,
-        payment_asset: Address,
+        payment_asset: Option<Address>,
+        expires_at: u64,
     ) -> Result<u64, Error> {
         seller.require_auth();
 
@@ -548,7 +1048,11 @@ impl MarketX {
         }
 
         // Verify category exists
-        let _category = get_category(e, category_id).ok_or(Error::CategoryNotFound)?;
+        let category = get_category(e, category_id).ok_or(Error::CategoryNotFound)?;
+
+        if !category.is_active {
+            return Err(Error::CategoryInactive);
+        }
 
         if name.is_empty() || description.is_empty() {
             return Err(Error::InvalidMetadata);
@@ -558,9 +1062,17 @@ impl MarketX {
             return Err(Error::InvalidInput);
         }
 
+        if expires_at != 0 && expires_at <= e.ledger().timestamp() {
+            return Err(Error::InvalidInput);
+        }
+
         // Validate price against oracle if configured
         if let Some(oracle_config) = get_oracle_config(e) {
             if oracle_config.is_enabled {
+                let payment_asset = payment_asset
+                    .or_else(|| get_category_reference_asset(e, category_id))
+                    .ok_or(Error::PaymentAssetNotSupported)?;
+
                 // Validate that the payment asset is supported
                 OracleService::validate_payment_asset(e, &payment_asset)?;
 
@@ -589,6 +1101,10 @@ impl MarketX {
             purchase_count: 0,
             created_at: e.ledger().timestamp(),
             metadata,
+            expires_at,
+            last_price_change: 0,
+            sale_price: None,
+            sale_ends_at: None,
         };
 
         set_product(e, &product);
@@ -611,10 +1127,65 @@ impl MarketX {
     }
 
     /// Get product information
+    ///
+    /// Returns the stored product, so `price` is always the regular price;
+    /// use `get_effective_price` for what a buyer pays right now.
     pub fn get_product(e: &Env, product_id: u64) -> Result<Product, Error> {
         get_product(e, product_id).ok_or(Error::ProductNotFound)
     }
 
+    /// Get the price a buyer currently pays, the sale price while a sale is running
+    pub fn get_effective_price(e: &Env, product_id: u64) -> Result<u128, Error> {
+        let product = get_product(e, product_id).ok_or(Error::ProductNotFound)?;
+        Ok(product.effective_price(e.ledger().timestamp()))
+    }
+
+    /// Run a time-limited sale on a product (seller only)
+    ///
+    /// # Arguments
+    /// * `seller` - Seller address (must be product owner)
+    /// * `product_id` - Product to discount
+    /// * `sale_price` - Discounted price, below the regular price (0 ends any running sale)
+    /// * `ends_at` - Timestamp the sale ends at, in the future
+    pub fn set_product_sale(
+        e: &Env,
+        seller: Address,
+        product_id: u64,
+        sale_price: u128,
+        ends_at: u64,
+    ) -> Result<(), Error> {
+        seller.require_auth();
+
+        let mut product = get_product(e, product_id).ok_or(Error::ProductNotFound)?;
+
+        if seller != product.seller {
+            return Err(Error::Unauthorized);
+        }
+
+        if sale_price == 0 {
+            product.sale_price = None;
+            product.sale_ends_at = None;
+        } else {
+            if sale_price >= product.price || ends_at <= e.ledger().timestamp() {
+                return Err(Error::InvalidInput);
+            }
+            product.sale_price = Some(sale_price);
+            product.sale_ends_at = Some(ends_at);
+        }
+        set_product(e, &product);
+
+        ProductSaleEventData {
+            seller,
+            product_id,
+            sale_price,
+            ends_at,
+        }
+        .publish(e);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
     /// Update product (seller only)
     ///
     /// # Arguments
@@ -639,10 +1210,13 @@ impl MarketX {
             return Err(Error::Unauthorized);
         }
 
+        let previous_stock = product.stock_quantity;
         let mut updated = false;
 
         if price > 0 && price != product.price {
+            Self::ensure_price_change_allowed(e, &product)?;
             product.price = price;
+            product.last_price_change = e.ledger().timestamp();
             updated = true;
         }
 
@@ -666,6 +1240,7 @@ impl MarketX {
         }
 
         set_product(e, &product);
+        Self::emit_low_stock_if_crossed(e, product_id, previous_stock, product.stock_quantity);
 
         ProductUpdatedEventData {
             seller: seller.clone(),
@@ -705,9 +1280,12 @@ impl MarketX {
             return Err(Error::Unauthorized);
         }
 
+        let previous_stock = product.stock_quantity;
         let mut updated = false;
 
         if price > 0 && price != product.price {
+            Self::ensure_price_change_allowed(e, &product)?;
+
             // Validate new price against oracle if configured
             if let Some(oracle_config) = get_oracle_config(e) {
                 if oracle_config.is_enabled {
@@ -720,6 +1298,7 @@ impl MarketX {
                 }
             }
             product.price = price;
+            product.last_price_change = e.ledger().timestamp();
             updated = true;
         }
 
@@ -743,6 +1322,7 @@ impl MarketX {
         }
 
         set_product(e, &product);
+        Self::emit_low_stock_if_crossed(e, product_id, previous_stock, product.stock_quantity);
 
         ProductUpdatedEventData {
             seller: seller.clone(),
@@ -753,8 +1333,64 @@ impl MarketX {
         Ok(())
     }
 
-    /// Delist product (seller only)
-    pub fn delist_product(e: &Env, seller: Address, product_id: u64) -> Result<(), Error> {
+    /// Check a listing price against the oracle before listing or repricing (seller only)
+    ///
+    /// Returns `false` and publishes `PriceRejectedEventData` when the price falls outside
+    /// the oracle tolerance, so monitoring can spot repeated out-of-range attempts. Always
+    /// `true` while oracle validation is disabled.
+    ///
+    /// # Arguments
+    /// * `seller` - Seller proposing the price
+    /// * `payment_asset` - Payment asset the price is quoted in
+    /// * `price` - Proposed price
+    ///
+    /// # Errors
+    /// * `Error::PaymentAssetNotSupported` - If payment asset is not tracked by oracle
+    pub fn validate_listing_price(
+        e: &Env,
+        seller: Address,
+        payment_asset: Address,
+        price: u128,
+    ) -> Result<bool, Error> {
+        seller.require_auth();
+
+        let oracle_config = match get_oracle_config(e) {
+            Some(config) if config.is_enabled => config,
+            _ => return Ok(true),
+        };
+
+        OracleService::validate_payment_asset(e, &payment_asset)?;
+        let price_data = OracleService::get_stellar_asset_price(e, &payment_asset)?;
+        let tolerance = oracle_config.price_tolerance;
+
+        match OracleService::validate_product_price(price_data.price, price, tolerance) {
+            Ok(()) => Ok(true),
+            Err(Error::PriceOutOfRange) => {
+                PriceRejectedEventData {
+                    seller,
+                    proposed_price: price,
+                    oracle_price: price_data.price,
+                    tolerance,
+                }
+                .publish(e);
+                Ok(false)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Add units to a product's stock (seller only)
+    ///
+    /// A sold-out product becomes `Active` again once restocked.
+    ///
+    /// # Errors
+    /// * `Error::InvalidInput` - If `added_quantity` is 0 or the stock would overflow
+    pub fn restock_product(
+        e: &Env,
+        seller: Address,
+        product_id: u64,
+        added_quantity: u64,
+    ) -> Result<(), Error> {
         seller.require_auth();
 
         let mut product = get_product(e, product_id).ok_or(Error::ProductNotFound)?;
@@ -763,11 +1399,24 @@ impl MarketX {
             return Err(Error::Unauthorized);
         }
 
-        product.status = ProductStatus::Delisted;
+        if added_quantity == 0 {
+            return Err(Error::InvalidInput);
+        }
+
+        product.stock_quantity = product
+            .stock_quantity
+            .checked_add(added_quantity)
+            .ok_or(Error::InvalidInput)?;
+
+        if product.status == ProductStatus::OutOfStock {
+            product.status = ProductStatus::Active;
+        }
         set_product(e, &product);
 
-        ProductDelistedEventData {
-            seller: seller.clone(),
+        ProductRestockedEventData {
+            seller,
+            product_id,
+            stock_quantity: product.stock_quantity,
         }
         .publish(e);
 
@@ -775,35 +1424,46 @@ impl MarketX {
         Ok(())
     }
 
-    /// Update product rating (seller only)
+    /// Set stock on several products at once (seller only)
+    ///
+    /// Each product flips to `OutOfStock` at zero stock and back to `Active`
+    /// when restocked; delisted products keep their status. The whole batch
+    /// reverts if any product is missing or owned by another seller.
     ///
     /// # Arguments
-    /// * `seller` - Seller address (must be product owner)
-    /// * `product_id` - Product to rate
-    /// * `new_rating` - Rating value (0-500, where 500 = 5 stars)
-    pub fn update_product_rating(
+    /// * `updates` - `(product_id, quantity)` pairs (1-50)
+    pub fn set_stock_batch(
         e: &Env,
         seller: Address,
-        product_id: u64,
-        new_rating: u32,
+        updates: Vec<(u64, u64)>,
     ) -> Result<(), Error> {
         seller.require_auth();
 
-        let mut product = get_product(e, product_id).ok_or(Error::ProductNotFound)?;
-
-        if new_rating > MAX_RATING {
+        if updates.is_empty() || updates.len() > MAX_STOCK_BATCH {
             return Err(Error::InvalidInput);
         }
 
-        if seller != product.seller {
-            return Err(Error::Unauthorized);
-        }
+        for (product_id, quantity) in updates.iter() {
+            let mut product = get_product(e, product_id).ok_or(Error::ProductNotFound)?;
 
-        product.rating = new_rating;
-        set_product(e, &product);
+            if seller != product.seller {
+                return Err(Error::Unauthorized);
+            }
 
-        QualityRatedEventData {
-            seller: seller.clone(),
+            let previous_stock = product.stock_quantity;
+            product.stock_quantity = quantity;
+            product.status = match product.status {
+                ProductStatus::Active if quantity == 0 => ProductStatus::OutOfStock,
+                ProductStatus::OutOfStock if quantity > 0 => ProductStatus::Active,
+                status => status,
+            };
+            set_product(e, &product);
+            Self::emit_low_stock_if_crossed(e, product_id, previous_stock, quantity);
+        }
+
+        StockBatchUpdatedEventData {
+            seller,
+            count: updates.len(),
         }
         .publish(e);
 
@@ -811,45 +1471,570 @@ impl MarketX {
         Ok(())
     }
 
-    // ========================================================================
-    // PRODUCT SEARCH & FILTERING
-    // ========================================================================
+    /// Set the stock level below which a `LowStockEventData` is emitted (seller only)
+    ///
+    /// # Arguments
+    /// * `threshold` - Stock level that triggers the alert (0 disables it)
+    pub fn set_low_stock_threshold(
+        e: &Env,
+        seller: Address,
+        product_id: u64,
+        threshold: u64,
+    ) -> Result<(), Error> {
+        seller.require_auth();
 
-    /// Get all products by seller
-    pub fn get_products_by_seller(e: &Env, seller_address: Address) -> Result<Vec<u64>, Error> {
-        if !seller_exists(e, &seller_address) {
-            return Err(Error::SellerNotFound);
+        let product = get_product(e, product_id).ok_or(Error::ProductNotFound)?;
+
+        if seller != product.seller {
+            return Err(Error::Unauthorized);
         }
 
-        Ok(get_seller_products(e, &seller_address))
+        set_low_stock_threshold(e, product_id, threshold);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
     }
 
-    /// Get all products in category
-    pub fn get_products_by_category(e: &Env, category_id: u32) -> Result<Vec<u64>, Error> {
-        if !category_exists(e, category_id) {
-            return Err(Error::CategoryNotFound);
+    /// Get the low-stock alert threshold for a product (0 = disabled)
+    pub fn get_low_stock_threshold(e: &Env, product_id: u64) -> Result<u64, Error> {
+        if get_product(e, product_id).is_none() {
+            return Err(Error::ProductNotFound);
         }
 
-        Ok(get_category_products(e, category_id))
+        Ok(get_low_stock_threshold(e, product_id))
+    }
+
+    /// Purchase units of a listed product
+    ///
+    /// # Arguments
+    /// * `buyer` - Buyer address
+    /// * `product_id` - Product to purchase
+    /// * `quantity` - Units to purchase
+    ///
+    /// # Errors
+    /// * `Error::MarketplacePaused` - If marketplace is paused
+    /// * `Error::InvalidProductStatus` - If product is not listed
+    /// * `Error::OutOfStock` - If quantity exceeds available stock
+    pub fn purchase_product(
+        e: &Env,
+        buyer: Address,
+        product_id: u64,
+        quantity: u64,
+    ) -> Result<(), Error> {
+        buyer.require_auth();
+
+        let config = get_config(e).ok_or(Error::NotInitialized)?;
+
+        if config.is_paused {
+            return Err(Error::MarketplacePaused);
+        }
+
+        if quantity == 0 {
+            return Err(Error::InvalidInput);
+        }
+
+        let mut product = get_product(e, product_id).ok_or(Error::ProductNotFound)?;
+
+        if !Self::is_listed(e, &product) {
+            return Err(Error::InvalidProductStatus);
+        }
+
+        if quantity > product.stock_quantity {
+            return Err(Error::OutOfStock);
+        }
+
+        let amount = product
+            .effective_price(e.ledger().timestamp())
+            .checked_mul(quantity as u128)
+            .ok_or(Error::InvalidInput)?;
+
+        let mut seller_data = get_seller(e, &product.seller).ok_or(Error::SellerNotFound)?;
+        seller_data.total_sales += quantity;
+        seller_data.total_revenue += amount;
+        set_seller(e, &seller_data);
+
+        let previous_stock = product.stock_quantity;
+        product.stock_quantity -= quantity;
+        product.purchase_count += quantity;
+        if product.stock_quantity == 0 {
+            product.status = ProductStatus::OutOfStock;
+        }
+        set_product(e, &product);
+        Self::emit_low_stock_if_crossed(e, product_id, previous_stock, product.stock_quantity);
+
+        ProductPurchasedEventData {
+            buyer,
+            seller: product.seller,
+            product_id,
+            quantity,
+        }
+        .publish(e);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    /// Delist product (seller only)
+    pub fn delist_product(e: &Env, seller: Address, product_id: u64) -> Result<(), Error> {
+        seller.require_auth();
+
+        let mut product = get_product(e, product_id).ok_or(Error::ProductNotFound)?;
+
+        if seller != product.seller {
+            return Err(Error::Unauthorized);
+        }
+
+        product.status = ProductStatus::Delisted;
+        set_product(e, &product);
+
+        ProductDelistedEventData {
+            seller: seller.clone(),
+        }
+        .publish(e);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    /// Delist a product whose listing has expired (callable by anyone)
+    pub fn expire_product(e: &Env, product_id: u64) -> Result<(), Error> {
+        let mut product = get_product(e, product_id).ok_or(Error::ProductNotFound)?;
+
+        if product.status == ProductStatus::Delisted || !Self::is_product_expired(e, &product) {
+            return Err(Error::InvalidProductStatus);
+        }
+
+        product.status = ProductStatus::Delisted;
+        set_product(e, &product);
+
+        ProductDelistedEventData {
+            seller: product.seller.clone(),
+        }
+        .publish(e);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    /// Relist a delisted or expired product with a new expiry (seller only)
+    ///
+    /// Fails with `OutOfStock` while the product has no stock and with
+    /// `SellerSuspended` for suspended sellers, who can't list either.
+    ///
+    /// # Arguments
+    /// * `seller` - Seller address (must be product owner)
+    /// * `product_id` - Product to relist
+    /// * `new_expiry` - New expiry timestamp (0 = never expires)
+    pub fn relist_product(
+        e: &Env,
+        seller: Address,
+        product_id: u64,
+        new_expiry: u64,
+    ) -> Result<(), Error> {
+        seller.require_auth();
+
+        let mut product = get_product(e, product_id).ok_or(Error::ProductNotFound)?;
+
+        if seller != product.seller {
+            return Err(Error::Unauthorized);
+        }
+
+        let seller_data = get_seller(e, &seller).ok_or(Error::SellerNotFound)?;
+        if seller_data.status == SellerStatus::Suspended {
+            return Err(Error::SellerSuspended);
+        }
+
+        if new_expiry != 0 && new_expiry <= e.ledger().timestamp() {
+            return Err(Error::InvalidInput);
+        }
+
+        if product.status != ProductStatus::Delisted && !Self::is_product_expired(e, &product) {
+            return Err(Error::InvalidProductStatus);
+        }
+
+        if product.stock_quantity == 0 {
+            return Err(Error::OutOfStock);
+        }
+
+        product.status = ProductStatus::Active;
+        product.expires_at = new_expiry;
+        set_product(e, &product);
+
+        ProductUpdatedEventData {
+            seller: seller.clone(),
+        }
+        .publish(e);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    /// Update product rating (seller only)
+    ///
+    /// # Arguments
+    /// * `seller` - Seller address (must be product owner)
+    /// * `product_id` - Product to rate
+    /// * `new_rating` - Rating value (0-500, where 500 = 5 stars)
+    pub fn update_product_rating(
+        e: &Env,
+        seller: Address,
+        product_id: u64,
+        new_rating: u32,
+    ) -> Result<(), Error> {
+        seller.require_auth();
+
+        let mut product = get_product(e, product_id).ok_or(Error::ProductNotFound)?;
+
+        if new_rating > MAX_RATING {
+            return Err(Error::InvalidInput);
+        }
+
+        if seller != product.seller {
+            return Err(Error::Unauthorized);
+        }
+
+        product.rating = new_rating;
+        set_product(e, &product);
+
+        QualityRatedEventData {
+            seller: seller.clone(),
+        }
+        .publish(e);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    // ========================================================================
+    // PRODUCT SEARCH & FILTERING
+    // ========================================================================
+
+    /// Get all products by seller
+    pub fn get_products_by_seller(e: &Env, seller_address: Address) -> Result<Vec<u64>, Error> {
+        if !seller_exists(e, &seller_address) {
+            return Err(Error::SellerNotFound);
+        }
+
+        Ok(get_seller_products(e, &seller_address))
+    }
+
+    /// Get all products in category
+    pub fn get_products_by_category(e: &Env, category_id: u32) -> Result<Vec<u64>, Error> {
+        if !category_exists(e, category_id) {
+            return Err(Error::CategoryNotFound);
+        }
+
+        Ok(get_category_products(e, category_id))
+    }
+
+    /// Get a page of a seller's product IDs
+    ///
+    /// # Arguments
+    /// * `seller_address` - Seller to list
+    /// * `offset` - Pagination offset
+    /// * `limit` - Maximum results to return (1-100)
+    pub fn get_products_by_seller_paged(
+        e: &Env,
+        seller_address: Address,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<u64>, Error> {
+        if !seller_exists(e, &seller_address) {
+            return Err(Error::SellerNotFound);
+        }
+
+        if limit == 0 || limit > 100 {
+            return Err(Error::InvalidInput);
+        }
+
+        Ok(get_seller_products_page(e, &seller_address, offset, limit))
+    }
+
+    /// Get a page of a category's product IDs
+    ///
+    /// # Arguments
+    /// * `category_id` - Category to list
+    /// * `offset` - Pagination offset
+    /// * `limit` - Maximum results to return (1-100)
+    pub fn get_products_by_category_paged(
+        e: &Env,
+        category_id: u32,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<u64>, Error> {
+        if !category_exists(e, category_id) {
+            return Err(Error::CategoryNotFound);
+        }
+
+        if limit == 0 || limit > 100 {
+            return Err(Error::InvalidInput);
+        }
+
+        Ok(get_category_products_page(e, category_id, offset, limit))
+    }
+
+    /// Get a category's highest rated listed products, best first
+    ///
+    /// Ties on rating are broken by purchase count.
+    ///
+    /// # Arguments
+    /// * `category_id` - Category to rank
+    /// * `limit` - Maximum results to return (1-100)
+    pub fn get_top_products_by_category(
+        e: &Env,
+        category_id: u32,
+        limit: u32,
+    ) -> Result<Vec<Product>, Error> {
+        if !category_exists(e, category_id) {
+            return Err(Error::CategoryNotFound);
+        }
+
+        if limit == 0 || limit > 100 {
+            return Err(Error::InvalidInput);
+        }
+
+        let mut results: Vec<Product> = Vec::new(e);
+
+        for product_id in get_category_products(e, category_id).iter() {
+            let product = match get_product(e, product_id) {
+                Some(product) if Self::is_listed(e, &product) => product,
+                _ => continue,
+            };
+
+            // Bounded insertion: find the first entry this product outranks
+            let position = results
+                .iter()
+                .position(|other| {
+                    (product.rating, product.purchase_count) > (other.rating, other.purchase_count)
+                })
+                .map(|i| i as u32)
+                .unwrap_or(results.len());
+
+            if position < limit {
+                results.insert(position, product);
+                if results.len() > limit {
+                    results.pop_back();
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Get a page of registered sellers in registration order
+    ///
+    /// # Arguments
+    /// * `offset` - Pagination offset
+    /// * `limit` - Maximum results to return (1-100)
+    pub fn get_sellers(e: &Env, offset: u32, limit: u32) -> Result<Vec<Address>, Error> {
+        if limit == 0 || limit > 100 {
+            return Err(Error::InvalidInput);
+        }
+
+        Ok(get_sellers_page(e, offset, limit))
+    }
+
+    /// Get a page of sellers with the given status
+    ///
+    /// # Arguments
+    /// * `status` - Seller status (0=Unverified, 1=Verified, 2=Suspended)
+    /// * `offset` - Pagination offset, counted among matching sellers
+    /// * `limit` - Maximum results to return (1-100)
+    pub fn get_sellers_by_status(
+        e: &Env,
+        status: u32,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<Address>, Error> {
+        let status = SellerStatus::from_u32(status).ok_or(Error::InvalidInput)?;
+
+        if limit == 0 || limit > 100 {
+            return Err(Error::InvalidInput);
+        }
+
+        let mut results: Vec<Address> = Vec::new(e);
+        let mut count = 0u32;
+
+        for address in get_seller_index(e).iter() {
+            if results.len() >= limit {
+                break;
+            }
+
+            if let Some(seller) = get_seller(e, &address) {
+                if seller.status == status {
+                    if count >= offset {
+                        results.push_back(address);
+                    }
+                    count += 1;
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Search non-suspended sellers whose metadata contains `query` (case-sensitive)
+    ///
+    /// # Arguments
+    /// * `query` - Substring to look for in seller metadata
+    /// * `offset` - Pagination offset, counted among matching sellers
+    /// * `limit` - Maximum results to return (1-100)
+    pub fn search_sellers(
+        e: &Env,
+        query: String,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<Seller>, Error> {
+        if query.is_empty() {
+            return Err(Error::InvalidInput);
+        }
+
+        if limit == 0 || limit > 100 {
+            return Err(Error::InvalidInput);
+        }
+
+        let needle = query.to_bytes();
+        let mut results: Vec<Seller> = Vec::new(e);
+        let mut count = 0u32;
+
+        for address in get_seller_index(e).iter() {
+            if results.len() >= limit {
+                break;
+            }
+
+            if let Some(seller) = get_seller(e, &address) {
+                if seller.status != SellerStatus::Suspended
+                    && Self::bytes_contain(&seller.metadata.to_bytes(), &needle)
+                {
+                    if count >= offset {
+                        results.push_back(seller);
+                    }
+                    count += 1;
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Browse every purchasable product (paginated, sorted within the page)
+    ///
+    /// Products are paged in listing order, then the page is sorted by `sort`.
+    ///
+    /// # Arguments
+    /// * `offset` - Pagination offset, counted among purchasable products
+    /// * `limit` - Maximum results to return (1-100)
+    /// * `sort` - 0=newest, 1=price ascending, 2=price descending, 3=rating descending;
+    ///   prices compare at their current effective (sale) price
+    pub fn get_all_products(
+        e: &Env,
+        offset: u32,
+        limit: u32,
+        sort: u32,
+    ) -> Result<Vec<Product>, Error> {
+        let config = get_config(e).ok_or(Error::NotInitialized)?;
+
+        if limit == 0 || limit > 100 || sort > 3 {
+            return Err(Error::InvalidInput);
+        }
+
+        let now = e.ledger().timestamp();
+        let mut results: Vec<Product> = Vec::new(e);
+        let mut count = 0u32;
+
+        for i in 1..=config.total_products {
+            if results.len() >= limit {
+                break;
+            }
+
+            if let Some(product) = get_product(e, i) {
+                if product.stock_quantity > 0 && Self::is_listed(e, &product) {
+                    if count >= offset {
+                        // Insertion keeps the page sorted as it fills
+                        let position = results
+                            .iter()
+                            .position(|other| Self::sorts_before(&product, &other, sort, now))
+                            .map(|p| p as u32)
+                            .unwrap_or(results.len());
+                        results.insert(position, product);
+                    }
+                    count += 1;
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Get products by price range (paginated)
+    ///
+    /// # Arguments
+    /// * `min_price` - Minimum price (inclusive)
+    /// * `max_price` - Maximum price (inclusive)
+    /// * `offset` - Pagination offset
+    /// * `limit` - Maximum results to return
+    pub fn get_products_by_price_range(
+        e: &Env,
+        min_price: u128,
+        max_price: u128,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<Product>, Error> {
+        let config = get_config(e).ok_or(Error::NotInitialized)?;
+
+        if min_price > max_price {
+            return Err(Error::InvalidInput);
+        }
+
+        if limit == 0 || limit > 100 {
+            return Err(Error::InvalidInput);
+        }
+
+        let mut results: Vec<Product> = Vec::new(e);
+        let mut count = 0u32;
+        let mut returned = 0u32;
+
+        for i in 1..=config.total_products {
+            if returned >= limit {
+                break;
+            }
+
+            if let Some(product) =
+                e.storage()
+                    .persistent()
+                    .get::<_, Product>(&StorageKey::Product(i))
+            {
+                if product.price >= min_price
+                    && product.price <= max_price
+                    && Self::is_listed(e, &product)
+                {
+                    if count >= offset {
+                        results.push_back(product);
+                        returned += 1;
+                    }
+                    count += 1;
+                }
+            }
+        }
+
+        Ok(results)
     }
 
-    /// Get products by price range (paginated)
+    /// Search listed products whose name contains `query` (case-sensitive)
     ///
     /// # Arguments
-    /// * `min_price` - Minimum price (inclusive)
-    /// * `max_price` - Maximum price (inclusive)
+    /// * `query` - Substring to look for in product names
     /// * `offset` - Pagination offset
     /// * `limit` - Maximum results to return
-    pub fn get_products_by_price_range(
+    pub fn search_products_by_name(
         e: &Env,
-        min_price: u128,
-        max_price: u128,
+        query: String,
         offset: u32,
         limit: u32,
     ) -> Result<Vec<Product>, Error> {
         let config = get_config(e).ok_or(Error::NotInitialized)?;
 
-        if min_price > max_price {
+        if query.is_empty() {
             return Err(Error::InvalidInput);
         }
 
@@ -857,6 +2042,7 @@ impl MarketX {
             return Err(Error::InvalidInput);
         }
 
+        let needle = query.to_bytes();
         let mut results: Vec<Product> = Vec::new(e);
         let mut count = 0u32;
         let mut returned = 0u32;
@@ -871,9 +2057,8 @@ impl MarketX {
                     .persistent()
                     .get::<_, Product>(&StorageKey::Product(i))
             {
-                if product.price >= min_price
-                    && product.price <= max_price
-                    && product.status == ProductStatus::Active
+                if Self::is_listed(e, &product)
+                    && Self::bytes_contain(&product.name.to_bytes(), &needle)
                 {
                     if count >= offset {
                         results.push_back(product);
@@ -901,6 +2086,104 @@ impl MarketX {
         amount: u128,
         category_id: Option<u32>,
     ) -> Result<u128, Error> {
+        let rate = Self::resolve_fee_rate(e, category_id)?;
+        Self::fee_at_rate(e, amount, rate)
+    }
+
+    /// Calculate fee for a sale by `seller`, discounted by their reputation tier
+    ///
+    /// Verified sellers get the discount configured for their 0-5 star tier, their
+    /// cached rating (see `get_seller_rating`) on a 5-point scale. The rating is
+    /// only re-read from the reputation contract once the sync interval has
+    /// passed. Without a reputation contract this is `calculate_fee`.
+    ///
+    /// # Arguments
+    /// * `amount` - Transaction amount
+    /// * `category_id` - Optional category ID for category-specific fees
+    /// * `seller` - Seller receiving the payment
+    pub fn calculate_fee_for_seller(
+        e: &Env,
+        amount: u128,
+        category_id: Option<u32>,
+        seller: Address,
+    ) -> Result<u128, Error> {
+        if get_reputation_contract(e).is_none() {
+            return Self::calculate_fee(e, amount, category_id);
+        }
+
+        let mut rate = Self::resolve_fee_rate(e, category_id)?;
+
+        let verified = get_seller(e, &seller)
+            .map(|s| s.status == SellerStatus::Verified)
+            .unwrap_or(false);
+        if verified {
+            // Same cached rating as get_seller_rating, scaled down to a 0-5 tier
+            let tier = Self::get_seller_rating(e, seller)? * MAX_TIER / MAX_RATING;
+            let discount = get_tier_discount(e, tier);
+            rate -= rate * discount / 10000;
+        }
+
+        Self::fee_at_rate(e, amount, rate)
+    }
+
+    /// Set the reputation contract used for tier fee discounts (admin only)
+    ///
+    /// Passing `None` disables tier discounts.
+    pub fn set_reputation_contract(
+        e: &Env,
+        admin: Address,
+        reputation: Option<Address>,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+
+        let config = get_config(e).ok_or(Error::NotInitialized)?;
+
+        if admin != config.admin {
+            return Err(Error::Unauthorized);
+        }
+
+        set_reputation_contract(e, &reputation);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    /// Set the fee discount for a reputation tier (admin only)
+    ///
+    /// # Arguments
+    /// * `tier` - 0-5 star tier from the reputation contract
+    /// * `discount` - Share of the fee rate waived, in basis points (e.g. 2000 = -20%)
+    pub fn set_tier_discount(
+        e: &Env,
+        admin: Address,
+        tier: u32,
+        discount: u32,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+
+        let config = get_config(e).ok_or(Error::NotInitialized)?;
+
+        if admin != config.admin {
+            return Err(Error::Unauthorized);
+        }
+
+        if discount > MAX_FEE_RATE {
+            return Err(Error::InvalidInput);
+        }
+
+        set_tier_discount(e, tier, discount);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    /// Get the fee discount for a reputation tier in basis points
+    pub fn get_tier_discount(e: &Env, tier: u32) -> u32 {
+        get_tier_discount(e, tier)
+    }
+
+    /// Fee rate for a category: override, then commission rate, then base rate
+    fn resolve_fee_rate(e: &Env, category_id: Option<u32>) -> Result<u32, Error> {
         let config = get_config(e).ok_or(Error::NotInitialized)?;
 
         let rate = if let Some(cat_id) = category_id {
@@ -918,6 +2201,11 @@ impl MarketX {
             config.base_fee_rate
         };
 
+        Ok(rate)
+    }
+
+    /// Apply a basis-point rate to an amount, honoring the absolute fee cap
+    fn fee_at_rate(e: &Env, amount: u128, rate: u32) -> Result<u128, Error> {
         // Calculate fee: amount * rate / 10000
         let fee = amount
             .checked_mul(rate as u128)
@@ -925,9 +2213,38 @@ impl MarketX {
             .checked_div(10000)
             .ok_or(Error::FeeOverflow)?;
 
+        // Clamp to the absolute ceiling, if one is configured
+        let max_fee = get_max_absolute_fee(e);
+        if max_fee > 0 && fee > max_fee {
+            return Ok(max_fee);
+        }
+
         Ok(fee)
     }
 
+    /// Set the absolute fee ceiling applied by `calculate_fee` (admin only)
+    ///
+    /// A value of 0 removes the cap.
+    pub fn set_max_absolute_fee(e: &Env, admin: Address, max_fee: u128) -> Result<(), Error> {
+        admin.require_auth();
+
+        let config = get_config(e).ok_or(Error::NotInitialized)?;
+
+        if admin != config.admin {
+            return Err(Error::Unauthorized);
+        }
+
+        set_max_absolute_fee(e, max_fee);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    /// Get the absolute fee ceiling (0 = uncapped)
+    pub fn get_max_absolute_fee(e: &Env) -> u128 {
+        get_max_absolute_fee(e)
+    }
+
     /// Record a fee collection (admin only)
     pub fn record_fee_collection(e: &Env, admin: Address, amount: u128) -> Result<(), Error> {
         admin.require_auth();
@@ -949,7 +2266,117 @@ impl MarketX {
         Ok(())
     }
 
-    /// Get total collected fees
+    /// Allow or revoke a contract (e.g. an escrow) to report fees (admin only)
+    pub fn set_fee_reporter(
+        e: &Env,
+        admin: Address,
+        reporter: Address,
+        allowed: bool,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+
+        let config = get_config(e).ok_or(Error::NotInitialized)?;
+
+        if admin != config.admin {
+            return Err(Error::Unauthorized);
+        }
+
+        set_fee_reporter(e, &reporter, allowed);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    /// Whether `reporter` may call `record_fee_collection_for_asset`
+    pub fn is_fee_reporter(e: &Env, reporter: Address) -> bool {
+        is_fee_reporter(e, &reporter)
+    }
+
+    /// Record a fee collection in a specific asset (admin or registered fee reporter)
+    ///
+    /// Also counts towards the all-asset total reported by `get_total_fees`.
+    pub fn record_fee_collection_for_asset(
+        e: &Env,
+        caller: Address,
+        asset: Address,
+        amount: u128,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+
+        let config = get_config(e).ok_or(Error::NotInitialized)?;
+
+        if caller != config.admin && !is_fee_reporter(e, &caller) {
+            return Err(Error::Unauthorized);
+        }
+
+        set_asset_fees(e, &asset, get_asset_fees(e, &asset).saturating_add(amount));
+        add_fees(e, amount);
+
+        FeeCollectedEventData {
+            admin: caller.clone(),
+        }
+        .publish(e);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    /// Get collected fees recorded for one asset
+    pub fn get_total_fees_for_asset(e: &Env, asset: Address) -> Result<u128, Error> {
+        let _config = get_config(e).ok_or(Error::NotInitialized)?;
+        Ok(get_asset_fees(e, &asset))
+    }
+
+    /// Transfer collected fees out of the contract (admin only)
+    ///
+    /// Only fees recorded in `token` via `record_fee_collection_for_asset` can
+    /// be withdrawn in it, so other balances held by the contract (e.g. seller
+    /// bonds) stay untouched. The withdrawal is deducted from `token`'s
+    /// per-asset total as well as the all-asset total.
+    ///
+    /// # Arguments
+    /// * `token` - Token the fees are held in
+    /// * `to` - Recipient, e.g. the treasury
+    /// * `amount` - Amount to withdraw
+    ///
+    /// # Errors
+    /// * `Error::InsufficientBalance` - If amount exceeds the fees recorded in `token`
+    pub fn withdraw_fees(
+        e: &Env,
+        admin: Address,
+        token: Address,
+        to: Address,
+        amount: u128,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+
+        let config = get_config(e).ok_or(Error::NotInitialized)?;
+
+        if admin != config.admin {
+            return Err(Error::Unauthorized);
+        }
+
+        if amount == 0 {
+            return Err(Error::InvalidInput);
+        }
+
+        let asset_fees = get_asset_fees(e, &token);
+        if amount > asset_fees {
+            return Err(Error::InsufficientBalance);
+        }
+
+        sub_fees(e, amount);
+        set_asset_fees(e, &token, asset_fees - amount);
+        Self::transfer_token(e, &token, &e.current_contract_address(), &to, amount)?;
+
+        FeesWithdrawnEventData { to, amount }.publish(e);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    /// Get total collected fees across all assets, including unattributed fees
+    /// recorded via `record_fee_collection`
     pub fn get_total_fees(e: &Env) -> Result<u128, Error> {
         let _config = get_config(e).ok_or(Error::NotInitialized)?;
         Ok(get_total_fees(e))
@@ -984,6 +2411,39 @@ impl MarketX {
         Ok(())
     }
 
+    /// Set the asset a category's listings are priced against by default (admin only)
+    ///
+    /// `add_product_with_validation` checks prices against it when the seller
+    /// doesn't name a payment asset. `None` clears it.
+    pub fn set_category_reference_asset(
+        e: &Env,
+        admin: Address,
+        category_id: u32,
+        asset: Option<Address>,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+
+        let config = get_config(e).ok_or(Error::NotInitialized)?;
+
+        if admin != config.admin {
+            return Err(Error::Unauthorized);
+        }
+
+        if !category_exists(e, category_id) {
+            return Err(Error::CategoryNotFound);
+        }
+
+        set_category_reference_asset(e, category_id, &asset);
+
+        Self::extend_instance_ttl(e);
+        Ok(())
+    }
+
+    /// Get a category's reference asset, if one is set
+    pub fn get_category_reference_asset(e: &Env, category_id: u32) -> Option<Address> {
+        get_category_reference_asset(e, category_id)
+    }
+
     // ========================================================================
     // STATISTICS & INFO
     // ========================================================================
@@ -1036,6 +2496,7 @@ impl MarketX {
             price_tolerance,
             update_frequency,
             is_enabled: true,
+            backup_stellar_oracle: None,
         };
 
         set_oracle_config(e, &oracle_config);
@@ -1080,7 +2541,7 @@ impl MarketX {
     /// Update a specific oracle address (admin only)
     ///
     /// # Arguments
-    /// * `oracle_type` - 0 for Stellar oracle, 1 for External oracle
+    /// * `oracle_type` - 0 for Stellar oracle, 1 for External oracle, 2 for backup Stellar oracle
     /// * `new_address` - New oracle address
     pub fn update_oracle_address(
         e: &Env,
@@ -1101,6 +2562,7 @@ impl MarketX {
         match oracle_type {
             0 => oracle_config.stellar_oracle = new_address.clone(),
             1 => oracle_config.external_oracle = new_address.clone(),
+            2 => oracle_config.backup_stellar_oracle = Some(new_address.clone()),
             _ => return Err(Error::InvalidInput),
         }
 
@@ -1141,6 +2603,21 @@ impl MarketX {
         Ok((price_data.price, price_data.timestamp))
     }
 
+    /// Get the current price for a Stellar asset, cross-checked against the backup oracle
+    ///
+    /// # Arguments
+    /// * `asset_address` - Address of the Stellar token
+    ///
+    /// # Returns
+    /// * Tuple of (price, timestamp)
+    pub fn get_stellar_asset_price_robust(
+        e: &Env,
+        asset_address: Address,
+    ) -> Result<(i128, u64), Error> {
+        let price_data = OracleService::get_stellar_asset_price_robust(e, &asset_address)?;
+        Ok((price_data.price, price_data.timestamp))
+    }
+
     /// Get the current price for an external asset (BTC, ETH, etc.)
     ///
     /// # Arguments
@@ -1190,6 +2667,25 @@ impl MarketX {
         OracleService::convert_price(e, amount, &from_asset, &to_asset)
     }
 
+    /// Convert an amount from one asset to another, preferring the oracle's
+    /// direct cross price over dividing both assets' base prices
+    ///
+    /// # Arguments
+    /// * `amount` - Amount to convert
+    /// * `from_asset` - Source asset address
+    /// * `to_asset` - Target asset address
+    ///
+    /// # Returns
+    /// * Converted amount
+    pub fn convert_price_cross(
+        e: &Env,
+        amount: i128,
+        from_asset: Address,
+        to_asset: Address,
+    ) -> Result<i128, Error> {
+        OracleService::convert_price_cross(e, amount, &from_asset, &to_asset)
+    }
+
     /// Get historical prices for an asset
     ///
     /// # Arguments
@@ -1218,6 +2714,27 @@ impl MarketX {
         Ok(result)
     }
 
+    /// Get historical price records for an asset, including each record's source
+    ///
+    /// # Arguments
+    /// * `asset_address` - Address of the asset
+    /// * `limit` - Maximum number of records to return
+    ///
+    /// # Returns
+    /// * The most recent `limit` records, oldest first
+    pub fn get_price_history_full(
+        e: &Env,
+        asset_address: Address,
+        limit: u32,
+    ) -> Result<Vec<PriceRecord>, Error> {
+        let history = crate::storage::get_price_history(e, &asset_address);
+
+        let len = history.len();
+        let count = core::cmp::min(limit, len);
+
+        Ok(history.slice(len - count..len))
+    }
+
     /// Get oracle status and last update time
     ///
     /// # Returns
@@ -1263,6 +2780,96 @@ impl MarketX {
     // INTERNAL HELPERS
     // ========================================================================
 
+    /// Emit a low-stock alert when stock drops from at/above the product's
+    /// threshold to below it, so each crossing fires exactly once.
+    fn emit_low_stock_if_crossed(e: &Env, product_id: u64, previous: u64, current: u64) {
+        let threshold = get_low_stock_threshold(e, product_id);
+
+        if threshold > 0 && previous >= threshold && current < threshold {
+            LowStockEventData {
+                product_id,
+                remaining: current,
+            }
+            .publish(e);
+        }
+    }
+
+    /// Whether a product should appear in buyer-facing listings and searches.
+    fn is_listed(e: &Env, product: &Product) -> bool {
+        product.status == ProductStatus::Active
+            && !Self::is_product_expired(e, product)
+            && !Self::is_seller_on_vacation(e, &product.seller)
+    }
+
+    /// Whether `a` belongs strictly before `b` under a `get_all_products` sort key.
+    fn sorts_before(a: &Product, b: &Product, sort: u32, now: u64) -> bool {
+        match sort {
+            0 => (a.created_at, a.id) > (b.created_at, b.id),
+            1 => a.effective_price(now) < b.effective_price(now),
+            2 => a.effective_price(now) > b.effective_price(now),
+            _ => a.rating > b.rating,
+        }
+    }
+
+    /// Naive substring match over raw bytes.
+    fn bytes_contain(haystack: &Bytes, needle: &Bytes) -> bool {
+        if needle.len() > haystack.len() {
+            return false;
+        }
+        (0..=haystack.len() - needle.len())
+            .any(|i| haystack.slice(i..i + needle.len()) == *needle)
+    }
+
+    /// Whether the product's listing has passed its expiry.
+    fn is_product_expired(e: &Env, product: &Product) -> bool {
+        product.expires_at != 0 && e.ledger().timestamp() >= product.expires_at
+    }
+
+    /// Whether the seller has paused their storefront.
+    fn is_seller_on_vacation(e: &Env, seller: &Address) -> bool {
+        get_seller(e, seller)
+            .map(|s| s.on_vacation)
+            .unwrap_or(false)
+    }
+
+    /// Pull the seller's rating from the reputation contract into the cache.
+    fn refresh_seller_rating(e: &Env, seller: &mut Seller) -> Result<(), Error> {
+        let reputation = get_reputation_contract(e).ok_or(Error::ReputationNotConfigured)?;
+
+        let rating = ReputationClient::new(e, &reputation)
+            .get_score_scaled(&seller.address, &MAX_RATING)
+            .min(MAX_RATING);
+        seller.rating = rating;
+        seller.rating_synced_at = e.ledger().timestamp();
+        set_seller(e, seller);
+        Ok(())
+    }
+
+    /// Reject a price change while the product is still inside its price update cooldown.
+    fn ensure_price_change_allowed(e: &Env, product: &Product) -> Result<(), Error> {
+        let cooldown = get_price_update_cooldown(e);
+        if cooldown > 0
+            && product.last_price_change > 0
+            && e.ledger().timestamp() < product.last_price_change.saturating_add(cooldown)
+        {
+            return Err(Error::PriceUpdateTooSoon);
+        }
+        Ok(())
+    }
+
+    /// Transfer `amount` of `token`, rejecting amounts that don't fit the token interface.
+    fn transfer_token(
+        e: &Env,
+        token: &Address,
+        from: &Address,
+        to: &Address,
+        amount: u128,
+    ) -> Result<(), Error> {
+        let amount = i128::try_from(amount).map_err(|_| Error::InvalidInput)?;
+        token::Client::new(e, token).transfer(from, to, &amount);
+        Ok(())
+    }
+
     /// Extend the TTL of instance storage.
     /// Called internally during state-changing operations.
     fn extend_instance_ttl(e: &Env) {
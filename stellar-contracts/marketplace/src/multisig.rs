@@ -0,0 +1,12 @@
+use soroban_sdk::{contractclient, symbol_short, Address, Env, Symbol};
+
+/// Action the access-control proposal must be created for; mirrors its `ACTION_TRANSFER_ADMIN`
+pub const ACTION_TRANSFER_ADMIN: Symbol = symbol_short!("xfer_adm");
+
+/// Approval interface exposed by the access-control contract's multisig.
+#[allow(dead_code)]
+#[contractclient(name = "MultisigClient")]
+pub trait AdminMultisig {
+    /// Whether the proposal for `action` against `target` has reached its threshold
+    fn is_proposal_approved(e: Env, proposal_id: u64, action: Symbol, target: Address) -> bool;
+}
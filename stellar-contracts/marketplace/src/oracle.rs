@@ -175,6 +175,89 @@ impl OracleService {
         Ok(price_data)
     }
 
+    /// Fetches a Stellar asset price from both the primary and backup oracles.
+    /// Without a backup oracle this is `get_stellar_asset_price`.
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    /// * `asset_address` - Address of the Stellar token
+    ///
+    /// # Returns
+    /// * `Ok(PriceData)` - The fresh price (the primary's when both are fresh)
+    /// * `Err(OraclePriceManipulated)` - Both fresh but diverging beyond the deviation threshold
+    /// * `Err(OraclePriceStale)` - Neither oracle has a fresh price
+    pub fn get_stellar_asset_price_robust(
+        e: &Env,
+        asset_address: &Address,
+    ) -> Result<PriceData, Error> {
+        let config = get_oracle_config(e).ok_or(Error::OracleNotConfigured)?;
+
+        if !config.is_enabled {
+            return Err(Error::OracleNotConfigured);
+        }
+
+        let backup_oracle = match &config.backup_stellar_oracle {
+            Some(backup) => backup,
+            None => return Self::get_stellar_asset_price(e, asset_address),
+        };
+
+        let current_time = e.ledger().timestamp();
+        let price_data = Self::select_robust_price(
+            ReflectorHelper::get_stellar_asset_price(e, &config.stellar_oracle, asset_address),
+            ReflectorHelper::get_stellar_asset_price(e, backup_oracle, asset_address),
+            current_time,
+            config.staleness_threshold,
+            config.price_deviation_threshold,
+        )?;
+
+        let record = PriceRecord {
+            price: price_data.price,
+            timestamp: price_data.timestamp,
+            source: PriceSource::Oracle,
+        };
+        add_price_record(e, asset_address, &record);
+        set_last_price_update(e, current_time);
+
+        Ok(price_data)
+    }
+
+    /// Picks a price from two oracle readings, discarding stale ones.
+    ///
+    /// # Arguments
+    /// * `primary` - Reading from the primary oracle
+    /// * `backup` - Reading from the backup oracle
+    /// * `current_timestamp` - Current ledger timestamp
+    /// * `staleness_threshold` - Maximum allowed age in seconds
+    /// * `deviation_bps` - Maximum allowed divergence between fresh readings (0 disables the check)
+    pub fn select_robust_price(
+        primary: Option<PriceData>,
+        backup: Option<PriceData>,
+        current_timestamp: u64,
+        staleness_threshold: u64,
+        deviation_bps: u32,
+    ) -> Result<PriceData, Error> {
+        let any_reading = primary.is_some() || backup.is_some();
+        let is_fresh = |p: &PriceData| {
+            !Self::is_price_stale(p.timestamp, current_timestamp, staleness_threshold)
+        };
+        let primary = primary.filter(is_fresh);
+        let backup = backup.filter(is_fresh);
+
+        match (primary, backup) {
+            (Some(primary), Some(backup)) => {
+                if deviation_bps > 0
+                    && Self::detect_manipulation(primary.price, backup.price, deviation_bps)
+                {
+                    return Err(Error::OraclePriceManipulated);
+                }
+                Ok(primary)
+            }
+            (Some(price), None) | (None, Some(price)) => Ok(price),
+            (None, None) if any_reading => Err(Error::OraclePriceStale),
+            (None, None) => Err(Error::OraclePriceUnavailable),
+        }
+    }
+
     /// Fetches the current price for an external asset (BTC, ETH, etc.).
     /// Falls back to cached price if oracle price is stale but cache is fresh.
     ///
@@ -310,6 +393,56 @@ impl OracleService {
         Ok(result)
     }
 
+    /// Converts an amount between assets using the oracle's direct cross price,
+    /// falling back to `convert_price` when no fresh cross quote exists.
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    /// * `amount` - Amount to convert
+    /// * `from_asset` - Source asset address
+    /// * `to_asset` - Target asset address
+    ///
+    /// # Returns
+    /// * `Ok(i128)` - Converted amount
+    /// * `Err` - If neither route yields a price
+    pub fn convert_price_cross(
+        e: &Env,
+        amount: i128,
+        from_asset: &Address,
+        to_asset: &Address,
+    ) -> Result<i128, Error> {
+        let config = get_oracle_config(e).ok_or(Error::OracleNotConfigured)?;
+
+        if !config.is_enabled {
+            return Err(Error::OracleNotConfigured);
+        }
+
+        let now = e.ledger().timestamp();
+        let cross =
+            ReflectorHelper::get_cross_price(e, &config.stellar_oracle, from_asset, to_asset)
+                .filter(|p| {
+                    p.price > 0
+                        && !Self::is_price_stale(p.timestamp, now, config.staleness_threshold)
+                });
+
+        let cross = match cross {
+            Some(cross) => cross,
+            None => return Self::convert_price(e, amount, from_asset, to_asset),
+        };
+
+        let scale = 10i128
+            .checked_pow(ReflectorHelper::get_decimals(e, &config.stellar_oracle))
+            .ok_or(Error::FeeOverflow)?;
+
+        let result = amount
+            .checked_mul(cross.price)
+            .ok_or(Error::FeeOverflow)?
+            .checked_div(scale)
+            .ok_or(Error::FeeOverflow)?;
+
+        Ok(result)
+    }
+
     /// Gets oracle configuration and last update timestamp.
     ///
     /// # Arguments
@@ -374,4 +507,46 @@ mod tests {
         assert!(OracleService::validate_product_price(1000, 700, 2000).is_err());
         assert!(OracleService::validate_product_price(0, 1000, 2000).is_ok());
     }
+
+    fn price(price: i128, timestamp: u64) -> Option<PriceData> {
+        Some(PriceData { price, timestamp })
+    }
+
+    #[test]
+    fn test_select_robust_price_agree() {
+        let selected =
+            OracleService::select_robust_price(price(100, 900), price(104, 950), 1000, 300, 1000)
+                .unwrap();
+        assert_eq!(selected.price, 100);
+    }
+
+    #[test]
+    fn test_select_robust_price_disagree() {
+        assert_eq!(
+            OracleService::select_robust_price(price(100, 900), price(120, 950), 1000, 300, 1000),
+            Err(Error::OraclePriceManipulated)
+        );
+    }
+
+    #[test]
+    fn test_select_robust_price_one_stale() {
+        // A stale reading is ignored even if it diverges
+        let selected =
+            OracleService::select_robust_price(price(100, 100), price(150, 950), 1000, 300, 1000)
+                .unwrap();
+        assert_eq!(selected.price, 150);
+
+        let selected =
+            OracleService::select_robust_price(price(100, 900), None, 1000, 300, 1000).unwrap();
+        assert_eq!(selected.price, 100);
+
+        assert_eq!(
+            OracleService::select_robust_price(price(100, 100), price(150, 200), 1000, 300, 1000),
+            Err(Error::OraclePriceStale)
+        );
+        assert_eq!(
+            OracleService::select_robust_price(None, None, 1000, 300, 1000),
+            Err(Error::OraclePriceUnavailable)
+        );
+    }
 }
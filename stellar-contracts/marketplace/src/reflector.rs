@@ -127,6 +127,35 @@ impl ReflectorHelper {
         client.lastprice(&symbol_asset(symbol.clone()))
     }
 
+    /// Fetches the last cross price of `base_asset` quoted in `quote_asset`.
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    /// * `oracle_address` - Address of the Reflector oracle contract
+    /// * `base_asset` - Address of the Stellar token being priced
+    /// * `quote_asset` - Address of the Stellar token the price is expressed in
+    ///
+    /// # Returns
+    /// * `Option<PriceData>` - Cross price and timestamp if available
+    pub fn get_cross_price(
+        e: &Env,
+        oracle_address: &Address,
+        base_asset: &Address,
+        quote_asset: &Address,
+    ) -> Option<PriceData> {
+        let client = ReflectorClient::new(e, oracle_address);
+        client.x_last_price(
+            &stellar_asset(base_asset.clone()),
+            &stellar_asset(quote_asset.clone()),
+        )
+    }
+
+    /// Fetches the number of decimals the oracle's prices are scaled by.
+    pub fn get_decimals(e: &Env, oracle_address: &Address) -> u32 {
+        let client = ReflectorClient::new(e, oracle_address);
+        client.decimals()
+    }
+
     /// Fetches the TWAP (Time-Weighted Average Price) for a Stellar asset.
     ///
     /// # Arguments
@@ -0,0 +1,10 @@
+use soroban_sdk::{contractclient, Address, Env};
+
+/// Score lookup exposed by the reputation contract.
+/// A score on the 5-point scale doubles as the seller's fee-discount tier.
+#[allow(dead_code)]
+#[contractclient(name = "ReputationClient")]
+pub trait SellerReputation {
+    /// Average rating converted to a 0..=`scale` range
+    fn get_score_scaled(e: Env, subject: Address, scale: u32) -> u32;
+}
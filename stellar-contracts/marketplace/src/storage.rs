@@ -1,4 +1,4 @@
-use soroban_sdk::{Address, Env, Symbol, Vec};
+use soroban_sdk::{Address, Env, IntoVal, Symbol, TryFromVal, Val, Vec};
 
 use crate::types::{
     Category, MarketplaceConfig, OracleConfig, PriceRecord, Product, Seller, StorageKey,
@@ -61,6 +61,52 @@ pub fn seller_exists(e: &Env, seller_address: &Address) -> bool {
     e.storage().persistent().has(&key)
 }
 
+/// Removes the seller record and drops it from the seller index
+pub fn remove_seller(e: &Env, seller_address: &Address) {
+    let key = StorageKey::Seller(seller_address.clone());
+    e.storage().persistent().remove(&key);
+
+    let mut sellers = get_seller_index(e);
+    if let Some(i) = sellers.first_index_of(seller_address) {
+        sellers.remove(i);
+        set_seller_index(e, &sellers);
+    }
+}
+
+/// Every registered seller in registration order
+pub fn get_seller_index(e: &Env) -> Vec<Address> {
+    let key = StorageKey::SellerIndex;
+    let sellers = e
+        .storage()
+        .persistent()
+        .get::<_, Vec<Address>>(&key)
+        .unwrap_or(Vec::new(e));
+    if !sellers.is_empty() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
+    }
+    sellers
+}
+
+fn set_seller_index(e: &Env, sellers: &Vec<Address>) {
+    let key = StorageKey::SellerIndex;
+    e.storage().persistent().set(&key, sellers);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
+}
+
+pub fn add_seller_to_index(e: &Env, seller_address: &Address) {
+    let mut sellers = get_seller_index(e);
+    sellers.push_back(seller_address.clone());
+    set_seller_index(e, &sellers);
+}
+
+pub fn get_sellers_page(e: &Env, offset: u32, limit: u32) -> Vec<Address> {
+    page_of(e, &get_seller_index(e), offset, limit)
+}
+
 pub fn get_product(e: &Env, product_id: u64) -> Option<Product> {
     let key = StorageKey::Product(product_id);
     let product = e.storage().persistent().get::<_, Product>(&key);
@@ -129,6 +175,15 @@ pub fn add_seller_product(e: &Env, seller_address: &Address, product_id: u64) {
         .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
 }
 
+pub fn get_seller_products_page(
+    e: &Env,
+    seller_address: &Address,
+    offset: u32,
+    limit: u32,
+) -> Vec<u64> {
+    page_of(e, &get_seller_products(e, seller_address), offset, limit)
+}
+
 pub fn get_category_products(e: &Env, category_id: u32) -> Vec<u64> {
     let key = StorageKey::CategoryProducts(category_id);
     let products = e
@@ -144,6 +199,27 @@ pub fn get_category_products(e: &Env, category_id: u32) -> Vec<u64> {
     products
 }
 
+pub fn get_category_products_page(
+    e: &Env,
+    category_id: u32,
+    offset: u32,
+    limit: u32,
+) -> Vec<u64> {
+    page_of(e, &get_category_products(e, category_id), offset, limit)
+}
+
+/// Window `[offset, offset + limit)` of an index, empty past the end
+fn page_of<T>(e: &Env, ids: &Vec<T>, offset: u32, limit: u32) -> Vec<T>
+where
+    T: IntoVal<Env, Val> + TryFromVal<Env, Val>,
+{
+    let end = offset.saturating_add(limit).min(ids.len());
+    if offset >= end {
+        return Vec::new(e);
+    }
+    ids.slice(offset..end)
+}
+
 pub fn add_category_product(e: &Env, category_id: u32, product_id: u64) {
     let key = StorageKey::CategoryProducts(category_id);
     let mut products = get_category_products(e, category_id);
@@ -175,6 +251,34 @@ pub fn add_fees(e: &Env, amount: u128) {
         .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
 }
 
+pub fn sub_fees(e: &Env, amount: u128) {
+    let key = StorageKey::FeesCollected;
+    let fees = get_total_fees(e).saturating_sub(amount);
+    e.storage().persistent().set(&key, &fees);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
+}
+
+pub fn get_asset_fees(e: &Env, asset: &Address) -> u128 {
+    let key = StorageKey::FeesCollectedByAsset(asset.clone());
+    let fees = e.storage().persistent().get::<_, u128>(&key).unwrap_or(0);
+    if fees > 0 {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
+    }
+    fees
+}
+
+pub fn set_asset_fees(e: &Env, asset: &Address, fees: u128) {
+    let key = StorageKey::FeesCollectedByAsset(asset.clone());
+    e.storage().persistent().set(&key, &fees);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
+}
+
 pub fn get_next_product_id(e: &Env) -> u64 {
     let key = StorageKey::ProductCounter;
     let counter = e.storage().persistent().get::<_, u64>(&key).unwrap_or(0);
@@ -195,6 +299,55 @@ pub fn increment_product_counter(e: &Env) {
         .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
 }
 
+/// Next unused category ID after the last auto-assigned one
+pub fn get_next_category_id(e: &Env) -> u32 {
+    let mut id = e
+        .storage()
+        .persistent()
+        .get::<_, u32>(&StorageKey::CategoryCounter)
+        .unwrap_or(0)
+        + 1;
+    // Skip IDs already taken through explicit-ID creation
+    while category_exists(e, id) {
+        id += 1;
+    }
+    id
+}
+
+pub fn set_category_counter(e: &Env, id: u32) {
+    let key = StorageKey::CategoryCounter;
+    e.storage().persistent().set(&key, &id);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
+}
+
+/// Every category ID in creation order
+pub fn get_category_index(e: &Env) -> Vec<u32> {
+    let key = StorageKey::CategoryIndex;
+    let ids = e
+        .storage()
+        .persistent()
+        .get::<_, Vec<u32>>(&key)
+        .unwrap_or(Vec::new(e));
+    if !ids.is_empty() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
+    }
+    ids
+}
+
+pub fn add_category_to_index(e: &Env, category_id: u32) {
+    let key = StorageKey::CategoryIndex;
+    let mut ids = get_category_index(e);
+    ids.push_back(category_id);
+    e.storage().persistent().set(&key, &ids);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
+}
+
 pub fn get_category_fee_rate(e: &Env, category_id: u32) -> Option<u32> {
     let key = StorageKey::CategoryFeeRate(category_id);
     let rate = e.storage().persistent().get::<_, u32>(&key);
@@ -214,6 +367,48 @@ pub fn set_category_fee_rate(e: &Env, category_id: u32, rate: u32) {
         .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
 }
 
+pub fn get_category_reference_asset(e: &Env, category_id: u32) -> Option<Address> {
+    let key = StorageKey::CategoryReferenceAsset(category_id);
+    let asset = e.storage().persistent().get::<_, Address>(&key);
+    if asset.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
+    }
+    asset
+}
+
+pub fn set_category_reference_asset(e: &Env, category_id: u32, asset: &Option<Address>) {
+    let key = StorageKey::CategoryReferenceAsset(category_id);
+    match asset {
+        Some(asset) => {
+            e.storage().persistent().set(&key, asset);
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
+        }
+        None => e.storage().persistent().remove(&key),
+    }
+}
+
+pub fn is_fee_reporter(e: &Env, reporter: &Address) -> bool {
+    e.storage()
+        .persistent()
+        .has(&StorageKey::FeeReporter(reporter.clone()))
+}
+
+pub fn set_fee_reporter(e: &Env, reporter: &Address, allowed: bool) {
+    let key = StorageKey::FeeReporter(reporter.clone());
+    if allowed {
+        e.storage().persistent().set(&key, &true);
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
+    } else {
+        e.storage().persistent().remove(&key);
+    }
+}
+
 pub fn get_oracle_config(e: &Env) -> Option<OracleConfig> {
     let key = StorageKey::OracleConfig;
     let config = e.storage().persistent().get::<_, OracleConfig>(&key);
@@ -319,3 +514,170 @@ pub fn set_last_price_update(e: &Env, timestamp: u64) {
         .persistent()
         .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
 }
+
+pub fn get_low_stock_threshold(e: &Env, product_id: u64) -> u64 {
+    let key = StorageKey::LowStockThreshold(product_id);
+    let threshold = e.storage().persistent().get::<_, u64>(&key).unwrap_or(0);
+    if threshold > 0 {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
+    }
+    threshold
+}
+
+pub fn set_low_stock_threshold(e: &Env, product_id: u64, threshold: u64) {
+    let key = StorageKey::LowStockThreshold(product_id);
+    e.storage().persistent().set(&key, &threshold);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
+}
+
+pub fn get_max_absolute_fee(e: &Env) -> u128 {
+    let key = StorageKey::MaxAbsoluteFee;
+    let max_fee = e.storage().persistent().get::<_, u128>(&key).unwrap_or(0);
+    if max_fee > 0 {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
+    }
+    max_fee
+}
+
+pub fn set_max_absolute_fee(e: &Env, max_fee: u128) {
+    let key = StorageKey::MaxAbsoluteFee;
+    e.storage().persistent().set(&key, &max_fee);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
+}
+
+pub fn get_admin_multisig(e: &Env) -> Option<Address> {
+    e.storage()
+        .instance()
+        .get::<_, Address>(&StorageKey::AdminMultisig)
+}
+
+pub fn set_admin_multisig(e: &Env, multisig: &Option<Address>) {
+    match multisig {
+        Some(address) => e
+            .storage()
+            .instance()
+            .set(&StorageKey::AdminMultisig, address),
+        None => e.storage().instance().remove(&StorageKey::AdminMultisig),
+    }
+}
+
+pub fn is_admin_proposal_consumed(e: &Env, proposal_id: u64) -> bool {
+    e.storage()
+        .persistent()
+        .has(&StorageKey::ConsumedAdminProposal(proposal_id))
+}
+
+pub fn consume_admin_proposal(e: &Env, proposal_id: u64) {
+    let key = StorageKey::ConsumedAdminProposal(proposal_id);
+    e.storage().persistent().set(&key, &true);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
+}
+
+pub fn get_pending_admin(e: &Env) -> Option<Address> {
+    e.storage()
+        .instance()
+        .get::<_, Address>(&StorageKey::PendingAdmin)
+}
+
+pub fn set_pending_admin(e: &Env, pending: &Address) {
+    e.storage()
+        .instance()
+        .set(&StorageKey::PendingAdmin, pending);
+}
+
+pub fn remove_pending_admin(e: &Env) {
+    e.storage().instance().remove(&StorageKey::PendingAdmin);
+}
+
+pub fn get_reputation_contract(e: &Env) -> Option<Address> {
+    e.storage()
+        .instance()
+        .get::<_, Address>(&StorageKey::ReputationContract)
+}
+
+pub fn set_reputation_contract(e: &Env, reputation: &Option<Address>) {
+    match reputation {
+        Some(address) => e
+            .storage()
+            .instance()
+            .set(&StorageKey::ReputationContract, address),
+        None => e
+            .storage()
+            .instance()
+            .remove(&StorageKey::ReputationContract),
+    }
+}
+
+pub fn get_tier_discount(e: &Env, tier: u32) -> u32 {
+    let key = StorageKey::TierDiscount(tier);
+    let discount = e.storage().persistent().get::<_, u32>(&key).unwrap_or(0);
+    if discount > 0 {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
+    }
+    discount
+}
+
+pub fn set_tier_discount(e: &Env, tier: u32, discount: u32) {
+    let key = StorageKey::TierDiscount(tier);
+    e.storage().persistent().set(&key, &discount);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_AMOUNT);
+}
+
+/// Bond token and amount charged at seller registration, if enabled
+pub fn get_seller_bond(e: &Env) -> Option<(Address, u128)> {
+    e.storage()
+        .instance()
+        .get::<_, (Address, u128)>(&StorageKey::SellerBond)
+}
+
+pub fn set_seller_bond(e: &Env, token: &Address, amount: u128) {
+    if amount == 0 {
+        e.storage().instance().remove(&StorageKey::SellerBond);
+    } else {
+        e.storage()
+            .instance()
+            .set(&StorageKey::SellerBond, &(token.clone(), amount));
+    }
+}
+
+/// Minimum seconds between two price changes on a product; 0 disables the cooldown
+pub fn get_price_update_cooldown(e: &Env) -> u64 {
+    e.storage()
+        .instance()
+        .get::<_, u64>(&StorageKey::PriceUpdateCooldown)
+        .unwrap_or(0)
+}
+
+pub fn set_price_update_cooldown(e: &Env, cooldown: u64) {
+    e.storage()
+        .instance()
+        .set(&StorageKey::PriceUpdateCooldown, &cooldown);
+}
+
+/// Seconds a synced seller rating stays fresh; 0 refreshes on every gated read
+pub fn get_rating_sync_interval(e: &Env) -> u64 {
+    e.storage()
+        .instance()
+        .get::<_, u64>(&StorageKey::RatingSyncInterval)
+        .unwrap_or(0)
+}
+
+pub fn set_rating_sync_interval(e: &Env, interval: u64) {
+    e.storage()
+        .instance()
+        .set(&StorageKey::RatingSyncInterval, &interval);
+}
@@ -1,8 +1,14 @@
 #![cfg(test)]
 
-use soroban_sdk::{testutils::Address as _, Address, Env, String};
+use soroban_sdk::{
+    testutils::{Address as _, Events, Ledger},
+    contract, contractimpl, symbol_short, vec, Address, Env, Event, String, Symbol, Vec,
+};
 
 use crate::oracle::OracleService;
+use crate::errors::Error;
+use crate::events::LowStockEventData;
+use crate::multisig::ACTION_TRANSFER_ADMIN;
 use crate::types::*;
 use crate::{MarketX, MarketXClient};
 
@@ -20,6 +26,34 @@ fn initialize_marketplace<'a>(e: &'a Env, admin: &Address) -> MarketXClient<'a>
     client
 }
 
+/// Creates category 1, a verified seller, and one product with the given stock.
+fn setup_product(e: &Env, client: &MarketXClient, admin: &Address, stock: u64) -> (Address, u64) {
+    client.create_category(
+        admin,
+        &1,
+        &String::from_str(e, "Electronics"),
+        &String::from_str(e, "Electronic products"),
+        &300,
+    );
+
+    let seller = Address::generate(e);
+    client.register_seller(&seller, &String::from_str(e, "Test seller"));
+    client.verify_seller(admin, &seller);
+
+    let product_id = client.add_product(
+        &seller,
+        &String::from_str(e, "Laptop"),
+        &String::from_str(e, "High performance laptop"),
+        &1,
+        &100_000_000,
+        &stock,
+        &String::from_str(e, "{}"),
+        &0,
+    );
+
+    (seller, product_id)
+}
+
 #[test]
 fn test_initialize() {
     let (e, admin) = setup_env();
@@ -270,6 +304,7 @@ fn test_add_product() {
         &100_000_000,
         &10,
         &product_meta,
+        &0,
     );
     assert_eq!(result, 1);
 }
@@ -300,6 +335,7 @@ fn test_add_product_seller_not_verified() {
         &100_000_000,
         &10,
         &product_meta,
+        &0,
     );
 }
 
@@ -326,6 +362,7 @@ fn test_add_product_invalid_category() {
         &100_000_000,
         &10,
         &product_meta,
+        &0,
     );
 }
 
@@ -354,6 +391,7 @@ fn test_get_product() {
         &100_000_000,
         &10,
         &product_meta,
+        &0,
     );
 
     let product = client.get_product(&product_id);
@@ -387,6 +425,7 @@ fn test_update_product() {
         &100_000_000,
         &10,
         &product_meta,
+        &0,
     );
 
     client.update_product(&seller, &product_id, &150_000_000, &5, &0);
@@ -421,6 +460,7 @@ fn test_delist_product() {
         &100_000_000,
         &10,
         &product_meta,
+        &0,
     );
 
     client.delist_product(&seller, &product_id);
@@ -460,6 +500,24 @@ fn test_calculate_fee_zero_amount() {
     assert_eq!(fee, 0);
 }
 
+#[test]
+fn test_calculate_fee_absolute_cap() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+
+    client.set_max_absolute_fee(&admin, &25_000);
+    assert_eq!(client.get_max_absolute_fee(), 25_000);
+
+    // Just below, at and above the cap at 2.5%
+    assert_eq!(client.calculate_fee(&999_960, &None), 24_999);
+    assert_eq!(client.calculate_fee(&1_000_000, &None), 25_000);
+    assert_eq!(client.calculate_fee(&2_000_000, &None), 25_000);
+
+    // 0 removes the cap
+    client.set_max_absolute_fee(&admin, &0);
+    assert_eq!(client.calculate_fee(&2_000_000, &None), 50_000);
+}
+
 #[test]
 fn test_record_fee_collection() {
     let (e, admin) = setup_env();
@@ -497,6 +555,7 @@ fn test_get_products_by_seller() {
         &100_000_000,
         &10,
         &product_meta,
+        &0,
     );
     client.add_product(
         &seller,
@@ -506,6 +565,7 @@ fn test_get_products_by_seller() {
         &150_000_000,
         &5,
         &product_meta,
+        &0,
     );
 
     let products = client.get_products_by_seller(&seller);
@@ -542,6 +602,7 @@ fn test_get_products_by_category() {
         &100_000_000,
         &10,
         &product_meta,
+        &0,
     );
     client.add_product(
         &seller,
@@ -551,6 +612,7 @@ fn test_get_products_by_category() {
         &100_000_000,
         &10,
         &product_meta,
+        &0,
     );
 
     let category_1_products = client.get_products_by_category(&1);
@@ -627,6 +689,7 @@ fn test_complete_marketplace_workflow() {
         &99_999_999,
         &5,
         &laptop_meta,
+        &0,
     );
     assert_eq!(product1_id, 1);
 
@@ -642,6 +705,7 @@ fn test_complete_marketplace_workflow() {
         &49_999_999,
         &20,
         &book_meta,
+        &0,
     );
     assert_eq!(product2_id, 2);
 
@@ -720,7 +784,7 @@ fn test_product_lifecycle() {
     let desc = String::from_str(&e, "Latest smartphone model");
     let meta = String::from_str(&e, "{}");
 
-    let product_id = client.add_product(&seller, &name, &desc, &1, &799_999_999, &50, &meta);
+    let product_id = client.add_product(&seller, &name, &desc, &1, &799_999_999, &50, &meta, &0);
     assert_eq!(product_id, 1);
 
     let product = client.get_product(&product_id);
@@ -1027,3 +1091,1500 @@ fn test_price_history() {
     let history = client.get_price_history(&asset, &10);
     assert_eq!(history.len(), 0);
 }
+
+#[test]
+fn test_price_history_full_keeps_source() {
+    let (e, admin) = setup_env();
+    let asset = Address::generate(&e);
+    let client = initialize_marketplace(&e, &admin);
+
+    e.as_contract(&client.address, || {
+        for (price, timestamp, source) in [
+            (100, 10, PriceSource::Oracle),
+            (101, 20, PriceSource::Cached),
+            (102, 30, PriceSource::Oracle),
+        ] {
+            crate::storage::add_price_record(
+                &e,
+                &asset,
+                &PriceRecord {
+                    price,
+                    timestamp,
+                    source,
+                },
+            );
+        }
+    });
+
+    let full = client.get_price_history_full(&asset, &2);
+    assert_eq!(full.len(), 2);
+    assert_eq!(full.get(0).unwrap().price, 101);
+    assert_eq!(full.get(0).unwrap().source, PriceSource::Cached);
+    assert_eq!(full.get(1).unwrap().source, PriceSource::Oracle);
+
+    // The tuple form returns the same window without sources
+    let simple = client.get_price_history(&asset, &2);
+    assert_eq!(simple.get(0).unwrap(), (101, 20));
+    assert_eq!(client.get_price_history_full(&asset, &10).len(), 3);
+}
+
+#[test]
+fn test_low_stock_event_fires_once_on_crossing() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let (seller, product_id) = setup_product(&e, &client, &admin, 10);
+
+    client.set_low_stock_threshold(&seller, &product_id, &5);
+    assert_eq!(client.get_low_stock_threshold(&product_id), 5);
+
+    let low_stock = |remaining: u64| {
+        LowStockEventData {
+            product_id,
+            remaining,
+        }
+        .to_xdr(&e, &client.address)
+    };
+
+    // Still above the threshold
+    client.update_product(&seller, &product_id, &0, &7, &0);
+    assert!(!e.events().all().events().contains(&low_stock(7)));
+
+    // Crosses below the threshold
+    client.update_product(&seller, &product_id, &0, &3, &0);
+    assert!(e.events().all().events().contains(&low_stock(3)));
+
+    // Already below: no repeated alert
+    client.update_product(&seller, &product_id, &0, &2, &0);
+    assert!(!e.events().all().events().contains(&low_stock(2)));
+}
+
+#[test]
+fn test_seller_vacation_hides_listings() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let (seller, product_id) = setup_product(&e, &client, &admin, 10);
+
+    client.set_seller_vacation(&seller, &true);
+    assert!(client.get_seller(&seller).on_vacation);
+    assert_eq!(client.get_products_by_price_range(&0, &u128::MAX, &0, &10).len(), 0);
+
+    // Stored product status is untouched
+    let product = client.get_product(&product_id);
+    assert_eq!(product.status, ProductStatus::Active);
+
+    client.set_seller_vacation(&seller, &false);
+    let listed = client.get_products_by_price_range(&0, &u128::MAX, &0, &10);
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed.get(0).unwrap().id, product_id);
+}
+
+#[test]
+fn test_product_listing_expiry() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let (seller, _) = setup_product(&e, &client, &admin, 10);
+
+    let product_id = client.add_product(
+        &seller,
+        &String::from_str(&e, "Phone"),
+        &String::from_str(&e, "Seasonal listing"),
+        &1,
+        &100_000_000,
+        &5,
+        &String::from_str(&e, "{}"),
+        &100,
+    );
+    assert_eq!(client.get_products_by_price_range(&0, &u128::MAX, &0, &10).len(), 2);
+
+    // Not expired yet
+    assert_eq!(
+        client.try_expire_product(&product_id),
+        Err(Ok(Error::InvalidProductStatus))
+    );
+    assert_eq!(
+        client.try_relist_product(&seller, &product_id, &0),
+        Err(Ok(Error::InvalidProductStatus))
+    );
+
+    e.ledger().with_mut(|li| li.timestamp = 100);
+    let listed = client.get_products_by_price_range(&0, &u128::MAX, &0, &10);
+    assert_eq!(listed.len(), 1);
+    assert!(listed.iter().all(|p| p.id != product_id));
+
+    client.expire_product(&product_id);
+    assert_eq!(client.get_product(&product_id).status, ProductStatus::Delisted);
+
+    // A sold-out listing can't come back until it is restocked
+    client.set_stock_batch(&seller, &vec![&e, (product_id, 0u64)]);
+    assert_eq!(
+        client.try_relist_product(&seller, &product_id, &0),
+        Err(Ok(Error::OutOfStock))
+    );
+    client.set_stock_batch(&seller, &vec![&e, (product_id, 5u64)]);
+
+    // Suspended sellers can't bring listings back, just as they can't add them
+    client.suspend_seller(&admin, &seller);
+    assert_eq!(
+        client.try_relist_product(&seller, &product_id, &0),
+        Err(Ok(Error::SellerSuspended))
+    );
+    assert_eq!(client.get_product(&product_id).status, ProductStatus::Delisted);
+    client.unsuspend_seller(&admin, &seller);
+
+    client.relist_product(&seller, &product_id, &0);
+    let product = client.get_product(&product_id);
+    assert_eq!(product.status, ProductStatus::Active);
+    assert_eq!(product.expires_at, 0);
+    assert_eq!(client.get_products_by_price_range(&0, &u128::MAX, &0, &10).len(), 2);
+
+    // Already live again
+    assert_eq!(
+        client.try_relist_product(&seller, &product_id, &0),
+        Err(Ok(Error::InvalidProductStatus))
+    );
+}
+
+#[test]
+fn test_search_products_by_name() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let (seller, laptop_id) = setup_product(&e, &client, &admin, 10);
+    client.create_category(
+        &admin,
+        &2,
+        &String::from_str(&e, "Accessories"),
+        &String::from_str(&e, "Add-ons"),
+        &300,
+    );
+
+    let add = |name: &str, category_id: u32| {
+        client.add_product(
+            &seller,
+            &String::from_str(&e, name),
+            &String::from_str(&e, "desc"),
+            &category_id,
+            &50_000_000,
+            &5,
+            &String::from_str(&e, "{}"),
+            &0,
+        )
+    };
+    let gaming_id = add("Gaming Laptop", 1);
+    let bag_id = add("Laptop Bag", 2);
+    add("Phone", 1);
+    add("laptop stand", 2);
+
+    let ids = |products: Vec<Product>| {
+        let mut out = Vec::new(&e);
+        for p in products.iter() {
+            out.push_back(p.id);
+        }
+        out
+    };
+
+    let query = String::from_str(&e, "Laptop");
+    let found = ids(client.search_products_by_name(&query, &0, &10));
+    assert_eq!(found, Vec::from_array(&e, [laptop_id, gaming_id, bag_id]));
+
+    let page = ids(client.search_products_by_name(&query, &1, &1));
+    assert_eq!(page, Vec::from_array(&e, [gaming_id]));
+
+    // Delisted products drop out
+    client.delist_product(&seller, &gaming_id);
+    let found = ids(client.search_products_by_name(&query, &0, &10));
+    assert_eq!(found, Vec::from_array(&e, [laptop_id, bag_id]));
+
+    assert_eq!(
+        client.try_search_products_by_name(&query, &0, &0),
+        Err(Ok(Error::InvalidInput))
+    );
+    assert_eq!(
+        client.try_search_products_by_name(&query, &0, &101),
+        Err(Ok(Error::InvalidInput))
+    );
+}
+
+#[test]
+fn test_search_sellers() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+
+    let register = |metadata: &str| {
+        let seller = Address::generate(&e);
+        client.register_seller(&seller, &String::from_str(&e, metadata));
+        seller
+    };
+    let books = register("Acme Books");
+    register("Garden Supplies");
+    let tools = register("Acme Tools");
+    let shady = register("Acme Outlet");
+    client.suspend_seller(&admin, &shady);
+
+    let addresses = |sellers: Vec<Seller>| {
+        let mut out = Vec::new(&e);
+        for s in sellers.iter() {
+            out.push_back(s.address);
+        }
+        out
+    };
+
+    let query = String::from_str(&e, "Acme");
+    let found = addresses(client.search_sellers(&query, &0, &10));
+    assert_eq!(found, Vec::from_array(&e, [books.clone(), tools.clone()]));
+
+    let page = addresses(client.search_sellers(&query, &1, &1));
+    assert_eq!(page, Vec::from_array(&e, [tools]));
+
+    let none = client.search_sellers(&String::from_str(&e, "acme"), &0, &10);
+    assert_eq!(none.len(), 0);
+
+    assert_eq!(
+        client.try_search_sellers(&String::from_str(&e, ""), &0, &10),
+        Err(Ok(Error::InvalidInput))
+    );
+    assert_eq!(
+        client.try_search_sellers(&query, &0, &101),
+        Err(Ok(Error::InvalidInput))
+    );
+}
+
+#[test]
+fn test_paged_product_indexes() {
+    let (e, admin) = setup_env();
+    e.cost_estimate().budget().reset_unlimited();
+    let client = initialize_marketplace(&e, &admin);
+    let (seller, first_id) = setup_product(&e, &client, &admin, 10);
+
+    for _ in 1..150 {
+        client.add_product(
+            &seller,
+            &String::from_str(&e, "Widget"),
+            &String::from_str(&e, "desc"),
+            &1,
+            &1_000,
+            &1,
+            &String::from_str(&e, "{}"),
+            &0,
+        );
+    }
+
+    let page = client.get_products_by_seller_paged(&seller, &0, &100);
+    assert_eq!(page.len(), 100);
+    assert_eq!(page.get(0).unwrap(), first_id);
+
+    let tail = client.get_products_by_seller_paged(&seller, &100, &100);
+    assert_eq!(tail.len(), 50);
+    assert_eq!(tail.get(0).unwrap(), first_id + 100);
+    assert_eq!(tail.get(49).unwrap(), first_id + 149);
+
+    let window = client.get_products_by_category_paged(&1, &140, &5);
+    assert_eq!(window.len(), 5);
+    assert_eq!(window.get(0).unwrap(), first_id + 140);
+
+    assert_eq!(client.get_products_by_category_paged(&1, &150, &10).len(), 0);
+    assert_eq!(
+        client.try_get_products_by_seller_paged(&seller, &0, &101),
+        Err(Ok(Error::InvalidInput))
+    );
+    assert_eq!(
+        client.try_get_products_by_category_paged(&1, &0, &0),
+        Err(Ok(Error::InvalidInput))
+    );
+}
+
+/// Stand-in for the access-control multisig: proposals are bound to an
+/// action and target and need two approvals.
+#[contract]
+struct MockMultisig;
+
+#[contractimpl]
+impl MockMultisig {
+    pub fn create(e: Env, proposal_id: u64, action: Symbol, target: Address) {
+        e.storage()
+            .instance()
+            .set(&proposal_id, &(action, target, 0u32));
+    }
+
+    pub fn approve(e: Env, proposal_id: u64) {
+        let (action, target, count): (Symbol, Address, u32) =
+            e.storage().instance().get(&proposal_id).unwrap();
+        e.storage()
+            .instance()
+            .set(&proposal_id, &(action, target, count + 1));
+    }
+
+    pub fn is_proposal_approved(e: Env, proposal_id: u64, action: Symbol, target: Address) -> bool {
+        match e
+            .storage()
+            .instance()
+            .get::<_, (Symbol, Address, u32)>(&proposal_id)
+        {
+            Some((a, t, count)) => a == action && t == target && count >= 2,
+            None => false,
+        }
+    }
+}
+
+#[test]
+fn test_transfer_admin_without_multisig() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let new_admin = Address::generate(&e);
+
+    assert_eq!(
+        client.try_transfer_admin(&new_admin, &new_admin, &None),
+        Err(Ok(Error::Unauthorized))
+    );
+
+    client.transfer_admin(&admin, &new_admin, &None);
+    assert_eq!(client.get_config().admin, new_admin);
+    assert_eq!(
+        client.try_set_fee_rate(&admin, &100),
+        Err(Ok(Error::Unauthorized))
+    );
+    client.set_fee_rate(&new_admin, &100);
+}
+
+#[test]
+fn test_transfer_admin_requires_multisig_approval() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let multisig_id = e.register(MockMultisig, ());
+    let multisig = MockMultisigClient::new(&e, &multisig_id);
+    client.set_admin_multisig(&admin, &Some(multisig_id.clone()));
+    assert_eq!(client.get_admin_multisig(), Some(multisig_id));
+
+    let new_admin = Address::generate(&e);
+    assert_eq!(
+        client.try_transfer_admin(&admin, &new_admin, &None),
+        Err(Ok(Error::MultisigNotApproved))
+    );
+
+    multisig.create(&7, &ACTION_TRANSFER_ADMIN, &new_admin);
+    multisig.approve(&7);
+    assert_eq!(
+        client.try_transfer_admin(&admin, &new_admin, &Some(7)),
+        Err(Ok(Error::MultisigNotApproved))
+    );
+    assert_eq!(client.get_config().admin, admin);
+
+    multisig.approve(&7);
+    // An approved proposal only covers the target and action it was made for
+    assert_eq!(
+        client.try_transfer_admin(&admin, &Address::generate(&e), &Some(7)),
+        Err(Ok(Error::MultisigNotApproved))
+    );
+    multisig.create(&8, &symbol_short!("pause"), &new_admin);
+    multisig.approve(&8);
+    multisig.approve(&8);
+    assert_eq!(
+        client.try_transfer_admin(&admin, &new_admin, &Some(8)),
+        Err(Ok(Error::MultisigNotApproved))
+    );
+
+    client.transfer_admin(&admin, &new_admin, &Some(7));
+    assert!(e.events().all().events().contains(
+        &crate::events::AdminTransferredEventData {
+            old_admin: admin.clone(),
+            new_admin: new_admin.clone(),
+        }
+        .to_xdr(&e, &client.address)
+    ));
+    assert_eq!(client.get_config().admin, new_admin);
+
+    // An approved proposal cannot be replayed for a second transfer
+    assert_eq!(
+        client.try_transfer_admin(&new_admin, &admin, &Some(7)),
+        Err(Ok(Error::MultisigNotApproved))
+    );
+}
+
+#[test]
+fn test_purchase_product_partial_stock() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let (seller, product_id) = setup_product(&e, &client, &admin, 10);
+    let buyer = Address::generate(&e);
+
+    client.purchase_product(&buyer, &product_id, &3);
+    assert!(e.events().all().events().contains(
+        &crate::events::ProductPurchasedEventData {
+            buyer: buyer.clone(),
+            seller: seller.clone(),
+            product_id,
+            quantity: 3,
+        }
+        .to_xdr(&e, &client.address)
+    ));
+
+    let product = client.get_product(&product_id);
+    assert_eq!(product.stock_quantity, 7);
+    assert_eq!(product.purchase_count, 3);
+    assert_eq!(product.status, ProductStatus::Active);
+
+    let seller_data = client.get_seller(&seller);
+    assert_eq!(seller_data.total_sales, 3);
+    assert_eq!(seller_data.total_revenue, 300_000_000);
+
+    assert_eq!(
+        client.try_purchase_product(&buyer, &product_id, &8),
+        Err(Ok(Error::OutOfStock))
+    );
+    assert_eq!(
+        client.try_purchase_product(&buyer, &product_id, &0),
+        Err(Ok(Error::InvalidInput))
+    );
+}
+
+#[test]
+fn test_purchase_product_exact_stock_sells_out() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let (_, product_id) = setup_product(&e, &client, &admin, 5);
+    let buyer = Address::generate(&e);
+
+    client.purchase_product(&buyer, &product_id, &5);
+
+    let product = client.get_product(&product_id);
+    assert_eq!(product.stock_quantity, 0);
+    assert_eq!(product.purchase_count, 5);
+    assert_eq!(product.status, ProductStatus::OutOfStock);
+
+    assert_eq!(
+        client.try_purchase_product(&buyer, &product_id, &1),
+        Err(Ok(Error::InvalidProductStatus))
+    );
+}
+
+#[test]
+fn test_purchase_product_rejected_while_paused() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let (_, product_id) = setup_product(&e, &client, &admin, 5);
+    let buyer = Address::generate(&e);
+
+    client.set_paused(&admin, &true);
+    assert_eq!(
+        client.try_purchase_product(&buyer, &product_id, &1),
+        Err(Ok(Error::MarketplacePaused))
+    );
+    assert_eq!(client.get_product(&product_id).stock_quantity, 5);
+}
+
+#[test]
+fn test_restock_product_reactivates_sold_out() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let (seller, product_id) = setup_product(&e, &client, &admin, 2);
+
+    client.purchase_product(&Address::generate(&e), &product_id, &2);
+    assert_eq!(client.get_product(&product_id).status, ProductStatus::OutOfStock);
+
+    client.restock_product(&seller, &product_id, &5);
+    assert!(e.events().all().events().contains(
+        &crate::events::ProductRestockedEventData {
+            seller: seller.clone(),
+            product_id,
+            stock_quantity: 5,
+        }
+        .to_xdr(&e, &client.address)
+    ));
+
+    let product = client.get_product(&product_id);
+    assert_eq!(product.stock_quantity, 5);
+    assert_eq!(product.status, ProductStatus::Active);
+
+    assert_eq!(
+        client.try_restock_product(&Address::generate(&e), &product_id, &1),
+        Err(Ok(Error::Unauthorized))
+    );
+}
+
+#[test]
+fn test_set_stock_batch() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let (seller, first) = setup_product(&e, &client, &admin, 10);
+    let (name, description, metadata) = (
+        String::from_str(&e, "Mouse"),
+        String::from_str(&e, "Wireless mouse"),
+        String::from_str(&e, "{}"),
+    );
+    let second = client.add_product(
+        &seller, &name, &description, &1, &1_000_000, &5, &metadata, &0,
+    );
+    let third = client.add_product(
+        &seller, &name, &description, &1, &1_000_000, &1, &metadata, &0,
+    );
+    client.purchase_product(&Address::generate(&e), &third, &1);
+
+    client.set_stock_batch(
+        &seller,
+        &vec![&e, (first, 0u64), (second, 8u64), (third, 3u64)],
+    );
+    assert!(e.events().all().events().contains(
+        &crate::events::StockBatchUpdatedEventData {
+            seller: seller.clone(),
+            count: 3,
+        }
+        .to_xdr(&e, &client.address)
+    ));
+
+    let product = client.get_product(&first);
+    assert_eq!((product.stock_quantity, product.status), (0, ProductStatus::OutOfStock));
+    let product = client.get_product(&second);
+    assert_eq!((product.stock_quantity, product.status), (8, ProductStatus::Active));
+    let product = client.get_product(&third);
+    assert_eq!((product.stock_quantity, product.status), (3, ProductStatus::Active));
+
+    // One foreign product reverts the whole batch
+    let other = Address::generate(&e);
+    client.register_seller(&other, &String::from_str(&e, "Other seller"));
+    client.verify_seller(&admin, &other);
+    let foreign = client.add_product(
+        &other, &name, &description, &1, &1_000_000, &4, &metadata, &0,
+    );
+    assert_eq!(
+        client.try_set_stock_batch(&seller, &vec![&e, (second, 1u64), (foreign, 0u64)]),
+        Err(Ok(Error::Unauthorized))
+    );
+    assert_eq!(client.get_product(&second).stock_quantity, 8);
+    assert_eq!(client.get_product(&foreign).stock_quantity, 4);
+
+    assert_eq!(
+        client.try_set_stock_batch(&seller, &Vec::new(&e)),
+        Err(Ok(Error::InvalidInput))
+    );
+}
+
+#[test]
+fn test_restock_product_rejects_overflow() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let (seller, product_id) = setup_product(&e, &client, &admin, 10);
+
+    assert_eq!(
+        client.try_restock_product(&seller, &product_id, &u64::MAX),
+        Err(Ok(Error::InvalidInput))
+    );
+    assert_eq!(client.get_product(&product_id).stock_quantity, 10);
+}
+
+#[test]
+fn test_update_seller_metadata() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let seller = Address::generate(&e);
+    client.register_seller(&seller, &String::from_str(&e, "Old shop"));
+
+    // Unverified sellers may edit their storefront
+    client.update_seller_metadata(&seller, &String::from_str(&e, "New shop"));
+    assert_eq!(
+        client.get_seller(&seller).metadata,
+        String::from_str(&e, "New shop")
+    );
+
+    assert_eq!(
+        client.try_update_seller_metadata(&seller, &String::from_str(&e, "")),
+        Err(Ok(Error::InvalidMetadata))
+    );
+
+    client.suspend_seller(&admin, &seller);
+    assert_eq!(
+        client.try_update_seller_metadata(&seller, &String::from_str(&e, "Hidden")),
+        Err(Ok(Error::SellerSuspended))
+    );
+    assert_eq!(
+        client.get_seller(&seller).metadata,
+        String::from_str(&e, "New shop")
+    );
+}
+
+#[test]
+fn test_two_step_admin_transfer() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let new_admin = Address::generate(&e);
+    let stranger = Address::generate(&e);
+
+    client.propose_admin_transfer(&admin, &new_admin);
+    assert!(e.events().all().events().contains(
+        &crate::events::AdminProposedEventData {
+            admin: admin.clone(),
+            pending_admin: new_admin.clone(),
+        }
+        .to_xdr(&e, &client.address)
+    ));
+    assert_eq!(client.get_pending_admin(), Some(new_admin.clone()));
+    // Proposing alone does not hand over control
+    assert_eq!(client.get_config().admin, admin);
+
+    assert_eq!(
+        client.try_accept_admin_transfer(&stranger),
+        Err(Ok(Error::Unauthorized))
+    );
+
+    client.accept_admin_transfer(&new_admin);
+    assert!(e.events().all().events().contains(
+        &crate::events::AdminTransferredEventData {
+            old_admin: admin.clone(),
+            new_admin: new_admin.clone(),
+        }
+        .to_xdr(&e, &client.address)
+    ));
+    assert_eq!(client.get_config().admin, new_admin);
+    assert_eq!(client.get_pending_admin(), None);
+
+    assert_eq!(
+        client.try_accept_admin_transfer(&new_admin),
+        Err(Ok(Error::Unauthorized))
+    );
+    assert_eq!(
+        client.try_propose_admin_transfer(&admin, &stranger),
+        Err(Ok(Error::Unauthorized))
+    );
+}
+
+/// Stand-in for the reputation contract's tier lookup.
+#[contract]
+struct MockReputation;
+
+#[contractimpl]
+impl MockReputation {
+    pub fn set_score(e: Env, user: Address, score: u32) {
+        e.storage().instance().set(&(user, 0u32), &score);
+    }
+
+    pub fn get_score_scaled(e: Env, user: Address, scale: u32) -> u32 {
+        let score: u32 = e.storage().instance().get(&(user, 0u32)).unwrap_or(0);
+        score * scale / 500
+    }
+}
+
+#[test]
+fn test_calculate_fee_for_seller_tier_discount() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let seller = Address::generate(&e);
+    client.register_seller(&seller, &String::from_str(&e, "Shop"));
+    client.verify_seller(&admin, &seller);
+
+    // No reputation contract: same as calculate_fee (2.5% base rate)
+    assert_eq!(client.calculate_fee_for_seller(&10_000, &None, &seller), 250);
+
+    let reputation_id = e.register(MockReputation, ());
+    let reputation = MockReputationClient::new(&e, &reputation_id);
+    client.set_reputation_contract(&admin, &Some(reputation_id));
+    client.set_tier_discount(&admin, &2, &2000);
+    client.set_tier_discount(&admin, &3, &3000);
+    assert_eq!(
+        client.try_set_tier_discount(&admin, &4, &10_001),
+        Err(Ok(Error::InvalidInput))
+    );
+
+    assert_eq!(client.calculate_fee_for_seller(&10_000, &None, &seller), 250);
+    reputation.set_score(&seller, &200);
+    assert_eq!(client.calculate_fee_for_seller(&10_000, &None, &seller), 200);
+    reputation.set_score(&seller, &300);
+    assert_eq!(client.calculate_fee_for_seller(&10_000, &None, &seller), 175);
+
+    // Discounts only apply to verified sellers
+    client.suspend_seller(&admin, &seller);
+    assert_eq!(client.calculate_fee_for_seller(&10_000, &None, &seller), 250);
+}
+
+#[test]
+fn test_calculate_fee_for_seller_with_reputation_contract() {
+    use marketx_reputation::{ReputationContract, ReputationContractClient};
+
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let seller = Address::generate(&e);
+    client.register_seller(&seller, &String::from_str(&e, "Shop"));
+    client.verify_seller(&admin, &seller);
+
+    let reputation_id = e.register(ReputationContract, ());
+    let reputation = ReputationContractClient::new(&e, &reputation_id);
+    reputation.initialize(&admin);
+    client.set_reputation_contract(&admin, &Some(reputation_id));
+    client.set_tier_discount(&admin, &4, &2000);
+
+    // Unreviewed sellers sit at tier 0
+    assert_eq!(client.calculate_fee_for_seller(&10_000, &None, &seller), 250);
+
+    for rating in [4u32, 4, 5, 3] {
+        let comment = String::from_str(&e, "Good seller");
+        reputation.submit_review(&Address::generate(&e), &seller, &rating, &comment);
+    }
+    assert_eq!(reputation.get_score_scaled(&seller, &5), 4);
+    assert_eq!(client.calculate_fee_for_seller(&10_000, &None, &seller), 200);
+}
+
+#[test]
+fn test_withdraw_fees() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let treasury = Address::generate(&e);
+
+    let token = e.register_stellar_asset_contract_v2(Address::generate(&e));
+    let token_client = soroban_sdk::token::Client::new(&e, &token.address());
+    soroban_sdk::token::StellarAssetClient::new(&e, &token.address())
+        .mint(&client.address, &1_000);
+    client.record_fee_collection_for_asset(&admin, &token.address(), &1_000);
+
+    client.withdraw_fees(&admin, &token.address(), &treasury, &600);
+    assert!(e.events().all().events().contains(
+        &crate::events::FeesWithdrawnEventData {
+            to: treasury.clone(),
+            amount: 600,
+        }
+        .to_xdr(&e, &client.address)
+    ));
+    assert_eq!(token_client.balance(&treasury), 600);
+    assert_eq!(token_client.balance(&client.address), 400);
+    assert_eq!(client.get_total_fees(), 400);
+    assert_eq!(client.get_total_fees_for_asset(&token.address()), 400);
+
+    assert_eq!(
+        client.try_withdraw_fees(&admin, &token.address(), &treasury, &401),
+        Err(Ok(Error::InsufficientBalance))
+    );
+    assert_eq!(
+        client.try_withdraw_fees(&treasury, &token.address(), &treasury, &100),
+        Err(Ok(Error::Unauthorized))
+    );
+    assert_eq!(token_client.balance(&treasury), 600);
+}
+
+#[test]
+fn test_withdraw_fees_limited_to_asset() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let treasury = Address::generate(&e);
+
+    let usdc = e.register_stellar_asset_contract_v2(Address::generate(&e));
+    let bond_token = e.register_stellar_asset_contract_v2(Address::generate(&e));
+    soroban_sdk::token::StellarAssetClient::new(&e, &usdc.address())
+        .mint(&client.address, &500);
+    // Held for seller bonds, never recorded as fees
+    soroban_sdk::token::StellarAssetClient::new(&e, &bond_token.address())
+        .mint(&client.address, &1_000);
+    client.record_fee_collection_for_asset(&admin, &usdc.address(), &500);
+
+    // Fees booked in one asset can't be drawn in another
+    assert_eq!(
+        client.try_withdraw_fees(&admin, &bond_token.address(), &treasury, &100),
+        Err(Ok(Error::InsufficientBalance))
+    );
+    assert_eq!(
+        client.try_withdraw_fees(&admin, &usdc.address(), &treasury, &501),
+        Err(Ok(Error::InsufficientBalance))
+    );
+
+    client.withdraw_fees(&admin, &usdc.address(), &treasury, &500);
+    let bond_client = soroban_sdk::token::Client::new(&e, &bond_token.address());
+    assert_eq!(bond_client.balance(&client.address), 1_000);
+    assert_eq!(client.get_total_fees(), 0);
+}
+
+#[test]
+fn test_seller_bond_refund_and_forfeit() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let token = e.register_stellar_asset_contract_v2(Address::generate(&e));
+    let token_client = soroban_sdk::token::Client::new(&e, &token.address());
+    let token_admin = soroban_sdk::token::StellarAssetClient::new(&e, &token.address());
+    client.set_seller_bond(&admin, &token.address(), &500);
+
+    let good = Address::generate(&e);
+    let bad = Address::generate(&e);
+    token_admin.mint(&good, &500);
+    token_admin.mint(&bad, &500);
+
+    client.register_seller(&good, &String::from_str(&e, "Good shop"));
+    client.register_seller(&bad, &String::from_str(&e, "Bad shop"));
+    assert_eq!(client.get_seller(&good).bond, 500);
+    assert_eq!(token_client.balance(&good), 0);
+    assert_eq!(token_client.balance(&client.address), 1_000);
+
+    // Leaving in good standing refunds the bond
+    client.deregister_seller(&good);
+    assert_eq!(token_client.balance(&good), 500);
+    assert_eq!(
+        client.try_get_seller(&good),
+        Err(Ok(Error::SellerNotFound))
+    );
+    assert_eq!(client.get_config().total_sellers, 1);
+
+    // A forfeited bond goes to the admin's chosen recipient, not back to the seller
+    let treasury = Address::generate(&e);
+    client.suspend_seller(&admin, &bad);
+    client.forfeit_seller_bond(&admin, &bad, &treasury);
+    assert_eq!(token_client.balance(&treasury), 500);
+    assert_eq!(client.get_seller(&bad).bond, 0);
+    assert_eq!(
+        client.try_deregister_seller(&bad),
+        Err(Ok(Error::SellerSuspended))
+    );
+    assert_eq!(
+        client.try_forfeit_seller_bond(&admin, &bad, &treasury),
+        Err(Ok(Error::InsufficientBalance))
+    );
+}
+
+#[test]
+fn test_zero_seller_bond_disables_it() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let token = e.register_stellar_asset_contract_v2(Address::generate(&e));
+    client.set_seller_bond(&admin, &token.address(), &0);
+    assert_eq!(client.get_seller_bond(), None);
+
+    let seller = Address::generate(&e);
+    client.register_seller(&seller, &String::from_str(&e, "Shop"));
+    assert_eq!(client.get_seller(&seller).bond, 0);
+    client.deregister_seller(&seller);
+    assert_eq!(client.get_sellers(&0, &100).len(), 0);
+}
+
+#[test]
+fn test_fees_tracked_per_asset() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let usdc = Address::generate(&e);
+    let xlm = Address::generate(&e);
+
+    client.record_fee_collection_for_asset(&admin, &usdc, &300);
+    client.record_fee_collection_for_asset(&admin, &xlm, &50);
+    client.record_fee_collection_for_asset(&admin, &usdc, &200);
+
+    assert_eq!(client.get_total_fees_for_asset(&usdc), 500);
+    assert_eq!(client.get_total_fees_for_asset(&xlm), 50);
+    assert_eq!(client.get_total_fees_for_asset(&Address::generate(&e)), 0);
+    // The legacy total aggregates every asset
+    assert_eq!(client.get_total_fees(), 550);
+
+    assert_eq!(
+        client.try_record_fee_collection_for_asset(&usdc, &usdc, &1),
+        Err(Ok(Error::Unauthorized))
+    );
+}
+
+#[test]
+fn test_registered_fee_reporter_records_fees() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let escrow = Address::generate(&e);
+    let usdc = Address::generate(&e);
+
+    assert_eq!(
+        client.try_record_fee_collection_for_asset(&escrow, &usdc, &25),
+        Err(Ok(Error::Unauthorized))
+    );
+
+    // Only the admin can register reporters
+    assert_eq!(
+        client.try_set_fee_reporter(&escrow, &escrow, &true),
+        Err(Ok(Error::Unauthorized))
+    );
+    client.set_fee_reporter(&admin, &escrow, &true);
+    assert!(client.is_fee_reporter(&escrow));
+
+    client.record_fee_collection_for_asset(&escrow, &usdc, &25);
+    assert_eq!(client.get_total_fees_for_asset(&usdc), 25);
+    assert_eq!(client.get_total_fees(), 25);
+
+    client.set_fee_reporter(&admin, &escrow, &false);
+    assert!(!client.is_fee_reporter(&escrow));
+    assert_eq!(
+        client.try_record_fee_collection_for_asset(&escrow, &usdc, &25),
+        Err(Ok(Error::Unauthorized))
+    );
+}
+
+#[test]
+fn test_inactive_category_blocks_listings() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let (seller, _) = setup_product(&e, &client, &admin, 10);
+    let list = || {
+        client.try_add_product(
+            &seller,
+            &String::from_str(&e, "Phone"),
+            &String::from_str(&e, "A phone"),
+            &1,
+            &1_000,
+            &1,
+            &String::from_str(&e, "{}"),
+            &0,
+        )
+    };
+
+    client.set_category_active(&admin, &1, &false);
+    assert!(!client.get_category(&1).is_active);
+    assert_eq!(list(), Err(Ok(Error::CategoryInactive)));
+
+    client.set_category_active(&admin, &1, &true);
+    assert!(list().is_ok());
+
+    assert_eq!(
+        client.try_set_category_active(&seller, &1, &false),
+        Err(Ok(Error::Unauthorized))
+    );
+    assert_eq!(
+        client.try_set_category_active(&admin, &99, &false),
+        Err(Ok(Error::CategoryNotFound))
+    );
+}
+
+#[test]
+fn test_seller_rating_cache() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let seller = Address::generate(&e);
+    client.register_seller(&seller, &String::from_str(&e, "Shop"));
+    e.ledger().set_timestamp(1_000);
+
+    assert_eq!(
+        client.try_sync_seller_rating(&seller),
+        Err(Ok(Error::ReputationNotConfigured))
+    );
+
+    let reputation_id = e.register(MockReputation, ());
+    let reputation = MockReputationClient::new(&e, &reputation_id);
+    client.set_reputation_contract(&admin, &Some(reputation_id));
+    client.set_rating_sync_interval(&admin, &3_600);
+
+    reputation.set_score(&seller, &400);
+    assert_eq!(client.sync_seller_rating(&seller), 400);
+    assert_eq!(client.get_seller(&seller).rating_synced_at, 1_000);
+
+    // Within the interval the cached rating is reused
+    reputation.set_score(&seller, &200);
+    e.ledger().set_timestamp(4_599);
+    assert_eq!(client.get_seller_rating(&seller), 400);
+    assert_eq!(client.get_seller(&seller).rating, 400);
+
+    // Once stale, a gated read refreshes the cache
+    e.ledger().set_timestamp(4_600);
+    assert_eq!(client.get_seller_rating(&seller), 200);
+    let cached = client.get_seller(&seller);
+    assert_eq!(cached.rating, 200);
+    assert_eq!(cached.rating_synced_at, 4_600);
+}
+
+#[test]
+fn test_fee_tier_uses_cached_rating() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let seller = Address::generate(&e);
+    client.register_seller(&seller, &String::from_str(&e, "Shop"));
+    client.verify_seller(&admin, &seller);
+    e.ledger().set_timestamp(1_000);
+
+    let reputation_id = e.register(MockReputation, ());
+    let reputation = MockReputationClient::new(&e, &reputation_id);
+    client.set_reputation_contract(&admin, &Some(reputation_id));
+    client.set_rating_sync_interval(&admin, &3_600);
+    client.set_tier_discount(&admin, &2, &2000);
+    client.set_tier_discount(&admin, &3, &3000);
+
+    reputation.set_score(&seller, &300);
+    assert_eq!(client.calculate_fee_for_seller(&10_000, &None, &seller), 175);
+    assert_eq!(client.get_seller(&seller).rating_synced_at, 1_000);
+
+    // Within the interval the fee path reuses the cached rating
+    reputation.set_score(&seller, &200);
+    e.ledger().set_timestamp(4_599);
+    assert_eq!(client.calculate_fee_for_seller(&10_000, &None, &seller), 175);
+
+    e.ledger().set_timestamp(4_600);
+    assert_eq!(client.calculate_fee_for_seller(&10_000, &None, &seller), 200);
+    assert_eq!(client.get_seller(&seller).rating, 200);
+}
+
+/// Stand-in for a Reflector oracle quoting Stellar assets with 7 decimals.
+#[contract]
+struct MockOracle;
+
+#[contractimpl]
+impl MockOracle {
+    pub fn set_price(e: Env, asset: Address, price: i128) {
+        e.storage().instance().set(&asset, &price);
+    }
+
+    pub fn set_cross_price(e: Env, base: Address, quote: Address, price: i128) {
+        e.storage().instance().set(&(base, quote), &price);
+    }
+
+    pub fn decimals(_e: Env) -> u32 {
+        7
+    }
+
+    pub fn lastprice(
+        e: Env,
+        asset: crate::reflector::Asset,
+    ) -> Option<crate::reflector::PriceData> {
+        let crate::reflector::Asset::Stellar(address) = asset else {
+            return None;
+        };
+        let price: i128 = e.storage().instance().get(&address)?;
+        Some(crate::reflector::PriceData {
+            price,
+            timestamp: e.ledger().timestamp(),
+        })
+    }
+
+    pub fn x_last_price(
+        e: Env,
+        base_asset: crate::reflector::Asset,
+        quote_asset: crate::reflector::Asset,
+    ) -> Option<crate::reflector::PriceData> {
+        let (crate::reflector::Asset::Stellar(base), crate::reflector::Asset::Stellar(quote)) =
+            (base_asset, quote_asset)
+        else {
+            return None;
+        };
+        let price: i128 = e.storage().instance().get(&(base, quote))?;
+        Some(crate::reflector::PriceData {
+            price,
+            timestamp: e.ledger().timestamp(),
+        })
+    }
+}
+
+fn setup_mock_oracle<'a>(
+    e: &'a Env,
+    client: &MarketXClient,
+    admin: &Address,
+) -> MockOracleClient<'a> {
+    let oracle_id = e.register(MockOracle, ());
+    client.configure_oracle(admin, &oracle_id, &Address::generate(e), &300, &0, &2000, &0);
+    MockOracleClient::new(e, &oracle_id)
+}
+
+#[test]
+fn test_convert_price_cross_uses_direct_quote() {
+    let (e, admin) = setup_env();
+    e.ledger().set_timestamp(10_000);
+    let client = initialize_marketplace(&e, &admin);
+    let oracle = setup_mock_oracle(&e, &client, &admin);
+    let xlm = Address::generate(&e);
+    let usdc = Address::generate(&e);
+
+    // Base prices would give 3 USDC per XLM; the direct quote is 0.1234567
+    oracle.set_price(&xlm, &30_000_000);
+    oracle.set_price(&usdc, &10_000_000);
+    oracle.set_cross_price(&xlm, &usdc, &1_234_567);
+
+    assert_eq!(client.convert_price_cross(&10_000_000_000, &xlm, &usdc), 1_234_567_000);
+}
+
+#[test]
+fn test_convert_price_cross_falls_back_to_base_prices() {
+    let (e, admin) = setup_env();
+    e.ledger().set_timestamp(10_000);
+    let client = initialize_marketplace(&e, &admin);
+    let oracle = setup_mock_oracle(&e, &client, &admin);
+    let xlm = Address::generate(&e);
+    let usdc = Address::generate(&e);
+
+    oracle.set_price(&xlm, &30_000_000);
+    oracle.set_price(&usdc, &10_000_000);
+
+    assert_eq!(client.convert_price_cross(&100, &xlm, &usdc), 300);
+    assert_eq!(
+        client.convert_price_cross(&100, &xlm, &usdc),
+        client.convert_price(&100, &xlm, &usdc)
+    );
+}
+
+#[test]
+fn test_validate_listing_price_emits_rejection_event() {
+    let (e, admin) = setup_env();
+    e.ledger().set_timestamp(10_000);
+    let client = initialize_marketplace(&e, &admin);
+    let (seller, _) = setup_product(&e, &client, &admin, 10);
+    let usdc = Address::generate(&e);
+
+    // Nothing to check against without an oracle
+    assert!(client.validate_listing_price(&seller, &usdc, &200_000_000));
+
+    let oracle = setup_mock_oracle(&e, &client, &admin);
+    oracle.set_price(&usdc, &100_000_000);
+    let rejected = |proposed_price: u128| {
+        crate::events::PriceRejectedEventData {
+            seller: seller.clone(),
+            proposed_price,
+            oracle_price: 100_000_000,
+            tolerance: 2000,
+        }
+        .to_xdr(&e, &client.address)
+    };
+
+    assert!(!client.validate_listing_price(&seller, &usdc, &200_000_000));
+    assert!(e.events().all().events().contains(&rejected(200_000_000)));
+
+    assert!(client.validate_listing_price(&seller, &usdc, &110_000_000));
+    assert!(!e.events().all().events().contains(&rejected(110_000_000)));
+
+    // Listing at the rejected price still fails
+    assert_eq!(
+        client.try_add_product_with_validation(
+            &seller,
+            &String::from_str(&e, "Phone"),
+            &String::from_str(&e, "Overpriced phone"),
+            &1,
+            &200_000_000,
+            &5,
+            &String::from_str(&e, "{}"),
+            &Some(usdc.clone()),
+            &0,
+        ),
+        Err(Ok(Error::PriceOutOfRange))
+    );
+    client.add_product_with_validation(
+        &seller,
+        &String::from_str(&e, "Phone"),
+        &String::from_str(&e, "Fairly priced phone"),
+        &1,
+        &110_000_000,
+        &5,
+        &String::from_str(&e, "{}"),
+        &Some(usdc.clone()),
+        &0,
+    );
+}
+
+#[test]
+fn test_add_product_with_validation_uses_category_reference_asset() {
+    let (e, admin) = setup_env();
+    e.ledger().set_timestamp(10_000);
+    let client = initialize_marketplace(&e, &admin);
+    let (seller, _) = setup_product(&e, &client, &admin, 10);
+    let oracle = setup_mock_oracle(&e, &client, &admin);
+    let usdc = Address::generate(&e);
+    oracle.set_price(&usdc, &100_000_000);
+    let (name, description, metadata) = (
+        String::from_str(&e, "Phone"),
+        String::from_str(&e, "Smartphone"),
+        String::from_str(&e, "{}"),
+    );
+
+    // Nothing to validate against yet
+    assert_eq!(
+        client.try_add_product_with_validation(
+            &seller, &name, &description, &1, &100_000_000, &5, &metadata, &None, &0,
+        ),
+        Err(Ok(Error::PaymentAssetNotSupported))
+    );
+
+    assert_eq!(
+        client.try_set_category_reference_asset(&seller, &1, &Some(usdc.clone())),
+        Err(Ok(Error::Unauthorized))
+    );
+    assert_eq!(
+        client.try_set_category_reference_asset(&admin, &9, &Some(usdc.clone())),
+        Err(Ok(Error::CategoryNotFound))
+    );
+    client.set_category_reference_asset(&admin, &1, &Some(usdc.clone()));
+    assert_eq!(client.get_category_reference_asset(&1), Some(usdc.clone()));
+
+    // Prices are now checked against USDC without naming it
+    assert_eq!(
+        client.try_add_product_with_validation(
+            &seller, &name, &description, &1, &200_000_000, &5, &metadata, &None, &0,
+        ),
+        Err(Ok(Error::PriceOutOfRange))
+    );
+    let product_id = client.add_product_with_validation(
+        &seller, &name, &description, &1, &105_000_000, &5, &metadata, &None, &0,
+    );
+    assert_eq!(client.get_product(&product_id).price, 105_000_000);
+
+    client.set_category_reference_asset(&admin, &1, &None);
+    assert_eq!(client.get_category_reference_asset(&1), None);
+}
+
+#[test]
+fn test_update_product_with_validation_rejects_out_of_range_price() {
+    let (e, admin) = setup_env();
+    e.ledger().set_timestamp(10_000);
+    let client = initialize_marketplace(&e, &admin);
+    let (seller, product_id) = setup_product(&e, &client, &admin, 10);
+    let oracle = setup_mock_oracle(&e, &client, &admin);
+    let usdc = Address::generate(&e);
+    oracle.set_price(&usdc, &100_000_000);
+
+    assert_eq!(
+        client.try_update_product_with_validation(&seller, &product_id, &50_000_000, &0, &0, &usdc),
+        Err(Ok(Error::PriceOutOfRange))
+    );
+    assert_eq!(client.get_product(&product_id).price, 100_000_000);
+
+    client.update_product_with_validation(&seller, &product_id, &90_000_000, &0, &0, &usdc);
+    assert_eq!(client.get_product(&product_id).price, 90_000_000);
+}
+
+#[test]
+fn test_get_sellers_paged_and_by_status() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+
+    let mut sellers = Vec::new(&e);
+    for _ in 0..5 {
+        let seller = Address::generate(&e);
+        client.register_seller(&seller, &String::from_str(&e, "Seller"));
+        sellers.push_back(seller);
+    }
+    client.verify_seller(&admin, &sellers.get(1).unwrap());
+    client.verify_seller(&admin, &sellers.get(3).unwrap());
+    client.verify_seller(&admin, &sellers.get(4).unwrap());
+    client.suspend_seller(&admin, &sellers.get(4).unwrap());
+
+    assert_eq!(client.get_sellers(&0, &100), sellers);
+    assert_eq!(client.get_sellers(&3, &10), sellers.slice(3..5));
+    assert_eq!(client.get_sellers(&5, &10).len(), 0);
+
+    let mut verified = Vec::new(&e);
+    verified.push_back(sellers.get(1).unwrap());
+    verified.push_back(sellers.get(3).unwrap());
+    assert_eq!(client.get_sellers_by_status(&1, &0, &100), verified);
+    assert_eq!(client.get_sellers_by_status(&1, &1, &100), verified.slice(1..2));
+
+    let mut unverified = Vec::new(&e);
+    unverified.push_back(sellers.get(0).unwrap());
+    unverified.push_back(sellers.get(2).unwrap());
+    assert_eq!(client.get_sellers_by_status(&0, &0, &100), unverified);
+    assert_eq!(client.get_sellers_by_status(&2, &0, &1).len(), 1);
+
+    assert_eq!(client.try_get_sellers(&0, &101), Err(Ok(Error::InvalidInput)));
+    assert_eq!(
+        client.try_get_sellers_by_status(&3, &0, &10),
+        Err(Ok(Error::InvalidInput))
+    );
+}
+
+#[test]
+fn test_get_top_products_by_category() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let (seller, first) = setup_product(&e, &client, &admin, 10);
+
+    let mut ids = Vec::new(&e);
+    ids.push_back(first);
+    for _ in 0..4 {
+        ids.push_back(client.add_product(
+            &seller,
+            &String::from_str(&e, "Phone"),
+            &String::from_str(&e, "Smartphone"),
+            &1,
+            &50_000_000,
+            &10,
+            &String::from_str(&e, "{}"),
+            &0,
+        ));
+    }
+
+    // Ratings 300, 450, 450, 500, 200; the two 450s are split by purchases
+    client.update_product_rating(&seller, &ids.get(0).unwrap(), &300);
+    client.update_product_rating(&seller, &ids.get(1).unwrap(), &450);
+    client.update_product_rating(&seller, &ids.get(2).unwrap(), &450);
+    client.update_product_rating(&seller, &ids.get(3).unwrap(), &500);
+    client.update_product_rating(&seller, &ids.get(4).unwrap(), &200);
+    client.purchase_product(&Address::generate(&e), &ids.get(2).unwrap(), &2);
+
+    let top = client.get_top_products_by_category(&1, &3);
+    assert_eq!(top.len(), 3);
+    assert_eq!(top.get(0).unwrap().id, ids.get(3).unwrap());
+    assert_eq!(top.get(1).unwrap().id, ids.get(2).unwrap());
+    assert_eq!(top.get(2).unwrap().id, ids.get(1).unwrap());
+
+    // Delisted products drop out of the ranking
+    client.update_product(&seller, &ids.get(3).unwrap(), &0, &0, &1);
+    let top = client.get_top_products_by_category(&1, &10);
+    assert_eq!(top.len(), 4);
+    assert_eq!(top.get(0).unwrap().id, ids.get(2).unwrap());
+    assert_eq!(top.get(3).unwrap().id, ids.get(4).unwrap());
+
+    assert_eq!(
+        client.try_get_top_products_by_category(&1, &0),
+        Err(Ok(Error::InvalidInput))
+    );
+    assert_eq!(
+        client.try_get_top_products_by_category(&9, &5),
+        Err(Ok(Error::CategoryNotFound))
+    );
+}
+
+#[test]
+fn test_price_update_cooldown() {
+    let (e, admin) = setup_env();
+    e.ledger().set_timestamp(1_000);
+    let client = initialize_marketplace(&e, &admin);
+    let (seller, product_id) = setup_product(&e, &client, &admin, 10);
+
+    client.set_price_update_cooldown(&admin, &3_600);
+    assert_eq!(client.get_price_update_cooldown(), 3_600);
+    assert_eq!(
+        client.try_set_price_update_cooldown(&seller, &0),
+        Err(Ok(Error::Unauthorized))
+    );
+
+    client.update_product(&seller, &product_id, &120_000_000, &0, &3);
+    assert_eq!(client.get_product(&product_id).last_price_change, 1_000);
+
+    e.ledger().set_timestamp(2_000);
+    assert_eq!(
+        client.try_update_product(&seller, &product_id, &90_000_000, &0, &3),
+        Err(Ok(Error::PriceUpdateTooSoon))
+    );
+
+    // Stock and status changes are not rate limited
+    client.update_product(&seller, &product_id, &0, &25, &3);
+    client.update_product(&seller, &product_id, &120_000_000, &0, &1);
+
+    e.ledger().set_timestamp(4_600);
+    client.update_product(&seller, &product_id, &90_000_000, &0, &3);
+    let product = client.get_product(&product_id);
+    assert_eq!(product.price, 90_000_000);
+    assert_eq!(product.stock_quantity, 25);
+    assert_eq!(product.last_price_change, 4_600);
+}
+
+#[test]
+fn test_product_sale_price_expires() {
+    let (e, admin) = setup_env();
+    e.ledger().set_timestamp(1_000);
+    let client = initialize_marketplace(&e, &admin);
+    let (seller, product_id) = setup_product(&e, &client, &admin, 10);
+
+    assert_eq!(
+        client.try_set_product_sale(&seller, &product_id, &100_000_000, &2_000),
+        Err(Ok(Error::InvalidInput))
+    );
+    assert_eq!(
+        client.try_set_product_sale(&seller, &product_id, &80_000_000, &1_000),
+        Err(Ok(Error::InvalidInput))
+    );
+    assert_eq!(
+        client.try_set_product_sale(&Address::generate(&e), &product_id, &80_000_000, &2_000),
+        Err(Ok(Error::Unauthorized))
+    );
+
+    client.set_product_sale(&seller, &product_id, &80_000_000, &2_000);
+    let product = client.get_product(&product_id);
+    assert_eq!(product.price, 100_000_000);
+    assert_eq!(product.effective_price(1_999), 80_000_000);
+    assert_eq!(client.get_effective_price(&product_id), 80_000_000);
+
+    client.purchase_product(&Address::generate(&e), &product_id, &1);
+    assert_eq!(client.get_seller(&seller).total_revenue, 80_000_000);
+
+    // The regular price is back once the sale ends
+    e.ledger().set_timestamp(2_000);
+    assert_eq!(client.get_effective_price(&product_id), 100_000_000);
+    client.purchase_product(&Address::generate(&e), &product_id, &1);
+    assert_eq!(client.get_seller(&seller).total_revenue, 180_000_000);
+
+    // A zero sale price cancels a running sale
+    client.set_product_sale(&seller, &product_id, &50_000_000, &5_000);
+    client.set_product_sale(&seller, &product_id, &0, &0);
+    assert_eq!(client.get_product(&product_id).sale_price, None);
+    assert_eq!(client.get_effective_price(&product_id), 100_000_000);
+
+    // Cutting the regular price below a running sale caps the sale at it
+    client.set_product_sale(&seller, &product_id, &80_000_000, &5_000);
+    client.update_product(&seller, &product_id, &60_000_000, &10, &0);
+    assert_eq!(client.get_effective_price(&product_id), 60_000_000);
+}
+
+#[test]
+fn test_get_all_products_sorted_by_price() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let (seller, first) = setup_product(&e, &client, &admin, 10);
+
+    let mut ids = Vec::new(&e);
+    ids.push_back(first);
+    for price in [300_000_000u128, 50_000_000, 200_000_000, 75_000_000] {
+        ids.push_back(client.add_product(
+            &seller,
+            &String::from_str(&e, "Item"),
+            &String::from_str(&e, "Generic item"),
+            &1,
+            &price,
+            &10,
+            &String::from_str(&e, "{}"),
+            &0,
+        ));
+    }
+    // Delisted products are not purchasable
+    client.update_product(&seller, &ids.get(4).unwrap(), &0, &0, &1);
+
+    let prices = |products: Vec<Product>| {
+        let mut prices = Vec::new(&e);
+        for product in products.iter() {
+            prices.push_back(product.price);
+        }
+        prices
+    };
+
+    let page = client.get_all_products(&0, &100, &1);
+    let mut expected = Vec::new(&e);
+    expected.push_back(50_000_000u128);
+    expected.push_back(100_000_000);
+    expected.push_back(200_000_000);
+    expected.push_back(300_000_000);
+    assert_eq!(prices(page), expected);
+
+    let page = client.get_all_products(&0, &100, &2);
+    assert_eq!(page.get(0).unwrap().price, 300_000_000);
+    assert_eq!(page.get(3).unwrap().price, 50_000_000);
+
+    // The window is taken in listing order before sorting
+    let page = client.get_all_products(&1, &2, &1);
+    let mut expected = Vec::new(&e);
+    expected.push_back(50_000_000u128);
+    expected.push_back(300_000_000);
+    assert_eq!(prices(page), expected);
+
+    let newest = client.get_all_products(&0, &100, &0);
+    assert_eq!(newest.get(0).unwrap().id, ids.get(3).unwrap());
+
+    assert_eq!(
+        client.try_get_all_products(&0, &101, &1),
+        Err(Ok(Error::InvalidInput))
+    );
+    assert_eq!(
+        client.try_get_all_products(&0, &10, &4),
+        Err(Ok(Error::InvalidInput))
+    );
+}
+
+#[test]
+fn test_create_category_auto_assigns_sequential_ids() {
+    let (e, admin) = setup_env();
+    let client = initialize_marketplace(&e, &admin);
+    let name = String::from_str(&e, "Books");
+    let description = String::from_str(&e, "Printed and digital books");
+
+    assert_eq!(client.create_category_auto(&admin, &name, &description, &300), 1);
+    assert_eq!(client.create_category_auto(&admin, &name, &description, &300), 2);
+    assert_eq!(client.get_category(&2).commission_rate, 300);
+
+    // Explicitly created IDs are skipped
+    client.create_category(&admin, &3, &name, &description, &300);
+    assert_eq!(client.create_category_auto(&admin, &name, &description, &300), 4);
+
+    let mut ids = Vec::new(&e);
+    for id in [1u32, 2, 3, 4] {
+        ids.push_back(id);
+    }
+    assert_eq!(client.get_categories(), ids);
+
+    assert_eq!(
+        client.try_create_category_auto(&Address::generate(&e), &name, &description, &300),
+        Err(Ok(Error::Unauthorized))
+    );
+}
@@ -19,6 +19,22 @@ pub enum StorageKey {
     PriceHistory(Address),
     ExternalPriceHistory(Symbol),
     LastPriceUpdate,
+    LowStockThreshold(u64),
+    MaxAbsoluteFee,
+    AdminMultisig,
+    ConsumedAdminProposal(u64),
+    PendingAdmin,
+    ReputationContract,
+    TierDiscount(u32),
+    SellerBond,
+    FeesCollectedByAsset(Address),
+    RatingSyncInterval,
+    SellerIndex,
+    PriceUpdateCooldown,
+    CategoryCounter,
+    CategoryIndex,
+    CategoryReferenceAsset(u32),
+    FeeReporter(Address),
 }
 
 #[contracttype]
@@ -87,6 +103,10 @@ pub struct Seller {
     pub total_revenue: u128,
     pub created_at: u64,
     pub metadata: String,
+    pub on_vacation: bool,
+    pub bond: u128,
+    pub bond_token: Option<Address>, // token `bond` was paid in
+    pub rating_synced_at: u64,       // last refresh of `rating` from the reputation contract
 }
 
 #[contracttype]
@@ -104,6 +124,23 @@ pub struct Product {
     pub purchase_count: u64,
     pub created_at: u64,
     pub metadata: String,
+    pub expires_at: u64,        // 0 = never expires
+    pub last_price_change: u64, // 0 = price never changed
+    pub sale_price: Option<u128>,
+    pub sale_ends_at: Option<u64>,
+}
+
+impl Product {
+    /// Price a buyer pays at `now`: the sale price while a sale runs, otherwise `price`
+    ///
+    /// A sale never charges more than `price`, even if the regular price was
+    /// cut below it after the sale was set.
+    pub fn effective_price(&self, now: u64) -> u128 {
+        match (self.sale_price, self.sale_ends_at) {
+            (Some(sale_price), Some(ends_at)) if now < ends_at => sale_price.min(self.price),
+            _ => self.price,
+        }
+    }
 }
 
 #[contracttype]
@@ -150,6 +187,7 @@ pub struct OracleConfig {
     pub price_tolerance: u32,
     pub update_frequency: u64,
     pub is_enabled: bool,
+    pub backup_stellar_oracle: Option<Address>, // cross-checked by robust price queries
 }
 
 #[contracttype]